@@ -26,10 +26,26 @@ CACHE_TTL=300  # 5 minutes
 PROJECT_DIR="${1:-$(pwd)}"
 SLUG=$(echo "$PROJECT_DIR" | sed 's|^/||; s|/|-|g')
 SLUG_PREFIX="-${SLUG}"  # prefix for hierarchical matching
-ALL_TRANSCRIPTS="$HOME/.claude/projects"
+# Transcript roots: STATUSLINE_TRANSCRIPT_ROOTS (colon-separated config dirs),
+# else $CLAUDE_CONFIG_DIR, else ~/.claude. Each root's projects/ is scanned.
+TRANSCRIPT_DIRS=()
+IFS=':' read -r -a _roots <<< "${STATUSLINE_TRANSCRIPT_ROOTS:-}"
+for _root in ${_roots[@]+"${_roots[@]}"}; do
+  [ -n "$_root" ] && TRANSCRIPT_DIRS+=("$_root/projects")
+done
+[ ${#TRANSCRIPT_DIRS[@]} -eq 0 ] && TRANSCRIPT_DIRS=("${CLAUDE_CONFIG_DIR:-$HOME/.claude}/projects")
+PROJECT_DIRS=()
+for _tdir in "${TRANSCRIPT_DIRS[@]}"; do
+  for _pdir in "$_tdir"/*/; do
+    [ -d "$_pdir" ] && PROJECT_DIRS+=("$_pdir")
+  done
+done
 
-# Short hash for cache filename (macOS/Linux compatible)
-if command -v md5 &>/dev/null; then
+# Short hash for cache filename (macOS/Linux compatible). The Rust engine
+# passes a keyed name instead under STATUSLINE_PRIVACY.
+if [ -n "${STATUSLINE_PROJ_CACHE_ID:-}" ]; then
+  PROJ_HASH="$STATUSLINE_PROJ_CACHE_ID"
+elif command -v md5 &>/dev/null; then
   PROJ_HASH=$(echo "$SLUG" | md5 -q | cut -c1-8)
 else
   PROJ_HASH=$(echo "$SLUG" | md5sum | cut -c1-8)
@@ -90,7 +106,44 @@ cleanup() {
 }
 trap cleanup EXIT
 
+# --- Work-hours split (optional) ---
+# STATUSLINE_WORK_HOURS="<hours> <weekdays>" in cron field syntax, local time,
+# e.g. "9-17 1-5" = 09:00-17:59 Monday-Friday. STATUSLINE_HOLIDAYS lists
+# YYYY-MM-DD dates that count as off-hours. When set, every period also gets
+# on_cost (inside the schedule) and off_cost.
+cron_field() {
+  local spec="$1" min="$2" max="$3" out="" part lo hi i
+  local -a parts
+  IFS=',' read -r -a parts <<< "$spec"
+  for part in ${parts[@]+"${parts[@]}"}; do
+    case "$part" in
+      '*') lo=$min; hi=$max ;;
+      *-*) lo=${part%-*}; hi=${part#*-} ;;
+      *) lo=$part; hi=$part ;;
+    esac
+    [[ "$lo" =~ ^[0-9]+$ && "$hi" =~ ^[0-9]+$ ]] || continue
+    for (( i = 10#$lo; i <= 10#$hi; i++ )); do
+      # cron allows 7 for Sunday
+      [ "$max" -eq 6 ] && [ "$i" -eq 7 ] && { out="$out,0"; continue; }
+      [ "$i" -ge "$min" ] && [ "$i" -le "$max" ] && out="$out,$i"
+    done
+  done
+  echo "[${out#,}]"
+}
+
+SCHEDULE=null
+if [ -n "${STATUSLINE_WORK_HOURS:-}" ]; then
+  read -r _hours _days <<< "$STATUSLINE_WORK_HOURS"
+  _holidays=$(echo "${STATUSLINE_HOLIDAYS:-}" | tr ',' '\n' | jq -R 'select(length > 0)' | jq -sc .)
+  SCHEDULE=$(jq -nc \
+    --argjson hours "$(cron_field "${_hours:-*}" 0 23)" \
+    --argjson days "$(cron_field "${_days:-*}" 0 6)" \
+    --argjson holidays "${_holidays:-[]}" \
+    '{hours: $hours, days: $days, holidays: $holidays}')
+fi
+
 # --- Core parser ---
+# Prints {"cost", "calls", "tokens"}, plus "on_cost" with a work-hours schedule.
 parse_cost() {
   local dir="$1"
   local cutoff="$2"
@@ -115,11 +168,14 @@ parse_cost() {
     return
   fi
 
+  # File mtimes only preselect: each message is bucketed by its own timestamp
   { grep -h '"type":"assistant"' "${files[@]}" 2>/dev/null || true; } | \
-  jq -r '
+  jq -r --argjson sched "$SCHEDULE" --argjson cutoff "$cutoff" '
     (.message.model // empty) as $model |
     (.message.usage // empty) as $u |
     select($model != null and $model != "<synthetic>" and $u != null) |
+    ((.timestamp // "") | sub("\\.[0-9]+Z$"; "Z") | try fromdateiso8601 catch null) as $ts |
+    select($ts == null or $ts >= $cutoff) |
     (if ($model | test("opus")) then
        { "in": 15e-6, "out": 75e-6, "cr": 1.875e-6, "cw": 18.75e-6 }
      elif ($model | test("sonnet")) then
@@ -129,19 +185,58 @@ parse_cost() {
      else
        { "in": 3e-6, "out": 15e-6, "cr": 0.3e-6, "cw": 3.75e-6 }
      end) as $p |
-    (($u.input_tokens // 0) * $p.in) +
-    (($u.output_tokens // 0) * $p.out) +
-    (($u.cache_read_input_tokens // 0) * $p.cr) +
-    (($u.cache_creation_input_tokens // 0) * $p.cw)
+    ((($u.input_tokens // 0) * $p.in) +
+     (($u.output_tokens // 0) * $p.out) +
+     (($u.cache_read_input_tokens // 0) * $p.cr) +
+     (($u.cache_creation_input_tokens // 0) * $p.cw)) as $cost |
+    (($u.input_tokens // 0) + ($u.output_tokens // 0) +
+     ($u.cache_read_input_tokens // 0) + ($u.cache_creation_input_tokens // 0)) as $tok |
+    if $sched == null then "\($cost) 0 \($tok)" else
+      ((.timestamp // "") | sub("\\.[0-9]+Z$"; "Z") | try (fromdateiso8601 | localtime) catch null) as $t |
+      ($t != null
+        and ($t[3] as $h | $sched.hours | any(. == $h))
+        and ($t[6] as $d | $sched.days | any(. == $d))
+        and (($t | strftime("%Y-%m-%d")) as $day | $sched.holidays | any(. == $day) | not)) as $on |
+      "\($cost) \(if $on then 1 else 0 end) \($tok)"
+    end
   ' 2>/dev/null | \
-  awk '{ total += $1; calls++ } END { printf "{\"cost\":%.2f,\"calls\":%d}\n", total+0, calls+0 }'
+  awk -v on_split="$([ "$SCHEDULE" = null ] && echo 0 || echo 1)" '
+    { total += $1; on += $1 * $2; tokens += $3; calls++ }
+    END {
+      if (on_split) printf "{\"cost\":%.2f,\"calls\":%d,\"tokens\":%.0f,\"on_cost\":%.2f}\n", total+0, calls+0, tokens+0, on+0
+      else printf "{\"cost\":%.2f,\"calls\":%d,\"tokens\":%.0f}\n", total+0, calls+0, tokens+0
+    }'
 }
 
 # --- Date boundaries ---
+# STATUSLINE_PERIOD_STARTS="<d1> <d7> <d30>" (Unix times, passed by the Rust
+# engine), else STATUSLINE_DAY_BOUNDARY=midnight starts each period at local
+# midnight (today, 6 and 29 days back), STATUSLINE_DAY_BOUNDARY=calendar at
+# today, this Monday and the 1st of the month, else rolling 24h windows.
+local_midnight() {
+  if [[ "$OSTYPE" == darwin* ]]; then
+    date -v-"$1"d -v0H -v0M -v0S +%s
+  else
+    date -d "$1 days ago 00:00" +%s
+  fi
+}
+
 NOW_EPOCH=$(date +%s)
-D1_EPOCH=$(( NOW_EPOCH - 1 * 86400 ))
-D7_EPOCH=$(( NOW_EPOCH - 7 * 86400 ))
-D30_EPOCH=$(( NOW_EPOCH - 30 * 86400 ))
+D1_EPOCH=""; D7_EPOCH=""; D30_EPOCH=""
+if [ -n "${STATUSLINE_PERIOD_STARTS:-}" ]; then
+  read -r D1_EPOCH D7_EPOCH D30_EPOCH <<< "$STATUSLINE_PERIOD_STARTS"
+elif [ "${STATUSLINE_DAY_BOUNDARY:-}" = midnight ]; then
+  D1_EPOCH=$(local_midnight 0 2>/dev/null)
+  D7_EPOCH=$(local_midnight 6 2>/dev/null)
+  D30_EPOCH=$(local_midnight 29 2>/dev/null)
+elif [ "${STATUSLINE_DAY_BOUNDARY:-}" = calendar ]; then
+  D1_EPOCH=$(local_midnight 0 2>/dev/null)
+  D7_EPOCH=$(local_midnight $(( $(date +%u) - 1 )) 2>/dev/null)
+  D30_EPOCH=$(local_midnight $(( 10#$(date +%d) - 1 )) 2>/dev/null)
+fi
+[[ "$D1_EPOCH" =~ ^[0-9]+$ ]] || D1_EPOCH=$(( NOW_EPOCH - 1 * 86400 ))
+[[ "$D7_EPOCH" =~ ^[0-9]+$ ]] || D7_EPOCH=$(( NOW_EPOCH - 7 * 86400 ))
+[[ "$D30_EPOCH" =~ ^[0-9]+$ ]] || D30_EPOCH=$(( NOW_EPOCH - 30 * 86400 ))
 
 # =============================================
 # PART 1: Project stats (per-project cache)
@@ -156,12 +251,12 @@ if [ "$PROJ_AGE" -ge "$CACHE_TTL" ]; then
   if acquire_lock "$PROJ_LOCK"; then
     CLEANUP_LOCKS+=("$PROJ_LOCK")
 
-    PROJ_1D_COST=0; PROJ_1D_CALLS=0
-    PROJ_7D_COST=0; PROJ_7D_CALLS=0
-    PROJ_30D_COST=0; PROJ_30D_CALLS=0
+    PROJ_1D_COST=0; PROJ_1D_CALLS=0; PROJ_1D_ON=0; PROJ_1D_TOK=0
+    PROJ_7D_COST=0; PROJ_7D_CALLS=0; PROJ_7D_ON=0; PROJ_7D_TOK=0
+    PROJ_30D_COST=0; PROJ_30D_CALLS=0; PROJ_30D_ON=0; PROJ_30D_TOK=0
     PROJ_MATCHED=0
 
-    for pdir in "$ALL_TRANSCRIPTS"/*/; do
+    for pdir in ${PROJECT_DIRS[@]+"${PROJECT_DIRS[@]}"}; do
       [ -d "$pdir" ] || continue
       dname=$(basename "$pdir")
       case "$dname" in
@@ -176,39 +271,59 @@ if [ "$PROJ_AGE" -ge "$CACHE_TTL" ]; then
       n=$(echo "$result1" | jq -r '.calls')
       PROJ_1D_COST=$(echo "$PROJ_1D_COST + $c" | bc)
       PROJ_1D_CALLS=$(echo "$PROJ_1D_CALLS + $n" | bc)
+      o=$(echo "$result1" | jq -r '.on_cost // 0')
+      PROJ_1D_ON=$(echo "$PROJ_1D_ON + $o" | bc)
+      t=$(echo "$result1" | jq -r '.tokens // 0')
+      PROJ_1D_TOK=$(( PROJ_1D_TOK + t ))
 
       result7=$(parse_cost "$pdir" "$D7_EPOCH")
       c=$(echo "$result7" | jq -r '.cost')
       n=$(echo "$result7" | jq -r '.calls')
       PROJ_7D_COST=$(echo "$PROJ_7D_COST + $c" | bc)
       PROJ_7D_CALLS=$(echo "$PROJ_7D_CALLS + $n" | bc)
+      o=$(echo "$result7" | jq -r '.on_cost // 0')
+      PROJ_7D_ON=$(echo "$PROJ_7D_ON + $o" | bc)
+      t=$(echo "$result7" | jq -r '.tokens // 0')
+      PROJ_7D_TOK=$(( PROJ_7D_TOK + t ))
 
       result30=$(parse_cost "$pdir" "$D30_EPOCH")
       c=$(echo "$result30" | jq -r '.cost')
       n=$(echo "$result30" | jq -r '.calls')
       PROJ_30D_COST=$(echo "$PROJ_30D_COST + $c" | bc)
       PROJ_30D_CALLS=$(echo "$PROJ_30D_CALLS + $n" | bc)
+      o=$(echo "$result30" | jq -r '.on_cost // 0')
+      PROJ_30D_ON=$(echo "$PROJ_30D_ON + $o" | bc)
+      t=$(echo "$result30" | jq -r '.tokens // 0')
+      PROJ_30D_TOK=$(( PROJ_30D_TOK + t ))
     done
 
     UPDATED=$(date -u +"%Y-%m-%dT%H:%M:%S")
     TMPFILE=$(mktemp "$CACHE_DIR/tmp-proj.XXXXXX")
-    jq -n \
+    jq -n --argjson split "$([ "$SCHEDULE" = null ] && echo false || echo true)" \
       --arg updated "$UPDATED" \
       --arg project_dir "$PROJECT_DIR" \
       --argjson matched_dirs "${PROJ_MATCHED:-0}" \
       --argjson d1_cost "${PROJ_1D_COST:-0}" \
       --argjson d1_calls "${PROJ_1D_CALLS:-0}" \
+      --argjson d1_on "${PROJ_1D_ON:-0}" \
+      --argjson d1_tok "${PROJ_1D_TOK:-0}" \
       --argjson d7_cost "${PROJ_7D_COST:-0}" \
       --argjson d7_calls "${PROJ_7D_CALLS:-0}" \
+      --argjson d7_on "${PROJ_7D_ON:-0}" \
+      --argjson d7_tok "${PROJ_7D_TOK:-0}" \
       --argjson d30_cost "${PROJ_30D_COST:-0}" \
       --argjson d30_calls "${PROJ_30D_CALLS:-0}" \
-      '{
+      --argjson d30_on "${PROJ_30D_ON:-0}" \
+      --argjson d30_tok "${PROJ_30D_TOK:-0}" \
+      'def period(c; n; on; t): {cost: c, calls: n, tokens: t}
+         + (if $split then {on_cost: on, off_cost: ((c - on) * 100 | round / 100)} else {} end);
+      {
         updated: $updated,
         project_dir: $project_dir,
         matched_dirs: $matched_dirs,
-        d1: { cost: $d1_cost, calls: $d1_calls },
-        d7: { cost: $d7_cost, calls: $d7_calls },
-        d30: { cost: $d30_cost, calls: $d30_calls }
+        d1: period($d1_cost; $d1_calls; $d1_on; $d1_tok),
+        d7: period($d7_cost; $d7_calls; $d7_on; $d7_tok),
+        d30: period($d30_cost; $d30_calls; $d30_on; $d30_tok)
       }' > "$TMPFILE"
     mv "$TMPFILE" "$PROJ_CACHE"
     rm -rf "$PROJ_LOCK"
@@ -223,11 +338,11 @@ if [ "$ALL_AGE" -ge "$CACHE_TTL" ]; then
   if acquire_lock "$ALL_LOCK"; then
     CLEANUP_LOCKS+=("$ALL_LOCK")
 
-    ALL_1D_COST=0; ALL_1D_CALLS=0
-    ALL_7D_COST=0; ALL_7D_CALLS=0
-    ALL_30D_COST=0; ALL_30D_CALLS=0
+    ALL_1D_COST=0; ALL_1D_CALLS=0; ALL_1D_ON=0; ALL_1D_TOK=0
+    ALL_7D_COST=0; ALL_7D_CALLS=0; ALL_7D_ON=0; ALL_7D_TOK=0
+    ALL_30D_COST=0; ALL_30D_CALLS=0; ALL_30D_ON=0; ALL_30D_TOK=0
 
-    for pdir in "$ALL_TRANSCRIPTS"/*/; do
+    for pdir in ${PROJECT_DIRS[@]+"${PROJECT_DIRS[@]}"}; do
       [ -d "$pdir" ] || continue
       dname=$(basename "$pdir")
       case "$dname" in *+*) continue ;; esac
@@ -237,35 +352,55 @@ if [ "$ALL_AGE" -ge "$CACHE_TTL" ]; then
       n=$(echo "$result1" | jq -r '.calls')
       ALL_1D_COST=$(echo "$ALL_1D_COST + $c" | bc)
       ALL_1D_CALLS=$(echo "$ALL_1D_CALLS + $n" | bc)
+      o=$(echo "$result1" | jq -r '.on_cost // 0')
+      ALL_1D_ON=$(echo "$ALL_1D_ON + $o" | bc)
+      t=$(echo "$result1" | jq -r '.tokens // 0')
+      ALL_1D_TOK=$(( ALL_1D_TOK + t ))
 
       result7=$(parse_cost "$pdir" "$D7_EPOCH")
       c=$(echo "$result7" | jq -r '.cost')
       n=$(echo "$result7" | jq -r '.calls')
       ALL_7D_COST=$(echo "$ALL_7D_COST + $c" | bc)
       ALL_7D_CALLS=$(echo "$ALL_7D_CALLS + $n" | bc)
+      o=$(echo "$result7" | jq -r '.on_cost // 0')
+      ALL_7D_ON=$(echo "$ALL_7D_ON + $o" | bc)
+      t=$(echo "$result7" | jq -r '.tokens // 0')
+      ALL_7D_TOK=$(( ALL_7D_TOK + t ))
 
       result30=$(parse_cost "$pdir" "$D30_EPOCH")
       c=$(echo "$result30" | jq -r '.cost')
       n=$(echo "$result30" | jq -r '.calls')
       ALL_30D_COST=$(echo "$ALL_30D_COST + $c" | bc)
       ALL_30D_CALLS=$(echo "$ALL_30D_CALLS + $n" | bc)
+      o=$(echo "$result30" | jq -r '.on_cost // 0')
+      ALL_30D_ON=$(echo "$ALL_30D_ON + $o" | bc)
+      t=$(echo "$result30" | jq -r '.tokens // 0')
+      ALL_30D_TOK=$(( ALL_30D_TOK + t ))
     done
 
     UPDATED=$(date -u +"%Y-%m-%dT%H:%M:%S")
     TMPFILE=$(mktemp "$CACHE_DIR/tmp-all.XXXXXX")
-    jq -n \
+    jq -n --argjson split "$([ "$SCHEDULE" = null ] && echo false || echo true)" \
       --arg updated "$UPDATED" \
       --argjson d1_cost "${ALL_1D_COST:-0}" \
       --argjson d1_calls "${ALL_1D_CALLS:-0}" \
+      --argjson d1_on "${ALL_1D_ON:-0}" \
+      --argjson d1_tok "${ALL_1D_TOK:-0}" \
       --argjson d7_cost "${ALL_7D_COST:-0}" \
       --argjson d7_calls "${ALL_7D_CALLS:-0}" \
+      --argjson d7_on "${ALL_7D_ON:-0}" \
+      --argjson d7_tok "${ALL_7D_TOK:-0}" \
       --argjson d30_cost "${ALL_30D_COST:-0}" \
       --argjson d30_calls "${ALL_30D_CALLS:-0}" \
-      '{
+      --argjson d30_on "${ALL_30D_ON:-0}" \
+      --argjson d30_tok "${ALL_30D_TOK:-0}" \
+      'def period(c; n; on; t): {cost: c, calls: n, tokens: t}
+         + (if $split then {on_cost: on, off_cost: ((c - on) * 100 | round / 100)} else {} end);
+      {
         updated: $updated,
-        d1: { cost: $d1_cost, calls: $d1_calls },
-        d7: { cost: $d7_cost, calls: $d7_calls },
-        d30: { cost: $d30_cost, calls: $d30_calls }
+        d1: period($d1_cost; $d1_calls; $d1_on; $d1_tok),
+        d7: period($d7_cost; $d7_calls; $d7_on; $d7_tok),
+        d30: period($d30_cost; $d30_calls; $d30_on; $d30_tok)
       }' > "$TMPFILE"
     mv "$TMPFILE" "$ALL_CACHE"
     rm -rf "$ALL_LOCK"
@@ -339,7 +339,8 @@ fi
 # --- Kick off cumulative stats refresh in background ---
 _SELF_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
 if [ -n "$PROJECT_DIR" ] && [ -x "$_SELF_DIR/cumulative-stats.sh" ]; then
-  "$_SELF_DIR/cumulative-stats.sh" "$PROJECT_DIR" &>/dev/null &
+  STATUSLINE_TRANSCRIPT_ROOTS="${STATUSLINE_TRANSCRIPT_ROOTS:-}" STATUSLINE_WORK_HOURS="${STATUSLINE_WORK_HOURS:-}" \
+    STATUSLINE_HOLIDAYS="${STATUSLINE_HOLIDAYS:-}" "$_SELF_DIR/cumulative-stats.sh" "$PROJECT_DIR" &>/dev/null &
   disown 2>/dev/null
 fi
 
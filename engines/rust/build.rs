@@ -0,0 +1,19 @@
+//! Report the release version from the repository's VERSION file rather
+//! than the crate version, so `--version`, self-update and telemetry agree
+//! with the other engines. Builds outside the repository fall back to the
+//! crate version.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+fn main() {
+    let file = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap_or_default()).join("../../VERSION");
+    println!("cargo:rerun-if-changed={}", file.display());
+    let version = fs::read_to_string(&file)
+        .ok()
+        .map(|v| v.trim().trim_start_matches('v').to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| env::var("CARGO_PKG_VERSION").unwrap_or_default());
+    println!("cargo:rustc-env=STATUSLINE_VERSION={}", version);
+}
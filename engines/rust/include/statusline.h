@@ -0,0 +1,26 @@
+/* C ABI of the statusline engine (Rust feature `ffi`).
+ *
+ * Build:  cargo rustc --lib --release --features ffi --crate-type cdylib
+ *         (target/release/libstatusline.so, .dylib on macOS)
+ */
+#ifndef STATUSLINE_H
+#define STATUSLINE_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Both status lines for the session JSON (as Claude Code sends on stdin),
+ * newline-terminated and with ANSI colors. config_json is an object of
+ * config keys, as in "statusline_options"; either argument may be NULL.
+ * Never returns NULL: the string is empty if rendering failed. Release it
+ * with statusline_free. */
+char *statusline_render(const char *json, const char *config_json);
+
+void statusline_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,157 @@
+//! Account indicator (STATUSLINE_ACCOUNT=true) and mismatch rules
+//! (STATUSLINE_ACCOUNT_EXPECT).
+//!
+//! The account is the provider for Bedrock/Vertex sessions, the API key
+//! (last four characters) when ANTHROPIC_API_KEY is set, and otherwise the
+//! logged-in organization from Claude Code's `.claude.json`. Expect rules map
+//! directory prefixes to a string the account must contain, e.g.
+//! `~/work=acme.com,~/oss=me@gmail.com`; a mismatch is always shown, in red.
+
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Longest account tag shown before it is cut with `…`.
+const MAX_TAG: usize = 24;
+
+pub struct Account {
+    /// Short label for the status line
+    pub tag: String,
+    /// Everything an expect rule may match against
+    identity: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct OauthAccount {
+    #[serde(default)]
+    email_address: String,
+    #[serde(default)]
+    organization_name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClaudeJson {
+    #[serde(default)]
+    oauth_account: Option<OauthAccount>,
+}
+
+/// `.claude.json` sits inside CLAUDE_CONFIG_DIR when set, else in $HOME.
+pub fn claude_json_path() -> PathBuf {
+    match env::var("CLAUDE_CONFIG_DIR") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir).join(".claude.json"),
+        _ => PathBuf::from(env::var("HOME").unwrap_or_default()).join(".claude.json"),
+    }
+}
+
+fn enabled(var: &str) -> bool {
+    matches!(env::var(var).as_deref(), Ok("1") | Ok("true"))
+}
+
+pub fn detect() -> Option<Account> {
+    if enabled("CLAUDE_CODE_USE_BEDROCK") {
+        return Some(plain("bedrock"));
+    }
+    if enabled("CLAUDE_CODE_USE_VERTEX") {
+        return Some(plain("vertex"));
+    }
+    if let Ok(key) = env::var("ANTHROPIC_API_KEY") {
+        let last: Vec<char> = key.chars().rev().take(4).collect();
+        if last.len() == 4 {
+            let last: String = last.into_iter().rev().collect();
+            return Some(plain(&format!("key\u{2026}{}", last)));
+        }
+    }
+    let data = fs::read(claude_json_path()).ok()?;
+    let oauth = serde_json::from_slice::<ClaudeJson>(&data)
+        .ok()?
+        .oauth_account?;
+    // Personal accounts get an organization named "<email>'s Organization"
+    let name = if oauth.organization_name.is_empty()
+        || oauth.organization_name.ends_with("'s Organization")
+    {
+        &oauth.email_address
+    } else {
+        &oauth.organization_name
+    };
+    if name.is_empty() {
+        return None;
+    }
+    Some(Account {
+        tag: shorten(name),
+        identity: format!("{} {}", oauth.email_address, oauth.organization_name),
+    })
+}
+
+/// An account known only by its tag, such as a cloud provider.
+pub fn plain(tag: &str) -> Account {
+    Account {
+        tag: tag.to_string(),
+        identity: tag.to_string(),
+    }
+}
+
+fn shorten(s: &str) -> String {
+    if s.chars().count() <= MAX_TAG {
+        return s.to_string();
+    }
+    let cut: String = s.chars().take(MAX_TAG - 1).collect();
+    format!("{}\u{2026}", cut)
+}
+
+/// Whether `dir` falls under an expect rule that the account does not
+/// satisfy. The longest matching prefix wins; `~` expands to $HOME.
+pub fn mismatch(account: &Account, rules: &str, dir: &str, home: &str) -> bool {
+    let rule = rules
+        .split(',')
+        .filter_map(|r| r.split_once('='))
+        .map(|(prefix, want)| {
+            let prefix = prefix.trim();
+            let prefix = match prefix.strip_prefix('~') {
+                Some(rest) => format!("{}{}", home, rest),
+                None => prefix.to_string(),
+            };
+            (prefix.trim_end_matches('/').to_string(), want.trim())
+        })
+        .filter(|(prefix, want)| {
+            !want.is_empty()
+                && (dir == prefix || dir.starts_with(&format!("{}/", prefix)) || prefix.is_empty())
+        })
+        .max_by_key(|(prefix, _)| prefix.len());
+    match rule {
+        Some((_, want)) => !account
+            .identity
+            .to_lowercase()
+            .contains(&want.to_lowercase()),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expect_rules() {
+        let work = Account {
+            tag: "Acme".into(),
+            identity: "jane@acme.com Acme".into(),
+        };
+        let rules = "~/work=acme.com, ~/work/oss=jane@gmail.com";
+        assert!(!mismatch(&work, rules, "/home/jane/work/api", "/home/jane"));
+        assert!(mismatch(
+            &work,
+            rules,
+            "/home/jane/work/oss/lib",
+            "/home/jane"
+        ));
+        assert!(!mismatch(&work, rules, "/home/jane/workshop", "/home/jane"));
+        assert!(!mismatch(&work, rules, "/tmp", "/home/jane"));
+        assert_eq!(
+            shorten("a-very-long-organization-name"),
+            "a-very-long-organizatio\u{2026}"
+        );
+    }
+}
@@ -1,12 +1,57 @@
 use crate::cache;
+use crate::config::Config;
 use std::env;
 use std::fs;
+#[cfg(unix)]
 use std::os::unix::process::CommandExt;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::time::{Duration, Instant, SystemTime};
 
-/// Run cumulative-stats.sh in the background (detached).
-pub fn spawn_cumulative_stats(project_dir: &str) {
+/// Time budget for an inline model refresh when spawning is unavailable.
+const INLINE_BUDGET: Duration = Duration::from_millis(50);
+
+/// A model cache older than this is rebuilt inline without INLINE_BUDGET,
+/// so a transcript too large to parse within the budget still refreshes, at
+/// the cost of one slow render per interval.
+const INLINE_FULL_AFTER: Duration = Duration::from_secs(60);
+
+/// Transcripts below this size are parsed inline before rendering.
+const INLINE_TRANSCRIPT_MAX: u64 = 256 * 1024;
+
+/// Tool results considered for the model cache's `recent_errors` count.
+const RECENT_TOOL_RESULTS: usize = 10;
+
+/// How long a failed spawn keeps the binary in inline mode.
+const SPAWN_FAILED_TTL: Duration = Duration::from_secs(3600);
+
+fn spawn_failed_marker() -> PathBuf {
+    cache::cache_dir().join("spawn-failed")
+}
+
+/// Whether background jobs may fork/exec: off with STATUSLINE_NO_SPAWN, or
+/// for an hour after a spawn failed (sandboxes that forbid fork/exec).
+pub fn spawn_allowed(no_spawn: bool) -> bool {
+    if no_spawn {
+        return false;
+    }
+    match fs::metadata(spawn_failed_marker()).and_then(|m| m.modified()) {
+        Ok(mtime) => SystemTime::now()
+            .duration_since(mtime)
+            .map(|age| age > SPAWN_FAILED_TTL)
+            .unwrap_or(true),
+        Err(_) => true,
+    }
+}
+
+fn mark_spawn_failed() {
+    let _ = fs::create_dir_all(cache::cache_dir());
+    let _ = fs::write(spawn_failed_marker(), b"");
+}
+
+/// Run cumulative-stats.sh in the background (detached). Settings the script
+/// reads are passed in its environment so config-file values reach it.
+pub fn spawn_cumulative_stats(project_dir: &str, cfg: &Config) {
     if project_dir.is_empty() {
         return;
     }
@@ -23,9 +68,7 @@ pub fn spawn_cumulative_stats(project_dir: &str) {
     let candidates = [
         self_dir.join("..").join("bash").join("cumulative-stats.sh"),
         self_dir.join("cumulative-stats.sh"),
-        PathBuf::from(env::var("HOME").unwrap_or_default())
-            .join(".claude")
-            .join("cumulative-stats.sh"),
+        cache::claude_dir().join("cumulative-stats.sh"),
     ];
 
     let mut script = None;
@@ -43,18 +86,40 @@ pub fn spawn_cumulative_stats(project_dir: &str) {
         None => return,
     };
 
+    let roots = cache::cumulative_roots(&cfg.transcript_roots);
     let mut cmd = Command::new(&script);
     cmd.arg(project_dir);
+    if cache::is_private() {
+        cmd.env(
+            "STATUSLINE_PROJ_CACHE_ID",
+            cache::file_id(&cache::project_hash(project_dir)),
+        );
+    }
+    for (key, value) in [
+        ("STATUSLINE_TRANSCRIPT_ROOTS", &roots),
+        ("STATUSLINE_WORK_HOURS", &cfg.work_hours),
+        ("STATUSLINE_HOLIDAYS", &cfg.holidays),
+        ("STATUSLINE_DAY_BOUNDARY", &cfg.day_boundary),
+    ] {
+        if !value.is_empty() {
+            cmd.env(key, value);
+        }
+    }
+    // Period starts from the system time zone via jiff, so the script's
+    // `date` flavor (GNU or BSD) does not matter
+    let starts = crate::clock::period_starts(crate::clock::now_unix(), &cfg.day_boundary);
+    cmd.env(
+        "STATUSLINE_PERIOD_STARTS",
+        starts.map(|s| s.to_string()).join(" "),
+    );
     cmd.stdout(Stdio::null());
     cmd.stderr(Stdio::null());
     cmd.stdin(Stdio::null());
-    unsafe {
-        cmd.pre_exec(|| {
-            libc::setpgid(0, 0);
-            Ok(())
-        });
+    #[cfg(unix)]
+    cmd.process_group(0);
+    if cmd.spawn().is_err() {
+        mark_spawn_failed();
     }
-    let _ = cmd.spawn();
 }
 
 /// Re-execute the binary with --internal-refresh-models to update the model cache.
@@ -79,20 +144,138 @@ pub fn spawn_model_refresh(session_id: &str, transcript_path: &str) {
     cmd.stdin(Stdio::null());
     cmd.stdout(Stdio::null());
     cmd.stderr(Stdio::null());
-    unsafe {
-        cmd.pre_exec(|| {
-            libc::setpgid(0, 0);
-            Ok(())
-        });
+    #[cfg(unix)]
+    cmd.process_group(0);
+    if cmd.spawn().is_err() {
+        mark_spawn_failed();
+    }
+}
+
+/// Re-execute the binary with --internal-digest to write yesterday's digest.
+pub fn spawn_digest() {
+    let exe = match env::current_exe() {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+
+    let mut cmd = Command::new(&exe);
+    cmd.arg("--internal-digest");
+    cmd.stdin(Stdio::null());
+    cmd.stdout(Stdio::null());
+    cmd.stderr(Stdio::null());
+    #[cfg(unix)]
+    cmd.process_group(0);
+    if cmd.spawn().is_err() {
+        mark_spawn_failed();
+    }
+}
+
+/// Re-execute the binary with --internal-telemetry to send yesterday's report.
+pub fn spawn_telemetry() {
+    let exe = match env::current_exe() {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+
+    let mut cmd = Command::new(&exe);
+    cmd.arg("--internal-telemetry");
+    cmd.stdin(Stdio::null());
+    cmd.stdout(Stdio::null());
+    cmd.stderr(Stdio::null());
+    #[cfg(unix)]
+    cmd.process_group(0);
+    if cmd.spawn().is_err() {
+        mark_spawn_failed();
+    }
+}
+
+/// Re-execute the binary with --internal-ci to fetch the CI status of HEAD.
+/// The job inherits the working directory, which picks the repository.
+pub fn spawn_ci() {
+    let exe = match env::current_exe() {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+
+    let mut cmd = Command::new(&exe);
+    cmd.arg("--internal-ci");
+    cmd.stdin(Stdio::null());
+    cmd.stdout(Stdio::null());
+    cmd.stderr(Stdio::null());
+    #[cfg(unix)]
+    cmd.process_group(0);
+    if cmd.spawn().is_err() {
+        mark_spawn_failed();
+    }
+}
+
+/// Whether the transcript is small enough to parse on the render path.
+pub fn transcript_is_small(transcript_path: &str) -> bool {
+    if transcript_path.is_empty() {
+        return false;
+    }
+    fs::metadata(transcript_path)
+        .map(|m| m.len() < INLINE_TRANSCRIPT_MAX)
+        .unwrap_or(false)
+}
+
+/// Refresh the model cache in-process when the transcript changed since the
+/// cache was last written: within INLINE_BUDGET, or without a deadline once
+/// the cache is missing or older than INLINE_FULL_AFTER.
+pub fn refresh_model_cache_inline(session_id: &str, transcript_path: &str) {
+    if session_id.is_empty() || transcript_path.is_empty() {
+        return;
+    }
+    let cache_file = cache::models_path(session_id);
+    let cache_mtime = fs::metadata(&cache_file).and_then(|m| m.modified()).ok();
+    let transcript_mtime = fs::metadata(transcript_path)
+        .and_then(|m| m.modified())
+        .ok();
+    if let (Some(c), Some(t)) = (cache_mtime, transcript_mtime) {
+        if c >= t {
+            return;
+        }
+    }
+    let overdue = cache_mtime
+        .and_then(|c| c.elapsed().ok())
+        .is_none_or(|age| age > INLINE_FULL_AFTER);
+    let deadline = (!overdue).then(|| Instant::now() + INLINE_BUDGET);
+    refresh_model_cache(session_id, transcript_path, deadline, None);
+}
+
+/// First render of a large transcript: with no model cache yet, parse its
+/// last `kb` KB inline (within INLINE_BUDGET) so the first paint has token
+/// data. The background refresh replaces it with the whole transcript.
+pub fn warm_start(session_id: &str, transcript_path: &str, kb: u64) {
+    if kb == 0 || session_id.is_empty() || transcript_path.is_empty() {
+        return;
     }
-    let _ = cmd.spawn();
+    let cache_file = cache::models_path(session_id);
+    if cache_file.exists() {
+        return;
+    }
+    let _span = crate::trace::span("transcript.warm_start");
+    refresh_model_cache(
+        session_id,
+        transcript_path,
+        Some(Instant::now() + INLINE_BUDGET),
+        Some(kb * 1024),
+    );
 }
 
 /// Parse JSONL transcripts and write the model cache (internal mode).
-pub fn refresh_model_cache(session_id: &str, transcript_path: &str) {
+/// With a deadline, gives up without writing once it passes; with `tail`,
+/// reads only that many bytes from the end of each file.
+pub fn refresh_model_cache(
+    session_id: &str,
+    transcript_path: &str,
+    deadline: Option<Instant>,
+    tail: Option<u64>,
+) {
     if session_id.is_empty() || transcript_path.is_empty() {
         return;
     }
+    let _span = crate::trace::span("transcript.scan");
 
     let cache_dir = cache::cache_dir();
     let _ = fs::create_dir_all(&cache_dir);
@@ -119,16 +302,29 @@ pub fn refresh_model_cache(session_id: &str, transcript_path: &str) {
         #[serde(rename = "in")]
         in_tokens: i64,
         out: i64,
+        /// Parts of `in` billed at cache rates (for cost estimates)
+        cache_read: i64,
+        cache_write: i64,
     }
 
     let mut models: std::collections::HashMap<String, ModelAgg> = std::collections::HashMap::new();
+    // Outcome of the most recent tool results (true = error), oldest first
+    let mut tool_results: Vec<bool> = Vec::new();
+    // Files the agent edited or wrote, for `statusline files`
+    let mut touched = std::collections::BTreeSet::new();
 
     for fpath in &files {
-        let data = match fs::read_to_string(fpath) {
-            Ok(d) => d,
-            Err(_) => continue,
+        let data = match tail {
+            Some(bytes) => cache::read_tail(fpath, bytes),
+            None => fs::read_to_string(fpath).ok(),
+        };
+        let Some(data) = data else {
+            continue;
         };
         for line in data.lines() {
+            if deadline.is_some_and(|d| Instant::now() > d) {
+                return;
+            }
             let line = line.trim();
             if line.is_empty() {
                 continue;
@@ -137,6 +333,18 @@ pub fn refresh_model_cache(session_id: &str, transcript_path: &str) {
                 Ok(v) => v,
                 Err(_) => continue,
             };
+            if entry.get("type").and_then(|t| t.as_str()) == Some("user") {
+                if let Some(items) = entry.pointer("/message/content").and_then(|c| c.as_array()) {
+                    for item in items {
+                        if item.get("type").and_then(|t| t.as_str()) == Some("tool_result") {
+                            let failed =
+                                item.get("is_error").and_then(|e| e.as_bool()) == Some(true);
+                            tool_results.push(failed);
+                        }
+                    }
+                }
+                continue;
+            }
             if entry.get("type").and_then(|t| t.as_str()) != Some("assistant") {
                 continue;
             }
@@ -144,48 +352,53 @@ pub fn refresh_model_cache(session_id: &str, transcript_path: &str) {
                 Some(m) => m,
                 None => continue,
             };
+            if let Some(items) = msg.get("content").and_then(|c| c.as_array()) {
+                touched.extend(items.iter().filter_map(edited_file));
+            }
+            // Bedrock/Vertex ids are stored in their Anthropic API form
             let name = match msg.get("model").and_then(|m| m.as_str()) {
-                Some(n) if n.starts_with("claude-") => n.to_string(),
-                _ => continue,
+                Some(n) => crate::models::normalize(n),
+                None => continue,
             };
+            if !name.starts_with("claude-") {
+                continue;
+            }
             let usage = match msg.get("usage") {
                 Some(u) => u,
                 None => continue,
             };
-            let input = usage
-                .get("input_tokens")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0)
-                + usage
-                    .get("cache_read_input_tokens")
-                    .and_then(|v| v.as_i64())
-                    .unwrap_or(0)
-                + usage
-                    .get("cache_creation_input_tokens")
-                    .and_then(|v| v.as_i64())
-                    .unwrap_or(0);
-            let output = usage
-                .get("output_tokens")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0);
+            let tokens = |key: &str| usage.get(key).and_then(|v| v.as_i64()).unwrap_or(0);
+            let cache_read = tokens("cache_read_input_tokens");
+            let cache_write = tokens("cache_creation_input_tokens");
+            let input = tokens("input_tokens") + cache_read + cache_write;
+            let output = tokens("output_tokens");
 
             let agg = models.entry(name.clone()).or_insert(ModelAgg {
                 model: name,
                 in_tokens: 0,
                 out: 0,
+                cache_read: 0,
+                cache_write: 0,
             });
             agg.in_tokens += input;
             agg.out += output;
+            agg.cache_read += cache_read;
+            agg.cache_write += cache_write;
         }
     }
 
     #[derive(serde::Serialize)]
     struct Result {
         models: Vec<ModelAgg>,
+        recent_errors: usize,
+        files: Vec<String>,
     }
 
+    let recent = tool_results.len().saturating_sub(RECENT_TOOL_RESULTS);
     let result = Result {
         models: models.into_values().collect(),
+        recent_errors: tool_results[recent..].iter().filter(|&&e| e).count(),
+        files: touched.into_iter().collect(),
     };
 
     let data = match serde_json::to_vec(&result) {
@@ -193,9 +406,52 @@ pub fn refresh_model_cache(session_id: &str, transcript_path: &str) {
         Err(_) => return,
     };
 
-    let cache_file = cache_dir.join(format!("models-{}.json", session_id));
+    let cache_file = cache::models_path(session_id);
     let tmp = cache_file.with_extension("json.tmp");
     if fs::write(&tmp, &data).is_ok() {
         let _ = fs::rename(&tmp, &cache_file);
     }
 }
+
+/// The path a file-changing tool call (Edit, MultiEdit, Write, NotebookEdit)
+/// works on.
+fn edited_file(item: &serde_json::Value) -> Option<String> {
+    if item.get("type").and_then(|t| t.as_str()) != Some("tool_use") {
+        return None;
+    }
+    let input = item.get("input")?;
+    let path = match item.get("name").and_then(|n| n.as_str())? {
+        "Edit" | "MultiEdit" | "Write" => input.get("file_path"),
+        "NotebookEdit" => input.get("notebook_path"),
+        _ => None,
+    };
+    path.and_then(|p| p.as_str())
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn edited_file_reads_file_changing_tools() {
+        let tool = |name: &str, input: serde_json::Value| json!({"type": "tool_use", "name": name, "input": input});
+        for name in ["Edit", "MultiEdit", "Write"] {
+            let item = tool(name, json!({"file_path": "/p/src/main.rs"}));
+            assert_eq!(edited_file(&item).as_deref(), Some("/p/src/main.rs"));
+        }
+        let notebook = tool("NotebookEdit", json!({"notebook_path": "/p/a.ipynb"}));
+        assert_eq!(edited_file(&notebook).as_deref(), Some("/p/a.ipynb"));
+
+        assert_eq!(
+            edited_file(&tool("Read", json!({"file_path": "/p/x"}))),
+            None
+        );
+        assert_eq!(edited_file(&tool("Write", json!({"file_path": ""}))), None);
+        assert_eq!(edited_file(&tool("Edit", json!({}))), None);
+        let result = json!({"type": "tool_result", "name": "Edit", "input": {"file_path": "/p/x"}});
+        assert_eq!(edited_file(&result), None);
+    }
+}
@@ -0,0 +1,119 @@
+//! Attention bell (STATUSLINE_BELL=context=bell,error=flash): ring or flash
+//! the terminal when the context window turns critical or a background job
+//! logs an error to `debug.log`.
+//!
+//! Status line output is captured by Claude Code, so the bell goes straight
+//! to `/dev/tty`. Only transitions fire: the last seen state is kept per
+//! session in `<cache dir>/bell-<session>.json`.
+
+use crate::cache;
+use crate::debuglog;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Context usage (percent) that counts as critical, as for the red bar.
+const CRITICAL_PCT: f64 = 90.0;
+/// How long the screen stays inverted for a flash.
+const FLASH: Duration = Duration::from_millis(120);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Style {
+    Bell,
+    Flash,
+}
+
+/// Per-event styles: `context` and `error`, each `bell` (default) or `flash`.
+fn parse(spec: &str) -> (Option<Style>, Option<Style>) {
+    let (mut context, mut error) = (None, None);
+    for item in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let (event, style) = item.split_once('=').unwrap_or((item, "bell"));
+        let style = match style.trim() {
+            "flash" => Style::Flash,
+            _ => Style::Bell,
+        };
+        match event.trim() {
+            "context" => context = Some(style),
+            "error" => error = Some(style),
+            _ => {}
+        }
+    }
+    (context, error)
+}
+
+#[derive(Serialize, Deserialize, PartialEq)]
+struct State {
+    critical: bool,
+    log_mtime: u64,
+}
+
+/// Compare with the previous render and fire the bells configured for
+/// whatever changed. When `quiet`, transitions are recorded but stay silent.
+pub fn check(spec: &str, session_id: &str, context_pct: f64, quiet: bool) {
+    let (on_context, on_error) = parse(spec);
+    if session_id.is_empty() || (on_context.is_none() && on_error.is_none()) {
+        return;
+    }
+    let path = cache::cache_dir().join(format!("bell-{}.json", cache::file_id(session_id)));
+    let prev: Option<State> = fs::read_to_string(&path)
+        .ok()
+        .and_then(|d| serde_json::from_str(&d).ok());
+    let state = State {
+        critical: context_pct >= CRITICAL_PCT,
+        log_mtime: fs::metadata(debuglog::path())
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    };
+    if let Some(prev) = &prev {
+        if *prev == state {
+            return;
+        }
+        let fired = [
+            on_context.filter(|_| state.critical && !prev.critical),
+            on_error.filter(|_| state.log_mtime > prev.log_mtime),
+        ];
+        if let Some(style) = fired.iter().flatten().next().filter(|_| !quiet) {
+            ring(*style);
+        }
+    }
+    let _ = fs::create_dir_all(cache::cache_dir());
+    let _ = fs::write(&path, serde_json::to_string(&state).unwrap_or_default());
+}
+
+fn ring(style: Style) {
+    let Ok(mut tty) = OpenOptions::new().write(true).open("/dev/tty") else {
+        return;
+    };
+    match style {
+        Style::Bell => {
+            let _ = tty.write_all(b"\x07");
+        }
+        Style::Flash => {
+            // DECSCNM: reverse video on, then off
+            let _ = tty.write_all(b"\x1b[?5h");
+            let _ = tty.flush();
+            thread::sleep(FLASH);
+            let _ = tty.write_all(b"\x1b[?5l");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_per_event_styles() {
+        assert_eq!(
+            parse("context, error=flash"),
+            (Some(Style::Bell), Some(Style::Flash))
+        );
+        assert_eq!(parse("context=flash,bogus"), (Some(Style::Flash), None));
+        assert_eq!(parse(""), (None, None));
+    }
+}
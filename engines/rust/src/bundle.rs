@@ -0,0 +1,247 @@
+use crate::cache;
+use crate::config::{self, Config};
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const REDACTED: &str = "[redacted]";
+/// Stand-in for file system paths under STATUSLINE_PRIVACY.
+const PATH: &str = "[path]";
+const SECRET_WORDS: &[&str] = &[
+    "secret",
+    "password",
+    "passwd",
+    "authorization",
+    "credential",
+    "apikey",
+    "api_key",
+];
+
+const TERMINAL_VARS: &[&str] = &[
+    "TERM",
+    "COLORTERM",
+    "TERM_PROGRAM",
+    "TERM_PROGRAM_VERSION",
+    "COLUMNS",
+    "LANG",
+    "LC_ALL",
+    "NO_COLOR",
+    "TMUX",
+    "XDG_CACHE_HOME",
+];
+
+/// `statusline debug-bundle [--input session.json] [--output FILE]`
+///
+/// Collects sanitized session JSON, config, cache snapshots, version and
+/// terminal info into a tarball for bug reports.
+pub fn run(cfg: &Config) -> Result<String, String> {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let name = format!("statusline-debug-{}", stamp);
+    let output = config::arg_value(&cfg.command_args, "--output")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(format!("{}.tar.gz", name)));
+
+    let work = env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+    let dir = work.join(&name);
+    fs::create_dir_all(&dir).map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+    let result = collect(cfg, &dir).and_then(|_| archive(&work, &name, &output));
+    let _ = fs::remove_dir_all(&work);
+    result.map(|_| format!("[ok] Wrote {}", output.display()))
+}
+
+fn collect(cfg: &Config, dir: &Path) -> Result<(), String> {
+    // Session payload: --input file, or piped stdin
+    let raw = match config::arg_value(&cfg.command_args, "--input") {
+        Some(p) => fs::read(&p).map_err(|e| format!("cannot read {}: {}", p, e))?,
+        None if !io::stdin().is_terminal() => {
+            let mut buf = Vec::new();
+            let _ = io::stdin().take(1 << 20).read_to_end(&mut buf);
+            buf
+        }
+        None => Vec::new(),
+    };
+    if !raw.is_empty() {
+        let session = match serde_json::from_slice::<Value>(&raw) {
+            Ok(mut v) => {
+                redact(&mut v);
+                if cfg.privacy {
+                    redact_paths(&mut v);
+                }
+                serde_json::to_string_pretty(&v).unwrap_or_default()
+            }
+            Err(e) => format!("unparseable session JSON: {}\n", e),
+        };
+        write(dir, "session.json", &session)?;
+    }
+
+    if let Ok(content) = fs::read_to_string(config::config_path()) {
+        write(dir, "statusline.env", &redact_env(&content, cfg.privacy))?;
+    }
+
+    let caches = dir.join("cache");
+    let _ = fs::create_dir_all(&caches);
+    if let Ok(entries) = fs::read_dir(cache::cache_dir()) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|e| e != "json") {
+                continue;
+            }
+            if !cfg.privacy {
+                let _ = fs::copy(&path, caches.join(entry.file_name()));
+            } else if let Some(mut v) = fs::read(&path)
+                .ok()
+                .and_then(|d| serde_json::from_slice::<Value>(&d).ok())
+            {
+                redact_paths(&mut v);
+                let _ = fs::write(caches.join(entry.file_name()), v.to_string());
+            }
+        }
+    }
+
+    let mut info = format!(
+        "statusline {} (rust)\nos: {}\narch: {}\nstdout tty: {}\n",
+        crate::VERSION,
+        env::consts::OS,
+        env::consts::ARCH,
+        io::stdout().is_terminal()
+    );
+    for var in TERMINAL_VARS {
+        if let Ok(v) = env::var(var) {
+            let v = if cfg.privacy && is_path(&v) { PATH } else { &v };
+            info.push_str(&format!("{}={}\n", var, v));
+        }
+    }
+    write(dir, "info.txt", &info)
+}
+
+fn archive(work: &Path, name: &str, output: &Path) -> Result<(), String> {
+    let output = if output.is_absolute() {
+        output.to_path_buf()
+    } else {
+        env::current_dir().map_err(|e| e.to_string())?.join(output)
+    };
+    let status = Command::new("tar")
+        .arg("czf")
+        .arg(&output)
+        .arg("-C")
+        .arg(work)
+        .arg(name)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| format!("cannot run tar: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("cannot write {}", output.display()))
+    }
+}
+
+fn write(dir: &Path, file: &str, content: &str) -> Result<(), String> {
+    fs::write(dir.join(file), content).map_err(|e| format!("cannot write {}: {}", file, e))
+}
+
+fn is_secret(key: &str) -> bool {
+    let k = key.to_lowercase();
+    // Suffix match keeps counters like total_input_tokens readable
+    SECRET_WORDS.iter().any(|w| k.contains(w)) || k.ends_with("token") || k.ends_with("_key")
+}
+
+/// Replace values of secret-looking keys, recursively.
+fn redact(v: &mut Value) {
+    match v {
+        Value::Object(map) => {
+            for (k, val) in map.iter_mut() {
+                if is_secret(k) && !val.is_object() {
+                    *val = Value::from(REDACTED);
+                } else {
+                    redact(val);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// Absolute or home-relative paths, which name users and workspaces.
+fn is_path(s: &str) -> bool {
+    s.starts_with('/') || s.starts_with("~/")
+}
+
+/// Replace path values (`cwd`, `project_dir`, edited files), recursively.
+fn redact_paths(v: &mut Value) {
+    match v {
+        Value::String(s) if is_path(s) => *v = Value::from(PATH),
+        Value::Object(map) => map.values_mut().for_each(redact_paths),
+        Value::Array(items) => items.iter_mut().for_each(redact_paths),
+        _ => {}
+    }
+}
+
+fn redact_env(content: &str, paths: bool) -> String {
+    content
+        .lines()
+        .map(|line| match line.find('=') {
+            Some(idx) if is_secret(&line[..idx]) => format!("{}={}", &line[..idx], REDACTED),
+            Some(idx) if paths && is_path(line[idx + 1..].trim_matches('"')) => {
+                format!("{}={}", &line[..idx], PATH)
+            }
+            _ => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redact_strips_secrets_keeps_counters() {
+        let mut v: Value = serde_json::from_str(
+            r#"{"api_key":"sk-1","nested":{"auth_token":"t","total_input_tokens":5}}"#,
+        )
+        .unwrap();
+        redact(&mut v);
+        assert_eq!(v["api_key"], REDACTED);
+        assert_eq!(v["nested"]["auth_token"], REDACTED);
+        assert_eq!(v["nested"]["total_input_tokens"], 5);
+    }
+
+    #[test]
+    fn redact_env_lines() {
+        let content = "STATUSLINE_SHOW_GIT=false\nGITHUB_TOKEN=ghp_x\nSTATUSLINE_TRANSCRIPT_ROOTS=/home/me/.claude\n";
+        let out = redact_env(content, false);
+        assert_eq!(
+            out,
+            "STATUSLINE_SHOW_GIT=false\nGITHUB_TOKEN=[redacted]\nSTATUSLINE_TRANSCRIPT_ROOTS=/home/me/.claude\n"
+        );
+        let out = redact_env(content, true);
+        assert!(
+            out.ends_with("STATUSLINE_TRANSCRIPT_ROOTS=[path]\n"),
+            "{}",
+            out
+        );
+    }
+
+    #[test]
+    fn redact_paths_in_values() {
+        let mut v: Value = serde_json::from_str(
+            r#"{"cwd":"/home/me/acme","files":["~/acme/a.rs"],"model":{"id":"opus"}}"#,
+        )
+        .unwrap();
+        redact_paths(&mut v);
+        assert_eq!(v["cwd"], PATH);
+        assert_eq!(v["files"][0], PATH);
+        assert_eq!(v["model"]["id"], "opus");
+    }
+}
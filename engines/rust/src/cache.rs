@@ -1,8 +1,15 @@
+use crate::models::{self, Family};
 use md5::{Digest, Md5};
-use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime};
 
 /// Return the statusline cache directory.
 pub fn cache_dir() -> PathBuf {
@@ -16,6 +23,184 @@ pub fn cache_dir() -> PathBuf {
     }
 }
 
+/// Claude Code's config directory: $CLAUDE_CONFIG_DIR, else ~/.claude.
+pub fn claude_dir() -> PathBuf {
+    match env::var("CLAUDE_CONFIG_DIR") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var("HOME").unwrap_or_default()).join(".claude"),
+    }
+}
+
+/// Directories holding per-project transcript folders: `<root>/projects`
+/// for each colon-separated root in STATUSLINE_TRANSCRIPT_ROOTS, or the
+/// Claude config dir when unset.
+pub fn transcript_roots(setting: &str) -> Vec<PathBuf> {
+    let roots: Vec<PathBuf> = setting
+        .split(':')
+        .filter(|r| !r.is_empty())
+        .map(PathBuf::from)
+        .collect();
+    if roots.is_empty() {
+        vec![claude_dir().join("projects")]
+    } else {
+        roots.into_iter().map(|r| r.join("projects")).collect()
+    }
+}
+
+/// The session's model cache, written in the background.
+pub fn models_path(session_id: &str) -> PathBuf {
+    cache_dir().join(format!("models-{}.json", file_id(session_id)))
+}
+
+static PRIVATE: AtomicBool = AtomicBool::new(false);
+
+/// Apply STATUSLINE_PRIVACY to the cache file names of this process.
+pub fn set_private(on: bool) {
+    PRIVATE.store(on, Ordering::Relaxed);
+}
+
+/// Whether STATUSLINE_PRIVACY applies to this process.
+pub fn is_private() -> bool {
+    PRIVATE.load(Ordering::Relaxed)
+}
+
+/// The name part of a cache file for a session id or project hash. Under
+/// STATUSLINE_PRIVACY it is a keyed hash, so file names neither show the
+/// session nor can be matched against guessed project paths. The project
+/// cumulative cache gets it too: cumulative-stats.sh is passed the name.
+pub fn file_id(id: &str) -> String {
+    if id.is_empty() || !is_private() {
+        return id.to_string();
+    }
+    keyed_hash(privacy_key(), id)
+}
+
+/// A stable stand-in for a project path in exports under STATUSLINE_PRIVACY:
+/// `project 3f2a9c1e`, the same for every export from this cache.
+pub fn private_name(project_dir: &str) -> String {
+    format!(
+        "project {}",
+        &keyed_hash(privacy_key(), &project_hash(project_dir))[..8]
+    )
+}
+
+fn keyed_hash(key: &str, id: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(key.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(id.as_bytes());
+    format!("{:x}", hasher.finalize())[..16].to_string()
+}
+
+/// A random key in `<cache dir>/privacy.key` (mode 600), created on first
+/// use. Removing it orphans the hashed caches, which are then rebuilt.
+fn privacy_key() -> &'static str {
+    static KEY: OnceLock<String> = OnceLock::new();
+    KEY.get_or_init(|| {
+        let path = cache_dir().join("privacy.key");
+        let read = || {
+            fs::read_to_string(&path)
+                .ok()
+                .map(|k| k.trim().to_string())
+                .filter(|k| !k.is_empty())
+        };
+        if let Some(key) = read() {
+            return key;
+        }
+        let mut bytes = [0u8; 32];
+        if fs::File::open("/dev/urandom")
+            .and_then(|mut f| f.read_exact(&mut bytes))
+            .is_err()
+        {
+            let mut hasher = Sha256::new();
+            hasher.update(format!("{:?} {}", SystemTime::now(), std::process::id()));
+            bytes.copy_from_slice(&hasher.finalize());
+        }
+        let key: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        let _ = fs::create_dir_all(cache_dir());
+        let mut file = fs::OpenOptions::new();
+        file.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut file, 0o600);
+        match file.open(&path) {
+            Ok(mut f) => {
+                let _ = std::io::Write::write_all(&mut f, key.as_bytes());
+                key
+            }
+            // Another render created it first
+            Err(_) => read().unwrap_or(key),
+        }
+    })
+}
+
+/// Transcripts fetched from other machines by `statusline sync pull`.
+pub fn sync_dir() -> PathBuf {
+    cache_dir().join("sync")
+}
+
+/// STATUSLINE_TRANSCRIPT_ROOTS for cumulative-stats.sh: the setting (or the
+/// Claude config dir) and, once `statusline sync pull` merged any, the
+/// other machines' transcripts.
+pub fn cumulative_roots(setting: &str) -> String {
+    let synced = sync_dir();
+    if !synced.join("projects").is_dir() {
+        return setting.to_string();
+    }
+    let own = if setting.is_empty() {
+        claude_dir().display().to_string()
+    } else {
+        setting.to_string()
+    };
+    format!("{}:{}", own, synced.display())
+}
+
+/// The transcript file itself, or the file of the same name under one of the
+/// transcript roots when the reported path does not exist (synced or
+/// relocated config dirs).
+pub fn locate_transcript(transcript_path: &str, roots: &[PathBuf]) -> String {
+    let path = Path::new(transcript_path);
+    if transcript_path.is_empty() || path.is_file() {
+        return transcript_path.to_string();
+    }
+    let (Some(name), Some(project)) = (path.file_name(), path.parent().and_then(|p| p.file_name()))
+    else {
+        return transcript_path.to_string();
+    };
+    roots
+        .iter()
+        .map(|root| root.join(project).join(name))
+        .find(|p| p.is_file())
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|| transcript_path.to_string())
+}
+
+/// Seconds since the file was last modified (0 if unknown).
+pub fn file_age_secs(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| SystemTime::now().duration_since(t).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// The last `bytes` of a JSONL file, from its first whole line on.
+pub fn read_tail(path: &Path, bytes: u64) -> Option<String> {
+    let mut file = fs::File::open(path).ok()?;
+    let start = file.metadata().ok()?.len().saturating_sub(bytes);
+    file.seek(SeekFrom::Start(start)).ok()?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).ok()?;
+    let text = String::from_utf8_lossy(&tail);
+    Some(match start {
+        0 => text.into_owned(),
+        // Mid-file starts cut a line: skip to the first whole one
+        _ => text
+            .split_once('\n')
+            .map_or_else(String::new, |(_, rest)| rest.to_string()),
+    })
+}
+
 /// Compute the 8-char hex hash for a project directory.
 /// Matches bash: echo "$slug" | md5 (note: newline included).
 pub fn project_hash(dir: &str) -> String {
@@ -33,20 +218,39 @@ struct ModelEntry {
     #[serde(rename = "in")]
     in_tokens: i64,
     out: i64,
+    /// Written by the Rust engine only
+    #[serde(default)]
+    cache_read: i64,
+    #[serde(default)]
+    cache_write: i64,
 }
 
 #[derive(Deserialize)]
 struct ModelsCache {
     models: Vec<ModelEntry>,
+    #[serde(default)]
+    recent_errors: i64,
+    #[serde(default)]
+    files: Vec<String>,
 }
 
+#[derive(Clone, Default)]
 pub struct ModelStats {
+    pub age_secs: u64,
     pub opus_in: i64,
     pub opus_out: i64,
     pub sonnet_in: i64,
     pub sonnet_out: i64,
     pub haiku_in: i64,
     pub haiku_out: i64,
+    /// Failed tool calls among the last few (Rust-written caches only).
+    pub recent_errors: i64,
+    /// Session cost at list prices, for providers that report none.
+    pub est_cost: f64,
+    /// The same at list prices with every cached token billed as fresh input.
+    pub uncached_cost: f64,
+    /// Files edited or written in the session, sorted (Rust-written caches only).
+    pub files: Vec<String>,
 }
 
 /// Read the per-session model cache and aggregate by model family.
@@ -54,31 +258,47 @@ pub fn read_models(session_id: &str) -> Option<ModelStats> {
     if session_id.is_empty() {
         return None;
     }
-    let cache_file = cache_dir().join(format!("models-{}.json", session_id));
-    let data = fs::read_to_string(cache_file).ok()?;
+    let cache_file = models_path(session_id);
+    let data = fs::read_to_string(&cache_file).ok()?;
     let mc: ModelsCache = serde_json::from_str(&data).ok()?;
 
     let mut stats = ModelStats {
+        age_secs: file_age_secs(&cache_file),
         opus_in: 0,
         opus_out: 0,
         sonnet_in: 0,
         sonnet_out: 0,
         haiku_in: 0,
         haiku_out: 0,
+        recent_errors: mc.recent_errors,
+        est_cost: 0.0,
+        uncached_cost: 0.0,
+        files: mc.files,
     };
 
     for m in &mc.models {
-        let name = m.model.to_lowercase();
-        if name.contains("opus") {
-            stats.opus_in += m.in_tokens;
-            stats.opus_out += m.out;
-        } else if name.contains("sonnet") {
-            stats.sonnet_in += m.in_tokens;
-            stats.sonnet_out += m.out;
-        } else if name.contains("haiku") {
-            stats.haiku_in += m.in_tokens;
-            stats.haiku_out += m.out;
+        match models::family(&m.model) {
+            Some(Family::Opus) => {
+                stats.opus_in += m.in_tokens;
+                stats.opus_out += m.out;
+            }
+            Some(Family::Sonnet) => {
+                stats.sonnet_in += m.in_tokens;
+                stats.sonnet_out += m.out;
+            }
+            Some(Family::Haiku) => {
+                stats.haiku_in += m.in_tokens;
+                stats.haiku_out += m.out;
+            }
+            None => {}
         }
+        let p = models::price(&m.model);
+        let uncached = m.in_tokens - m.cache_read - m.cache_write;
+        stats.est_cost += uncached as f64 * p.input
+            + m.out as f64 * p.output
+            + m.cache_read as f64 * p.cache_read
+            + m.cache_write as f64 * p.cache_write;
+        stats.uncached_cost += m.in_tokens as f64 * p.input + m.out as f64 * p.output;
     }
 
     Some(stats)
@@ -87,6 +307,15 @@ pub fn read_models(session_id: &str) -> Option<ModelStats> {
 #[derive(Deserialize)]
 struct CumulativePeriod {
     cost: f64,
+    /// Present when cumulative-stats.sh ran with STATUSLINE_WORK_HOURS
+    #[serde(default)]
+    on_cost: Option<f64>,
+    #[serde(default)]
+    off_cost: Option<f64>,
+    /// All tokens billed in the period, cache reads and writes included
+    /// (absent in caches written before it was tracked)
+    #[serde(default)]
+    tokens: i64,
 }
 
 #[derive(Deserialize)]
@@ -96,19 +325,29 @@ struct CumulativeCache {
     d30: CumulativePeriod,
 }
 
+#[derive(Clone, Default)]
 pub struct CumulativeStats {
+    pub age_secs: u64,
     pub d1: f64,
     pub d7: f64,
     pub d30: f64,
+    /// 30-day cost inside and outside work hours, when the cache has a split
+    pub d30_split: Option<(f64, f64)>,
+    /// Token totals for d1, d7 and d30
+    pub tokens: [i64; 3],
+}
+
+/// The project's cumulative cache, written by cumulative-stats.sh under the
+/// name from `file_id`.
+pub fn cumulative_path(project_dir: &str) -> PathBuf {
+    cache_dir().join(format!("proj-{}.json", file_id(&project_hash(project_dir))))
 }
 
 /// Read project and global cumulative caches.
 pub fn read_cumulative(project_dir: &str) -> (Option<CumulativeStats>, Option<CumulativeStats>) {
     let cd = cache_dir();
     let proj = if !project_dir.is_empty() {
-        let hash = project_hash(project_dir);
-        let proj_file = cd.join(format!("proj-{}.json", hash));
-        read_cumulative_file(&proj_file)
+        read_cumulative_file(&cumulative_path(project_dir))
     } else {
         None
     };
@@ -122,12 +361,229 @@ pub fn read_cumulative(project_dir: &str) -> (Option<CumulativeStats>, Option<Cu
 fn read_cumulative_file(path: &PathBuf) -> Option<CumulativeStats> {
     let data = fs::read_to_string(path).ok()?;
     let cc: CumulativeCache = serde_json::from_str(&data).ok()?;
-    if cc.d1.cost == 0.0 && cc.d7.cost == 0.0 && cc.d30.cost == 0.0 {
+    if cc.d1.cost == 0.0 && cc.d7.cost == 0.0 && cc.d30.cost == 0.0 && cc.d30.tokens == 0 {
         return None;
     }
     Some(CumulativeStats {
+        age_secs: file_age_secs(path),
         d1: cc.d1.cost,
         d7: cc.d7.cost,
         d30: cc.d30.cost,
+        d30_split: cc.d30.on_cost.zip(cc.d30.off_cost),
+        tokens: [cc.d1.tokens, cc.d7.tokens, cc.d30.tokens],
     })
 }
+
+/// Samples kept for the context-history sparkline.
+const CONTEXT_HISTORY_LEN: usize = 24;
+
+/// Append a context usage sample to the session's history and return it
+/// (oldest first). Repeated values are stored once, so the history tracks
+/// changes rather than render count.
+pub fn push_context_history(session_id: &str, pct: u8) -> Vec<u8> {
+    let dir = cache_dir();
+    let path = dir.join(format!("context-{}.json", file_id(session_id)));
+    let mut history: Vec<u8> = fs::read_to_string(&path)
+        .ok()
+        .and_then(|d| serde_json::from_str(&d).ok())
+        .unwrap_or_default();
+    if history.last() == Some(&pct) {
+        return history;
+    }
+    history.push(pct);
+    if history.len() > CONTEXT_HISTORY_LEN {
+        history.drain(..history.len() - CONTEXT_HISTORY_LEN);
+    }
+    let _ = fs::create_dir_all(&dir);
+    let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    if fs::write(&tmp, serde_json::to_string(&history).unwrap_or_default()).is_ok() {
+        let _ = fs::rename(&tmp, &path);
+    }
+    history
+}
+
+/// How long a writer waits for another process's lock before going ahead
+/// without it, so a render never hangs on a stuck peer.
+const LOCK_WAIT: Duration = Duration::from_millis(50);
+
+/// Run `f` holding an exclusive advisory lock on `<path>.lock` (flock, so
+/// it is released even when a holder dies). Runs unlocked when the lock
+/// cannot be taken within LOCK_WAIT or the directory is read-only.
+pub fn with_lock<R>(path: &Path, f: impl FnOnce() -> R) -> R {
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let lock = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        // Appended, not replacing `.json`: cumulative-stats.sh owns `all.lock`
+        .open(format!("{}.lock", path.display()));
+    #[cfg(not(target_arch = "wasm32"))]
+    let _held = lock.ok().filter(|file| {
+        let deadline = Instant::now() + LOCK_WAIT;
+        loop {
+            match rustix::fs::flock(file, rustix::fs::FlockOperation::NonBlockingLockExclusive) {
+                Ok(()) => return true,
+                Err(_) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(1)),
+                Err(_) => return false,
+            }
+        }
+    });
+    #[cfg(target_arch = "wasm32")]
+    drop(lock);
+    f()
+}
+
+/// Read-modify-write a JSON file shared between sessions: `f` gets the
+/// current value (None when missing or unreadable) and returns the new one,
+/// which replaces the file atomically. Under `with_lock`, so concurrent
+/// renders apply their updates in turn instead of overwriting each other.
+pub fn update_json<T: Serialize + DeserializeOwned>(
+    path: &Path,
+    f: impl FnOnce(Option<T>) -> T,
+) -> T {
+    with_lock(path, || {
+        let prev = fs::read_to_string(path)
+            .ok()
+            .and_then(|d| serde_json::from_str(&d).ok());
+        let next = f(prev);
+        let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+        if serde_json::to_vec(&next).is_ok_and(|d| fs::write(&tmp, d).is_ok()) {
+            let _ = fs::rename(&tmp, path);
+        }
+        next
+    })
+}
+
+/// Claim a once-a-day background job for `date`: true unless `marker`
+/// already holds `date` or another render claimed it less than `retry` ago.
+/// The job calls `finish_daily` once it has succeeded, so a failed spawn or
+/// POST is retried by a later render.
+pub fn claim_daily(marker: &Path, date: &str, retry: Duration) -> bool {
+    // Renders of other sessions may claim at the same moment
+    with_lock(marker, || {
+        if fs::read_to_string(marker).is_ok_and(|d| d.trim() == date) {
+            return false;
+        }
+        let pending = marker.with_extension("pending");
+        let claimed = fs::metadata(&pending)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.elapsed().ok())
+            .is_some_and(|age| age < retry);
+        !claimed && fs::write(&pending, date).is_ok()
+    })
+}
+
+/// Record that the job claimed with `claim_daily` is done for `date`.
+pub fn finish_daily(marker: &Path, date: &str) {
+    with_lock(marker, || {
+        let _ = fs::write(marker, date);
+        let _ = fs::remove_file(marker.with_extension("pending"));
+    })
+}
+
+/// Renders further apart than this end a stretch of continuous activity.
+const ACTIVITY_GAP_SECS: i64 = 10 * 60;
+
+#[derive(Serialize, Deserialize)]
+struct Activity {
+    start: i64,
+    last: i64,
+}
+
+/// Record a render at `now` and return when the current stretch of
+/// continuous activity began. Shared across sessions, since a break from one
+/// session while working in another is no break.
+pub fn touch_activity(now: i64) -> i64 {
+    let path = cache_dir().join("activity.json");
+    update_json(&path, |prev: Option<Activity>| {
+        let start = match prev {
+            Some(a) if now - a.last <= ACTIVITY_GAP_SECS => a.start,
+            _ => now,
+        };
+        Activity { start, last: now }
+    })
+    .start
+}
+
+/// Snapshot of the most recent session payload, used by `statusline watch`.
+pub fn last_session_path() -> PathBuf {
+    cache_dir().join("last-session.json")
+}
+
+/// Atomically replace the last-session snapshot, unless it already holds
+/// `raw` (renders repeat with the same payload). Privacy mode keeps no
+/// snapshot, since the payload holds the project path and transcript path.
+pub fn write_last_session(raw: &[u8]) {
+    if raw.is_empty() {
+        return;
+    }
+    if is_private() {
+        let _ = fs::remove_file(last_session_path());
+        return;
+    }
+    let path = last_session_path();
+    if fs::read(&path).is_ok_and(|prev| prev == raw) {
+        return;
+    }
+    let _ = fs::create_dir_all(cache_dir());
+    let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    if fs::write(&tmp, raw).is_ok() {
+        let _ = fs::rename(&tmp, &path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrent_updates_are_not_lost() {
+        let dir = env::temp_dir().join(format!("statusline-lock-{}", std::process::id()));
+        let path = dir.join("counter.json");
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..25 {
+                        update_json(&path, |n: Option<u32>| n.unwrap_or(0) + 1);
+                    }
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        let total: u32 = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(total, 100);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn daily_jobs_run_once_after_they_succeed() {
+        let dir = env::temp_dir().join(format!("statusline-daily-{}", std::process::id()));
+        let _ = fs::create_dir_all(&dir);
+        let marker = dir.join("last");
+        let retry = Duration::from_secs(60);
+        assert!(claim_daily(&marker, "2026-10-15", retry));
+        // Claimed but not finished: others wait, then retry
+        assert!(!claim_daily(&marker, "2026-10-15", retry));
+        assert!(claim_daily(&marker, "2026-10-15", Duration::ZERO));
+        finish_daily(&marker, "2026-10-15");
+        assert!(!claim_daily(&marker, "2026-10-15", Duration::ZERO));
+        assert!(claim_daily(&marker, "2026-10-16", retry));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn keyed_file_ids() {
+        assert_eq!(file_id("abc-123"), "abc-123");
+        let a = keyed_hash("k1", "abc-123");
+        assert_eq!(a.len(), 16);
+        assert_eq!(a, keyed_hash("k1", "abc-123"));
+        assert_ne!(a, keyed_hash("k2", "abc-123"));
+        assert!(!a.contains("abc"));
+    }
+}
@@ -0,0 +1,274 @@
+//! CI status for HEAD (STATUSLINE_CI): the render only reads
+//! `<cache dir>/ci-<hash>.json`; once it is older than STATUSLINE_CI_TTL or
+//! was written for another commit, a background job asks the forge (GitHub
+//! check runs, GitLab pipelines) and rewrites it.
+
+use crate::background;
+use crate::cache;
+use crate::config::Config;
+use crate::debuglog;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+#[derive(Serialize, Deserialize, Default)]
+struct Cached {
+    sha: String,
+    /// pass, fail, pending, none, or empty while the first fetch runs
+    state: String,
+}
+
+/// Cache for the repository at the current directory (background jobs
+/// inherit it), shared by all of its subdirectories.
+pub fn path() -> PathBuf {
+    let cwd = env::current_dir().unwrap_or_default();
+    let root = repo_root(&cwd);
+    cache::cache_dir().join(format!(
+        "ci-{}.json",
+        cache::file_id(&cache::project_hash(&root.to_string_lossy()))
+    ))
+}
+
+/// The nearest directory with a `.git` (a directory, or a file in linked
+/// worktrees), else `dir` itself.
+fn repo_root(dir: &Path) -> PathBuf {
+    dir.ancestors()
+        .find(|d| d.join(".git").exists())
+        .unwrap_or(dir)
+        .to_path_buf()
+}
+
+fn read() -> Option<Cached> {
+    serde_json::from_str(&fs::read_to_string(path()).ok()?).ok()
+}
+
+fn write(cached: &Cached) {
+    let path = path();
+    let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+    let _ = fs::create_dir_all(cache::cache_dir());
+    if serde_json::to_vec(cached).is_ok_and(|d| fs::write(&tmp, d).is_ok()) {
+        let _ = fs::rename(&tmp, &path);
+    }
+}
+
+/// The cached state for `head` (pass, fail or pending). Starts a refresh when
+/// the cache is stale or belongs to another commit; the cache is rewritten
+/// first so renders in the meantime don't spawn again.
+pub fn status(cfg: &Config, head: &str) -> Option<String> {
+    let cached = read().unwrap_or_default();
+    let current = cached.sha == head;
+    let stale = !current || cache::file_age_secs(&path()) >= cfg.ci_ttl;
+    if stale && !cfg.offline && background::spawn_allowed(cfg.no_spawn) {
+        write(&Cached {
+            sha: head.to_string(),
+            state: if current {
+                cached.state.clone()
+            } else {
+                String::new()
+            },
+        });
+        background::spawn_ci();
+    }
+    let state = cached.state;
+    (current && matches!(state.as_str(), "pass" | "fail" | "pending")).then_some(state)
+}
+
+/// Fetch the status of HEAD and write the cache (internal mode).
+pub fn refresh(cfg: &Config) {
+    if cfg.offline {
+        return;
+    }
+    let cwd = env::current_dir().unwrap_or_default();
+    let Some((sha, remote)) = crate::git::ci_target(&cwd.to_string_lossy()) else {
+        return;
+    };
+    let state = match fetch(&remote, &sha) {
+        Ok(state) => state,
+        Err(e) => {
+            debuglog::write(&format!("ci {}: {}", remote, e));
+            return;
+        }
+    };
+    write(&Cached { sha, state });
+}
+
+fn fetch(remote: &str, sha: &str) -> Result<String, String> {
+    let (host, repo) = forge_repo(remote).ok_or("not a GitHub or GitLab remote")?;
+    // Headers go in on stdin (-H @-), so the token is not in curl's argv
+    // where `ps` shows it
+    let mut headers = String::new();
+    let github = host == "github.com";
+    let url = if github {
+        if let Some(token) = token(&["GITHUB_TOKEN", "GH_TOKEN"]) {
+            headers = format!("Authorization: Bearer {}\n", token);
+        }
+        format!(
+            "https://api.github.com/repos/{}/commits/{}/check-runs?per_page=100",
+            repo, sha
+        )
+    } else if host.contains("gitlab") {
+        if let Some(token) = token(&["GITLAB_TOKEN"]) {
+            headers = format!("PRIVATE-TOKEN: {}\n", token);
+        }
+        format!(
+            "https://{}/api/v4/projects/{}/pipelines?sha={}&per_page=1",
+            host,
+            repo.replace('/', "%2F"),
+            sha
+        )
+    } else {
+        return Err("not a GitHub or GitLab remote".into());
+    };
+    let mut child = Command::new("curl")
+        .args(["-fsSL", "--max-time", "10", "-H", "@-", &url])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("cannot run curl: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(headers.as_bytes());
+    }
+    let out = child
+        .wait_with_output()
+        .map_err(|e| format!("cannot run curl: {}", e))?;
+    if !out.status.success() {
+        return Err(format!("request failed: {}", url));
+    }
+    let v: serde_json::Value =
+        serde_json::from_slice(&out.stdout).map_err(|_| "invalid response")?;
+    Ok(if github {
+        github_state(&v)
+    } else {
+        gitlab_state(&v)
+    }
+    .to_string())
+}
+
+fn token(keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .filter_map(|k| env::var(k).ok())
+        .find(|t| !t.is_empty())
+}
+
+/// Host and `owner/repo` path of an https, ssh or scp-style remote URL.
+fn forge_repo(url: &str) -> Option<(String, String)> {
+    let (host, path) = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?,
+        None => url.split_once(':')?,
+    };
+    let host = host.rsplit('@').next()?;
+    let host = host.split(':').next()?.to_lowercase();
+    let path = path.trim_end_matches('/').trim_end_matches(".git");
+    (!host.is_empty() && path.contains('/')).then(|| (host, path.to_string()))
+}
+
+/// Any failed run fails the commit; otherwise unfinished runs keep it pending.
+fn github_state(v: &serde_json::Value) -> &'static str {
+    let runs = v
+        .get("check_runs")
+        .and_then(|r| r.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let field = |run: &serde_json::Value, key: &str| {
+        run.get(key)
+            .and_then(|s| s.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    if runs.is_empty() {
+        "none"
+    } else if runs.iter().any(|r| {
+        matches!(
+            field(r, "conclusion").as_str(),
+            "failure" | "timed_out" | "cancelled" | "action_required"
+        )
+    }) {
+        "fail"
+    } else if runs.iter().any(|r| field(r, "status") != "completed") {
+        "pending"
+    } else {
+        "pass"
+    }
+}
+
+/// Status of the newest pipeline for the commit.
+fn gitlab_state(v: &serde_json::Value) -> &'static str {
+    let status = v
+        .get(0)
+        .and_then(|p| p.get("status"))
+        .and_then(|s| s.as_str());
+    match status {
+        None => "none",
+        Some("success") => "pass",
+        Some("failed" | "canceled") => "fail",
+        Some("skipped") => "none",
+        Some(_) => "pending",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn forge_repo_from_remote_urls() {
+        let gh = Some(("github.com".to_string(), "ridjex/statusline".to_string()));
+        assert_eq!(forge_repo("git@github.com:ridjex/statusline.git"), gh);
+        assert_eq!(forge_repo("https://github.com/ridjex/statusline"), gh);
+        assert_eq!(
+            forge_repo("ssh://git@github.com:22/ridjex/statusline.git"),
+            gh
+        );
+        assert_eq!(
+            forge_repo("https://user@gitlab.example.com/group/sub/app.git"),
+            Some(("gitlab.example.com".into(), "group/sub/app".into()))
+        );
+        assert_eq!(forge_repo("/srv/git/app.git"), None);
+    }
+
+    #[test]
+    fn subdirectories_share_the_repository_cache() {
+        let dir = env::temp_dir().join(format!("statusline-ci-{}", std::process::id()));
+        fs::create_dir_all(dir.join("repo/.git")).unwrap();
+        fs::create_dir_all(dir.join("repo/src/deep")).unwrap();
+        assert_eq!(repo_root(&dir.join("repo/src/deep")), dir.join("repo"));
+        assert_eq!(repo_root(&dir), dir);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn states_from_forge_responses() {
+        let run = |status: &str, conclusion: Option<&str>| json!({ "status": status, "conclusion": conclusion });
+        let runs = |r: Vec<serde_json::Value>| json!({ "check_runs": r });
+        assert_eq!(github_state(&runs(vec![])), "none");
+        assert_eq!(
+            github_state(&runs(vec![
+                run("completed", Some("success")),
+                run("completed", Some("skipped"))
+            ])),
+            "pass"
+        );
+        assert_eq!(
+            github_state(&runs(vec![
+                run("in_progress", None),
+                run("completed", Some("success"))
+            ])),
+            "pending"
+        );
+        assert_eq!(
+            github_state(&runs(vec![
+                run("in_progress", None),
+                run("completed", Some("failure"))
+            ])),
+            "fail"
+        );
+        assert_eq!(gitlab_state(&json!([])), "none");
+        assert_eq!(gitlab_state(&json!([{ "status": "running" }])), "pending");
+        assert_eq!(gitlab_state(&json!([{ "status": "failed" }])), "fail");
+    }
+}
@@ -0,0 +1,169 @@
+use jiff::civil::DateTime;
+use jiff::tz::TimeZone;
+use jiff::{Timestamp, Zoned};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Broken-down local time.
+pub struct LocalTime {
+    pub hour: u32,
+    pub minute: u32,
+}
+
+thread_local! {
+    // Time set by an embedding host (0 = unset); wasm32 has no system clock
+    static HOST_NOW: std::cell::Cell<i64> = const { std::cell::Cell::new(0) };
+}
+
+/// Use `ts` as the current time on this thread (the WASM build), or the
+/// system clock again with 0.
+pub fn set_now(ts: i64) {
+    HOST_NOW.with(|now| now.set(ts));
+}
+
+/// Current Unix time in seconds.
+pub fn now_unix() -> i64 {
+    let host = HOST_NOW.with(|now| now.get());
+    if host != 0 || cfg!(target_arch = "wasm32") {
+        return host;
+    }
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Unix seconds of an RFC 3339 timestamp (transcript entries).
+pub fn parse_timestamp(text: &str) -> Option<i64> {
+    text.parse::<Timestamp>().ok().map(|t| t.as_second())
+}
+
+/// `ts` in the system time zone (TZ, else /etc/localtime), resolved by jiff
+/// rather than libc so static musl builds behave the same.
+fn local_zoned(ts: i64) -> Zoned {
+    Timestamp::from_second(ts)
+        .unwrap_or(Timestamp::UNIX_EPOCH)
+        .to_zoned(TimeZone::system())
+}
+
+/// Convert a Unix timestamp to local time (honors TZ).
+pub fn local(ts: i64) -> LocalTime {
+    let z = local_zoned(ts);
+    LocalTime {
+        hour: z.hour() as u32,
+        minute: z.minute() as u32,
+    }
+}
+
+/// Local calendar date of a Unix timestamp, `YYYY-MM-DD`.
+pub fn local_date(ts: i64) -> String {
+    let z = local_zoned(ts);
+    format!("{:04}-{:02}-{:02}", z.year(), z.month(), z.day())
+}
+
+/// Minutes since midnight for `HH` or `HH:MM`.
+pub fn minutes(text: &str) -> Option<u32> {
+    let (h, m) = text.trim().split_once(':').unwrap_or((text.trim(), "0"));
+    let (h, m) = (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?);
+    (h <= 24 && m < 60).then_some(h * 60 + m)
+}
+
+/// Unix time of the local midnight that starts the day of `ts`.
+pub fn day_start(ts: i64) -> i64 {
+    let z = local_zoned(ts);
+    z.start_of_day().map_or(ts, |d| d.timestamp().as_second())
+}
+
+/// Starts of the cumulative d1/d7/d30 periods for STATUSLINE_DAY_BOUNDARY:
+/// `rolling` windows of 1, 7 and 30 × 24h; `midnight`, the last 1, 7 and 30
+/// local days including today (23 or 25 hours apart across DST changes); or
+/// `calendar`, today, this week (from Monday) and this month.
+pub fn period_starts(now: i64, boundary: &str) -> [i64; 3] {
+    period_starts_in(&TimeZone::system(), now, boundary)
+}
+
+fn period_starts_in(tz: &TimeZone, now: i64, boundary: &str) -> [i64; 3] {
+    let rolling = [1, 7, 30].map(|days| now - days * 86_400);
+    let today = Timestamp::from_second(now)
+        .unwrap_or(Timestamp::UNIX_EPOCH)
+        .to_zoned(tz.clone())
+        .start_of_day();
+    let Ok(today) = today else {
+        return rolling;
+    };
+    let days_back = |days: i64| {
+        today
+            .checked_sub(jiff::Span::new().days(days))
+            .map_or(now, |d| d.timestamp().as_second())
+    };
+    match boundary {
+        "midnight" => [days_back(0), days_back(6), days_back(29)],
+        "calendar" => [
+            days_back(0),
+            days_back(today.weekday().to_monday_zero_offset() as i64),
+            days_back(today.day() as i64 - 1),
+        ],
+        _ => rolling,
+    }
+}
+
+/// Unix time of the local Monday midnight that starts the week of `ts`.
+pub fn week_start(ts: i64) -> i64 {
+    let days_since_monday = local_zoned(ts).weekday().to_monday_zero_offset() as i64;
+    day_start(day_start(ts) - days_since_monday * 86_400 + 12 * 3600)
+}
+
+/// Unix time of a local calendar date and time (honors TZ and DST), or None
+/// for a date that does not exist.
+pub fn local_unix(year: i32, month: i32, day: i32, hour: i32, minute: i32) -> Option<i64> {
+    let dt = DateTime::new(
+        i16::try_from(year).ok()?,
+        i8::try_from(month).ok()?,
+        i8::try_from(day).ok()?,
+        i8::try_from(hour).ok()?,
+        i8::try_from(minute).ok()?,
+        0,
+        0,
+    )
+    .ok()?;
+    let zoned = dt.to_zoned(TimeZone::system()).ok()?;
+    Some(zoned.timestamp().as_second())
+}
+
+/// Current local time.
+pub fn now() -> LocalTime {
+    local(now_unix())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn period_starts_follow_local_midnight_across_dst() {
+        let berlin = TimeZone::posix("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+        // Monday 2026-03-30 10:00 CEST, the day after clocks went forward
+        let now = 1_774_857_600;
+        let day = 86_400;
+        assert_eq!(
+            period_starts_in(&berlin, now, "rolling"),
+            [now - day, now - 7 * day, now - 30 * day]
+        );
+        // 2026-03-30 00:00 CEST; 2026-03-24 and 2026-03-01 00:00 CET, six
+        // calendar days before today's midnight being 6 x 24h - 1h
+        assert_eq!(
+            period_starts_in(&berlin, now, "midnight"),
+            [1_774_821_600, 1_774_306_800, 1_772_319_600]
+        );
+        // Monday: the week starts today; the month on 2026-03-01
+        assert_eq!(
+            period_starts_in(&berlin, now, "calendar"),
+            [1_774_821_600, 1_774_821_600, 1_772_319_600]
+        );
+        // Wednesday 2026-04-01: the week from Monday, the month from today
+        let wednesday = 1_775_030_400;
+        assert_eq!(
+            period_starts_in(&berlin, wednesday, "calendar"),
+            [1_774_994_400, 1_774_821_600, 1_774_994_400]
+        );
+    }
+}
@@ -1,7 +1,15 @@
+use crate::format::Precision;
 use std::collections::HashMap;
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Values of the cumulative settings; the first is the default. The schema
+/// lists the same.
+pub const DAY_BOUNDARIES: &[&str] = &["rolling", "midnight", "calendar"];
+pub const CUMULATIVE_UNITS: &[&str] = &["cost", "tokens", "both"];
+pub const CUMULATIVE_FORMATS: &[&str] = &["compact", "labeled", "today"];
+pub const CUMULATIVE_SCOPES: &[&str] = &["both", "project", "global"];
 
 pub struct Config {
     pub show_model: bool,
@@ -12,18 +20,192 @@ pub struct Config {
     pub show_git: bool,
     pub show_diff: bool,
     pub line2: bool,
+    /// STATUSLINE_LINE2=auto: omit line 2 until it has data to show
+    pub line2_auto: bool,
     pub show_tokens: bool,
     pub show_speed: bool,
     pub show_cumulative: bool,
     pub no_color: bool,
+    pub offline: bool,
+    pub no_spawn: bool,
+    pub stale_secs: u64,
+    pub trace: bool,
+    pub sparkline: bool,
+    pub graphics: String,
+    pub output: String,
+    pub write_to: String,
+    pub render_cache: bool,
+    pub max_width: usize,
+    pub layout: String,
+    pub mood: bool,
+    pub budget: f64,
+    pub rules: String,
+    pub post_hook: String,
+    pub transcript_roots: String,
+    pub model_aliases: String,
+    pub account: bool,
+    pub account_expect: String,
+    pub work_hours: String,
+    pub holidays: String,
+    /// Cumulative periods: "rolling" (24h windows), "midnight" (local days)
+    /// or "calendar" (today, this week, this month)
+    pub day_boundary: String,
+    /// Cumulative figures: "cost", "tokens" or "both"
+    pub cumulative_unit: String,
+    /// Show what the session would cost without prompt caching
+    pub uncached_cost: bool,
+    /// Cumulative layout: "compact" ($4.2/$31/$187), "labeled" or "today"
+    pub cumulative_format: String,
+    /// Cumulative totals shown: "both", "project" (⌂) or "global" (Σ)
+    pub cumulative_scope: String,
+    pub history: bool,
+    /// Keyed hashes in cache file names, paths redacted in exports
+    pub privacy: bool,
+    pub digest: bool,
+    pub digest_webhook: String,
+    /// Opt-in daily report of coarse aggregates (telemetry.rs)
+    pub telemetry: bool,
+    pub telemetry_url: String,
+    pub break_after: u64,
+    pub bell: String,
+    pub quiet_hours: String,
+    pub lang: String,
+    pub labels: String,
+    /// Glyph overrides: `ascii` and/or `key=text` pairs (symbols.rs)
+    pub symbols: String,
+    pub rtl: bool,
+    pub precision: Precision,
+    pub session_start: String,
+    pub workday_end: String,
+    pub plan_tokens: u64,
+    pub fit_terminal: bool,
+    pub worktree_pattern: String,
+    pub default_branch: String,
+    pub git_walk_limit: usize,
+    pub dirty_scope: String,
+    pub dirty_ignore: String,
+    pub untracked: String,
+    pub protected_branches: String,
+    pub commits_today: bool,
+    pub stash_detail: String,
+    pub signed: bool,
+    pub large_file_mb: u64,
+    pub lfs_pointers: bool,
+    pub ci: bool,
+    pub ci_ttl: u64,
+    pub ticket: bool,
+    pub ticket_patterns: String,
+    pub ticket_url: String,
+    pub branch_icons: String,
+    pub branch_truncate: String,
+    pub files_touched: bool,
+    pub awaiting_secs: u64,
+    pub hide_when_zero: String,
+    pub warm_start_kb: u64,
     pub show_version: bool,
     pub show_help: bool,
+    pub describe_segments: bool,
+    pub self_profile: bool,
+    pub explain: bool,
+    pub command: String,
+    pub command_args: Vec<String>,
     pub internal_refresh_models: bool,
+    pub internal_digest: bool,
+    pub internal_ci: bool,
+    pub internal_telemetry: bool,
     pub internal_session_id: String,
     pub internal_transcript_path: String,
 }
 
-const ENV_KEYS: &[&str] = &[
+pub const ENV_KEYS: &[&str] = &[
+    "STATUSLINE_SHOW_MODEL",
+    "STATUSLINE_SHOW_MODEL_BARS",
+    "STATUSLINE_SHOW_CONTEXT",
+    "STATUSLINE_SHOW_COST",
+    "STATUSLINE_SHOW_DURATION",
+    "STATUSLINE_SHOW_GIT",
+    "STATUSLINE_SHOW_DIFF",
+    "STATUSLINE_LINE2",
+    "STATUSLINE_SHOW_TOKENS",
+    "STATUSLINE_SHOW_SPEED",
+    "STATUSLINE_SHOW_CUMULATIVE",
+    "STATUSLINE_OFFLINE",
+    "STATUSLINE_NO_SPAWN",
+    "STATUSLINE_STALE_SECS",
+    "STATUSLINE_TRACE",
+    "STATUSLINE_SPARKLINE",
+    "STATUSLINE_GRAPHICS",
+    "STATUSLINE_OUTPUT",
+    "STATUSLINE_WRITE_TO",
+    "STATUSLINE_RENDER_CACHE",
+    "STATUSLINE_MAX_WIDTH",
+    "STATUSLINE_LAYOUT",
+    "STATUSLINE_MOOD",
+    "STATUSLINE_BUDGET",
+    "STATUSLINE_RULES",
+    "STATUSLINE_POST_HOOK",
+    "STATUSLINE_TRANSCRIPT_ROOTS",
+    "STATUSLINE_MODEL_ALIASES",
+    "STATUSLINE_ACCOUNT",
+    "STATUSLINE_ACCOUNT_EXPECT",
+    "STATUSLINE_WORK_HOURS",
+    "STATUSLINE_HOLIDAYS",
+    "STATUSLINE_HISTORY",
+    "STATUSLINE_DIGEST",
+    "STATUSLINE_DIGEST_WEBHOOK",
+    "STATUSLINE_BREAK_AFTER",
+    "STATUSLINE_BELL",
+    "STATUSLINE_QUIET_HOURS",
+    "STATUSLINE_LANG",
+    "STATUSLINE_LABELS",
+    "STATUSLINE_RTL",
+    "STATUSLINE_COST_CENTS",
+    "STATUSLINE_PCT_DECIMALS",
+    "STATUSLINE_TOKEN_WIDTH",
+    "STATUSLINE_TOKENS_EXACT",
+    "STATUSLINE_DURATION_SECONDS",
+    "STATUSLINE_SESSION_START",
+    "STATUSLINE_WORKDAY_END",
+    "STATUSLINE_PLAN_TOKENS",
+    "STATUSLINE_FIT_TERMINAL",
+    "STATUSLINE_WORKTREE_PATTERN",
+    "STATUSLINE_DEFAULT_BRANCH",
+    "STATUSLINE_GIT_WALK_LIMIT",
+    "STATUSLINE_DIRTY_SCOPE",
+    "STATUSLINE_DIRTY_IGNORE",
+    "STATUSLINE_UNTRACKED",
+    "STATUSLINE_PROTECTED_BRANCHES",
+    "STATUSLINE_COMMITS_TODAY",
+    "STATUSLINE_STASH_DETAIL",
+    "STATUSLINE_SIGNED",
+    "STATUSLINE_LARGE_FILE_MB",
+    "STATUSLINE_LFS_POINTERS",
+    "STATUSLINE_CI",
+    "STATUSLINE_CI_TTL",
+    "STATUSLINE_TICKET",
+    "STATUSLINE_TICKET_PATTERNS",
+    "STATUSLINE_TICKET_URL",
+    "STATUSLINE_BRANCH_ICONS",
+    "STATUSLINE_BRANCH_TRUNCATE",
+    "STATUSLINE_FILES",
+    "STATUSLINE_AWAITING_SECS",
+    "STATUSLINE_HIDE_WHEN_ZERO",
+    "STATUSLINE_WARM_START_KB",
+    "STATUSLINE_DAY_BOUNDARY",
+    "STATUSLINE_CUMULATIVE_UNIT",
+    "STATUSLINE_UNCACHED_COST",
+    "STATUSLINE_CUMULATIVE_FORMAT",
+    "STATUSLINE_CUMULATIVE_SCOPE",
+    "STATUSLINE_SYMBOLS",
+    "STATUSLINE_PRIVACY",
+    "STATUSLINE_TELEMETRY",
+    "STATUSLINE_TELEMETRY_URL",
+];
+
+/// Keys the session JSON's `statusline_options` may set: display settings
+/// only. Keys that run commands, write files, reach the network or change
+/// what is recorded stay with the environment and the config file.
+pub const OPTION_KEYS: &[&str] = &[
     "STATUSLINE_SHOW_MODEL",
     "STATUSLINE_SHOW_MODEL_BARS",
     "STATUSLINE_SHOW_CONTEXT",
@@ -35,9 +217,67 @@ const ENV_KEYS: &[&str] = &[
     "STATUSLINE_SHOW_TOKENS",
     "STATUSLINE_SHOW_SPEED",
     "STATUSLINE_SHOW_CUMULATIVE",
+    "STATUSLINE_SPARKLINE",
+    "STATUSLINE_OUTPUT",
+    "STATUSLINE_MAX_WIDTH",
+    "STATUSLINE_LAYOUT",
+    "STATUSLINE_MOOD",
+    "STATUSLINE_LANG",
+    "STATUSLINE_LABELS",
+    "STATUSLINE_RTL",
+    "STATUSLINE_COST_CENTS",
+    "STATUSLINE_PCT_DECIMALS",
+    "STATUSLINE_TOKEN_WIDTH",
+    "STATUSLINE_TOKENS_EXACT",
+    "STATUSLINE_DURATION_SECONDS",
+    "STATUSLINE_FIT_TERMINAL",
+    "STATUSLINE_BRANCH_ICONS",
+    "STATUSLINE_BRANCH_TRUNCATE",
+    "STATUSLINE_FILES",
+    "STATUSLINE_HIDE_WHEN_ZERO",
+    "STATUSLINE_CUMULATIVE_UNIT",
+    "STATUSLINE_UNCACHED_COST",
+    "STATUSLINE_CUMULATIVE_FORMAT",
+    "STATUSLINE_CUMULATIVE_SCOPE",
+    "STATUSLINE_SYMBOLS",
 ];
 
+impl Config {
+    /// Whether a render reads the per-model cache. When not, the transcript is
+    /// neither located nor parsed and no refresh job runs.
+    pub fn needs_models(&self) -> bool {
+        let full = self.layout != "minimal";
+        self.mood
+            || (full && (self.show_model_bars || self.files_touched))
+            || (full && self.show_cost && self.uncached_cost)
+            || (full && self.line2 && self.show_tokens)
+    }
+
+    /// Whether a render reads the cumulative caches, and so whether
+    /// cumulative-stats.sh needs to run.
+    pub fn needs_cumulative(&self) -> bool {
+        self.layout != "minimal" && self.line2 && self.show_cumulative
+    }
+}
+
 pub fn load(args: &[String]) -> Config {
+    load_with(args, &HashMap::new())
+}
+
+/// Load with `options` (from the stdin JSON) on top of file and env, at the
+/// same priority as command-line flags, which are applied after them.
+pub fn load_with(args: &[String], options: &HashMap<String, String>) -> Config {
+    load_layers(args, options, true)
+}
+
+/// Defaults with only `options` applied: no environment, config file or
+/// arguments, for hosts that embed the renderer (`embed`).
+pub fn from_options(options: &HashMap<String, String>) -> Config {
+    load_layers(&[], options, false)
+}
+
+/// `host` reads the environment and ~/.claude/statusline.env under `options`.
+fn load_layers(args: &[String], options: &HashMap<String, String>, host: bool) -> Config {
     let mut cfg = Config {
         show_model: true,
         show_model_bars: true,
@@ -47,30 +287,109 @@ pub fn load(args: &[String]) -> Config {
         show_git: true,
         show_diff: true,
         line2: true,
+        line2_auto: false,
         show_tokens: true,
         show_speed: true,
         show_cumulative: true,
         no_color: false,
+        offline: false,
+        no_spawn: false,
+        stale_secs: 0,
+        trace: false,
+        sparkline: false,
+        graphics: String::new(),
+        output: String::new(),
+        write_to: String::new(),
+        render_cache: false,
+        max_width: 0,
+        layout: String::new(),
+        mood: false,
+        budget: 0.0,
+        rules: String::new(),
+        post_hook: String::new(),
+        transcript_roots: String::new(),
+        model_aliases: String::new(),
+        account: false,
+        account_expect: String::new(),
+        work_hours: String::new(),
+        holidays: String::new(),
+        day_boundary: "rolling".into(),
+        cumulative_unit: "cost".into(),
+        uncached_cost: false,
+        cumulative_format: "compact".into(),
+        cumulative_scope: "both".into(),
+        history: false,
+        privacy: false,
+        digest: false,
+        digest_webhook: String::new(),
+        telemetry: false,
+        telemetry_url: String::new(),
+        break_after: 0,
+        bell: String::new(),
+        quiet_hours: String::new(),
+        lang: String::new(),
+        labels: String::new(),
+        symbols: String::new(),
+        rtl: false,
+        precision: Precision::default(),
+        session_start: String::new(),
+        workday_end: String::new(),
+        plan_tokens: 0,
+        fit_terminal: false,
+        worktree_pattern: "/.worktrees/".into(),
+        default_branch: String::new(),
+        git_walk_limit: 1000,
+        dirty_scope: "repo".into(),
+        dirty_ignore: String::new(),
+        untracked: "git".into(),
+        protected_branches: "main,master".into(),
+        commits_today: false,
+        stash_detail: "off".into(),
+        signed: false,
+        large_file_mb: 0,
+        lfs_pointers: false,
+        ci: false,
+        ci_ttl: 300,
+        ticket: false,
+        ticket_patterns: "[A-Z][A-Z0-9]+-[0-9]+ #([0-9]+)".into(),
+        ticket_url: String::new(),
+        branch_icons: String::new(),
+        branch_truncate: "tail".into(),
+        files_touched: false,
+        awaiting_secs: 0,
+        hide_when_zero: String::new(),
+        warm_start_kb: 256,
         show_version: false,
         show_help: false,
+        describe_segments: false,
+        self_profile: false,
+        explain: false,
+        command: String::new(),
+        command_args: Vec::new(),
         internal_refresh_models: false,
+        internal_digest: false,
+        internal_ci: false,
+        internal_telemetry: false,
         internal_session_id: String::new(),
         internal_transcript_path: String::new(),
     };
 
     // Save env overrides before loading config file
     let mut env_overrides: HashMap<String, String> = HashMap::new();
-    for &key in ENV_KEYS {
-        if let Ok(val) = env::var(key) {
-            env_overrides.insert(key.to_string(), val);
+    if host {
+        for &key in ENV_KEYS {
+            if let Ok(val) = env::var(key) {
+                env_overrides.insert(key.to_string(), val);
+            }
         }
     }
 
     // Load config file
-    let cfg_path = env::var("HOME")
-        .map(|h| PathBuf::from(h).join(".claude").join("statusline.env"))
-        .unwrap_or_default();
-    let file_vals = load_env_file(&cfg_path);
+    let file_vals = if host {
+        load_env_file(&config_path())
+    } else {
+        HashMap::new()
+    };
 
     // Merge: file < env
     let mut merged: HashMap<String, String> = HashMap::new();
@@ -80,6 +399,9 @@ pub fn load(args: &[String]) -> Config {
     for (k, v) in &env_overrides {
         merged.insert(k.clone(), v.clone());
     }
+    for (k, v) in options {
+        merged.insert(k.clone(), v.clone());
+    }
 
     // Apply merged values
     apply_bool(&merged, "STATUSLINE_SHOW_MODEL", &mut cfg.show_model);
@@ -94,6 +416,7 @@ pub fn load(args: &[String]) -> Config {
     apply_bool(&merged, "STATUSLINE_SHOW_GIT", &mut cfg.show_git);
     apply_bool(&merged, "STATUSLINE_SHOW_DIFF", &mut cfg.show_diff);
     apply_bool(&merged, "STATUSLINE_LINE2", &mut cfg.line2);
+    cfg.line2_auto = merged.get("STATUSLINE_LINE2").is_some_and(|v| v == "auto");
     apply_bool(&merged, "STATUSLINE_SHOW_TOKENS", &mut cfg.show_tokens);
     apply_bool(&merged, "STATUSLINE_SHOW_SPEED", &mut cfg.show_speed);
     apply_bool(
@@ -101,8 +424,166 @@ pub fn load(args: &[String]) -> Config {
         "STATUSLINE_SHOW_CUMULATIVE",
         &mut cfg.show_cumulative,
     );
+    apply_opt_in(&merged, "STATUSLINE_OFFLINE", &mut cfg.offline);
+    apply_opt_in(&merged, "STATUSLINE_NO_SPAWN", &mut cfg.no_spawn);
+    apply_parse(&merged, "STATUSLINE_STALE_SECS", &mut cfg.stale_secs);
+    apply_opt_in(&merged, "STATUSLINE_TRACE", &mut cfg.trace);
+    apply_opt_in(&merged, "STATUSLINE_SPARKLINE", &mut cfg.sparkline);
+    apply_parse(&merged, "STATUSLINE_GRAPHICS", &mut cfg.graphics);
+    apply_parse(&merged, "STATUSLINE_OUTPUT", &mut cfg.output);
+    apply_parse(&merged, "STATUSLINE_WRITE_TO", &mut cfg.write_to);
+    apply_opt_in(&merged, "STATUSLINE_RENDER_CACHE", &mut cfg.render_cache);
+    apply_parse(&merged, "STATUSLINE_MAX_WIDTH", &mut cfg.max_width);
+    apply_parse(&merged, "STATUSLINE_LAYOUT", &mut cfg.layout);
+    apply_opt_in(&merged, "STATUSLINE_MOOD", &mut cfg.mood);
+    apply_parse(&merged, "STATUSLINE_BUDGET", &mut cfg.budget);
+    apply_parse(&merged, "STATUSLINE_RULES", &mut cfg.rules);
+    apply_parse(&merged, "STATUSLINE_POST_HOOK", &mut cfg.post_hook);
+    apply_parse(
+        &merged,
+        "STATUSLINE_TRANSCRIPT_ROOTS",
+        &mut cfg.transcript_roots,
+    );
+    apply_parse(&merged, "STATUSLINE_MODEL_ALIASES", &mut cfg.model_aliases);
+    apply_opt_in(&merged, "STATUSLINE_ACCOUNT", &mut cfg.account);
+    apply_parse(
+        &merged,
+        "STATUSLINE_ACCOUNT_EXPECT",
+        &mut cfg.account_expect,
+    );
+    apply_parse(&merged, "STATUSLINE_WORK_HOURS", &mut cfg.work_hours);
+    apply_parse(&merged, "STATUSLINE_HOLIDAYS", &mut cfg.holidays);
+    apply_choice(
+        &merged,
+        "STATUSLINE_DAY_BOUNDARY",
+        DAY_BOUNDARIES,
+        &mut cfg.day_boundary,
+    );
+    apply_choice(
+        &merged,
+        "STATUSLINE_CUMULATIVE_UNIT",
+        CUMULATIVE_UNITS,
+        &mut cfg.cumulative_unit,
+    );
+    apply_opt_in(&merged, "STATUSLINE_UNCACHED_COST", &mut cfg.uncached_cost);
+    apply_choice(
+        &merged,
+        "STATUSLINE_CUMULATIVE_FORMAT",
+        CUMULATIVE_FORMATS,
+        &mut cfg.cumulative_format,
+    );
+    apply_choice(
+        &merged,
+        "STATUSLINE_CUMULATIVE_SCOPE",
+        CUMULATIVE_SCOPES,
+        &mut cfg.cumulative_scope,
+    );
+    apply_opt_in(&merged, "STATUSLINE_HISTORY", &mut cfg.history);
+    apply_opt_in(&merged, "STATUSLINE_PRIVACY", &mut cfg.privacy);
+    apply_opt_in(&merged, "STATUSLINE_DIGEST", &mut cfg.digest);
+    apply_parse(
+        &merged,
+        "STATUSLINE_DIGEST_WEBHOOK",
+        &mut cfg.digest_webhook,
+    );
+    apply_opt_in(&merged, "STATUSLINE_TELEMETRY", &mut cfg.telemetry);
+    apply_parse(&merged, "STATUSLINE_TELEMETRY_URL", &mut cfg.telemetry_url);
+    apply_parse(&merged, "STATUSLINE_BREAK_AFTER", &mut cfg.break_after);
+    apply_parse(&merged, "STATUSLINE_BELL", &mut cfg.bell);
+    apply_parse(&merged, "STATUSLINE_QUIET_HOURS", &mut cfg.quiet_hours);
+    apply_parse(&merged, "STATUSLINE_LANG", &mut cfg.lang);
+    apply_parse(&merged, "STATUSLINE_LABELS", &mut cfg.labels);
+    apply_parse(&merged, "STATUSLINE_SYMBOLS", &mut cfg.symbols);
+    apply_opt_in(&merged, "STATUSLINE_RTL", &mut cfg.rtl);
+    apply_opt_in(&merged, "STATUSLINE_COST_CENTS", &mut cfg.precision.cents);
+    apply_parse(
+        &merged,
+        "STATUSLINE_PCT_DECIMALS",
+        &mut cfg.precision.pct_decimals,
+    );
+    apply_parse(
+        &merged,
+        "STATUSLINE_TOKEN_WIDTH",
+        &mut cfg.precision.token_width,
+    );
+    apply_opt_in(
+        &merged,
+        "STATUSLINE_TOKENS_EXACT",
+        &mut cfg.precision.tokens_exact,
+    );
+    apply_parse(
+        &merged,
+        "STATUSLINE_DURATION_SECONDS",
+        &mut cfg.precision.seconds_below,
+    );
+    apply_parse(&merged, "STATUSLINE_SESSION_START", &mut cfg.session_start);
+    apply_parse(&merged, "STATUSLINE_WORKDAY_END", &mut cfg.workday_end);
+    apply_parse(&merged, "STATUSLINE_PLAN_TOKENS", &mut cfg.plan_tokens);
+    apply_opt_in(&merged, "STATUSLINE_FIT_TERMINAL", &mut cfg.fit_terminal);
+    apply_parse(
+        &merged,
+        "STATUSLINE_WORKTREE_PATTERN",
+        &mut cfg.worktree_pattern,
+    );
+    apply_parse(
+        &merged,
+        "STATUSLINE_DEFAULT_BRANCH",
+        &mut cfg.default_branch,
+    );
+    apply_parse(
+        &merged,
+        "STATUSLINE_GIT_WALK_LIMIT",
+        &mut cfg.git_walk_limit,
+    );
+    apply_parse(&merged, "STATUSLINE_DIRTY_SCOPE", &mut cfg.dirty_scope);
+    apply_parse(&merged, "STATUSLINE_DIRTY_IGNORE", &mut cfg.dirty_ignore);
+    apply_parse(&merged, "STATUSLINE_UNTRACKED", &mut cfg.untracked);
+    apply_parse(
+        &merged,
+        "STATUSLINE_PROTECTED_BRANCHES",
+        &mut cfg.protected_branches,
+    );
+    apply_opt_in(&merged, "STATUSLINE_COMMITS_TODAY", &mut cfg.commits_today);
+    apply_parse(&merged, "STATUSLINE_STASH_DETAIL", &mut cfg.stash_detail);
+    apply_opt_in(&merged, "STATUSLINE_SIGNED", &mut cfg.signed);
+    apply_parse(&merged, "STATUSLINE_LARGE_FILE_MB", &mut cfg.large_file_mb);
+    apply_opt_in(&merged, "STATUSLINE_LFS_POINTERS", &mut cfg.lfs_pointers);
+    apply_opt_in(&merged, "STATUSLINE_CI", &mut cfg.ci);
+    apply_parse(&merged, "STATUSLINE_CI_TTL", &mut cfg.ci_ttl);
+    apply_opt_in(&merged, "STATUSLINE_TICKET", &mut cfg.ticket);
+    apply_parse(
+        &merged,
+        "STATUSLINE_TICKET_PATTERNS",
+        &mut cfg.ticket_patterns,
+    );
+    apply_parse(&merged, "STATUSLINE_TICKET_URL", &mut cfg.ticket_url);
+    apply_parse(&merged, "STATUSLINE_BRANCH_ICONS", &mut cfg.branch_icons);
+    apply_parse(
+        &merged,
+        "STATUSLINE_BRANCH_TRUNCATE",
+        &mut cfg.branch_truncate,
+    );
+    apply_opt_in(&merged, "STATUSLINE_FILES", &mut cfg.files_touched);
+    apply_parse(&merged, "STATUSLINE_AWAITING_SECS", &mut cfg.awaiting_secs);
+    apply_parse(
+        &merged,
+        "STATUSLINE_HIDE_WHEN_ZERO",
+        &mut cfg.hide_when_zero,
+    );
+    apply_parse(&merged, "STATUSLINE_WARM_START_KB", &mut cfg.warm_start_kb);
 
-    // CLI args (highest priority) — manual parsing, no clap
+    // Subcommand: first positional arg, remaining args belong to it
+    if let Some(first) = args.first() {
+        if !first.starts_with('-') {
+            cfg.command = first.clone();
+            cfg.command_args = args[1..].to_vec();
+        }
+    }
+
+    // CLI args (highest priority) — manual parsing, no clap. A subcommand's
+    // arguments are its own (`self-update --version v2.1.0`), so global
+    // flags are only read when there is none
+    let args = if cfg.command.is_empty() { args } else { &[] };
     let mut i = 0;
     while i < args.len() {
         match args[i].as_str() {
@@ -118,9 +599,35 @@ pub fn load(args: &[String]) -> Config {
             "--no-speed" => cfg.show_speed = false,
             "--no-cumulative" => cfg.show_cumulative = false,
             "--no-color" => cfg.no_color = true,
+            "--offline" => cfg.offline = true,
+            "--no-spawn" => cfg.no_spawn = true,
             "--version" => cfg.show_version = true,
             "--help" => cfg.show_help = true,
+            "--describe-segments" => cfg.describe_segments = true,
+            "--self-profile" => cfg.self_profile = true,
+            "--explain" => cfg.explain = true,
             "--internal-refresh-models" => cfg.internal_refresh_models = true,
+            "--internal-digest" => cfg.internal_digest = true,
+            "--internal-ci" => cfg.internal_ci = true,
+            "--internal-telemetry" => cfg.internal_telemetry = true,
+            "--output" => {
+                i += 1;
+                if i < args.len() {
+                    cfg.output = args[i].clone();
+                }
+            }
+            "--layout" => {
+                i += 1;
+                if i < args.len() {
+                    cfg.layout = args[i].clone();
+                }
+            }
+            "--write-to" => {
+                i += 1;
+                if i < args.len() {
+                    cfg.write_to = args[i].clone();
+                }
+            }
             "--session-id" => {
                 i += 1;
                 if i < args.len() {
@@ -139,16 +646,76 @@ pub fn load(args: &[String]) -> Config {
     }
 
     // NO_COLOR env
-    if env::var("NO_COLOR").is_ok() {
-        cfg.no_color = true;
-    }
-    if env::var("STATUSLINE_NO_COLOR").is_ok() {
+    if host && (env::var("NO_COLOR").is_ok() || env::var("STATUSLINE_NO_COLOR").is_ok()) {
         cfg.no_color = true;
     }
 
     cfg
 }
 
+/// Path of the user config file (~/.claude/statusline.env).
+pub fn config_path() -> PathBuf {
+    env::var("HOME")
+        .map(|h| PathBuf::from(h).join(".claude").join("statusline.env"))
+        .unwrap_or_default()
+}
+
+/// Config values from the stdin JSON's `statusline_options`, keyed by full
+/// name (`STATUSLINE_LAYOUT`) or short name (`layout`, `show_git`). Keys
+/// outside OPTION_KEYS and non-scalar values are dropped.
+pub fn stdin_options(options: &HashMap<String, serde_json::Value>) -> HashMap<String, String> {
+    options
+        .iter()
+        .filter_map(|(name, value)| {
+            let key = name.to_ascii_uppercase();
+            let key = if key.starts_with("STATUSLINE_") {
+                key
+            } else {
+                format!("STATUSLINE_{}", key)
+            };
+            let key = *OPTION_KEYS.iter().find(|k| **k == key)?;
+            let value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                serde_json::Value::Bool(b) => b.to_string(),
+                serde_json::Value::Number(n) => n.to_string(),
+                _ => return None,
+            };
+            Some((key.to_string(), value))
+        })
+        .collect()
+}
+
+/// The layer that set `key` and its raw value: `session options` (the
+/// stdin JSON's `statusline_options`, as from `stdin_options`), `env` or
+/// `file` (~/.claude/statusline.env). None means the built-in default.
+pub fn source(key: &str, options: &HashMap<String, String>) -> Option<(&'static str, String)> {
+    if let Some(v) = options.get(key) {
+        return Some(("session options", v.clone()));
+    }
+    if let Ok(v) = env::var(key) {
+        return Some(("env", v));
+    }
+    file_value(key).map(|v| ("file", v))
+}
+
+/// Raw value of `key` in the config file, if set there.
+pub fn file_value(key: &str) -> Option<String> {
+    file_values().remove(key)
+}
+
+/// All values from the config file and the files it includes.
+pub fn file_values() -> HashMap<String, String> {
+    load_env_file(&config_path())
+}
+
+/// Value following `name` in an argument list (`--name value`).
+pub fn arg_value(args: &[String], name: &str) -> Option<String> {
+    args.iter()
+        .position(|a| a == name)
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+}
+
 fn apply_bool(m: &HashMap<String, String>, key: &str, target: &mut bool) {
     if let Some(v) = m.get(key) {
         if v == "false" {
@@ -157,22 +724,331 @@ fn apply_bool(m: &HashMap<String, String>, key: &str, target: &mut bool) {
     }
 }
 
-fn load_env_file(path: &PathBuf) -> HashMap<String, String> {
+/// Enable an opt-in feature (off by default) when set to "true" or "1".
+fn apply_opt_in(m: &HashMap<String, String>, key: &str, target: &mut bool) {
+    if let Some(v) = m.get(key) {
+        *target = v == "true" || v == "1";
+    }
+}
+
+/// Take one of `choices`, keeping the default for anything else.
+fn apply_choice(m: &HashMap<String, String>, key: &str, choices: &[&str], target: &mut String) {
+    if let Some(v) = m.get(key).filter(|v| choices.contains(&v.as_str())) {
+        *target = v.clone();
+    }
+}
+
+/// Parse a numeric (or other FromStr) value, keeping the default when invalid.
+fn apply_parse<T: std::str::FromStr>(m: &HashMap<String, String>, key: &str, target: &mut T) {
+    if let Some(v) = m.get(key).and_then(|v| v.parse().ok()) {
+        *target = v;
+    }
+}
+
+fn load_env_file(path: &Path) -> HashMap<String, String> {
     let mut vals = HashMap::new();
-    let content = match fs::read_to_string(path) {
-        Ok(c) => c,
-        Err(_) => return vals,
+    load_into(path, &mut vals, &mut Vec::new());
+    vals
+}
+
+/// Read `path` into `vals`. An `include = ["base.env", "./local.env"]` line
+/// (or `include = base.env`) reads those files at that point, so later lines
+/// override them; relative paths are relative to the including file.
+/// `stack` holds the files being read, to skip include cycles.
+fn load_into(path: &Path, vals: &mut HashMap<String, String>, stack: &mut Vec<PathBuf>) {
+    let Ok(content) = fs::read_to_string(path) else {
+        return;
     };
-    for line in content.lines() {
-        let line = line.trim();
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if stack.contains(&canonical) {
+        crate::debuglog::write(&format!(
+            "config: include cycle at {}, skipped",
+            path.display()
+        ));
+        return;
+    }
+    stack.push(canonical);
+    for (n, line) in content.lines().enumerate() {
+        let line = line.trim_start_matches('\u{feff}').trim();
         if line.is_empty() || line.starts_with('#') {
             continue;
         }
-        if let Some(idx) = line.find('=') {
-            let k = line[..idx].trim().to_string();
-            let v = line[idx + 1..].trim().to_string();
-            vals.insert(k, v);
+        let Some((k, value)) = parse_line(line) else {
+            crate::debuglog::write(&format!(
+                "config: {}:{}: not KEY=value, skipped",
+                path.display(),
+                n + 1
+            ));
+            continue;
+        };
+        if k == "include" {
+            let (Value::Literal(list) | Value::Expand(list)) = value;
+            let dir = path.parent().unwrap_or(Path::new(""));
+            for item in list
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .split(',')
+            {
+                let item = item.trim().trim_matches('"');
+                if !item.is_empty() {
+                    let file = expand(item, |name| env::var(name).ok());
+                    load_into(&dir.join(file), vals, stack);
+                }
+            }
+            continue;
         }
+        let value = match value {
+            Value::Literal(v) => v,
+            Value::Expand(v) => expand(&v, |name| env::var(name).ok()),
+        };
+        // A key set twice keeps the last value, as when the shell sources it
+        vals.insert(k, value);
+    }
+    stack.pop();
+}
+
+enum Value {
+    /// Single-quoted: taken as is
+    Literal(String),
+    Expand(String),
+}
+
+/// One `KEY=value` line in the shell syntax the Bash engine sources:
+/// an optional `export `, a value in double quotes (with `\"` and `\\`
+/// escapes), single quotes (with `'\''` for a quote inside), or bare up to
+/// a ` #` comment. None when the line has no `=` or a quote is left open.
+fn parse_line(line: &str) -> Option<(String, Value)> {
+    let line = line.strip_prefix("export ").unwrap_or(line);
+    let (k, raw) = line.split_once('=')?;
+    let k = k.trim();
+    if k.is_empty() {
+        return None;
+    }
+    let raw = raw.trim_start();
+    let value = if let Some(mut rest) = raw.strip_prefix('\'') {
+        let mut out = String::new();
+        loop {
+            let end = rest.find('\'')?;
+            out.push_str(&rest[..end]);
+            match rest[end..].strip_prefix("'\\''") {
+                Some(more) => {
+                    out.push('\'');
+                    rest = more;
+                }
+                None => break,
+            }
+        }
+        Value::Literal(out)
+    } else if let Some(rest) = raw.strip_prefix('"') {
+        let mut out = String::new();
+        let mut chars = rest.chars();
+        loop {
+            match chars.next()? {
+                '"' => break,
+                '\\' => match chars.next()? {
+                    c @ ('"' | '\\') => out.push(c),
+                    c => out.extend(['\\', c]),
+                },
+                c => out.push(c),
+            }
+        }
+        Value::Expand(out)
+    } else {
+        let end = raw
+            .char_indices()
+            .find(|&(i, c)| c == '#' && raw[..i].ends_with(char::is_whitespace))
+            .map_or(raw.len(), |(i, _)| i);
+        Value::Expand(raw[..end].trim_end().to_string())
+    };
+    Some((k.to_string(), value))
+}
+
+/// Expand a leading `~` and `${VAR}` references in a config file value, so
+/// one file works across machines. Unset variables expand to nothing.
+fn expand(value: &str, var: impl Fn(&str) -> Option<String>) -> String {
+    let home = || var("HOME").unwrap_or_default();
+    let mut out = match value.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => home() + rest,
+        _ => value.to_string(),
+    };
+    let mut pos = 0;
+    while let Some(found) = out[pos..].find("${") {
+        let start = pos + found;
+        let Some(len) = out[start + 2..].find('}') else {
+            break;
+        };
+        let name = &out[start + 2..start + 2 + len];
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            pos = start + 2;
+            continue;
+        }
+        let value = var(name).unwrap_or_default();
+        out.replace_range(start..start + 3 + len, &value);
+        pos = start + value.len();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_home_and_variables() {
+        let var = |name: &str| match name {
+            "HOME" => Some("/home/me".to_string()),
+            "XDG" => Some("/x".to_string()),
+            _ => None,
+        };
+        assert_eq!(expand("~/.cache", var), "/home/me/.cache");
+        assert_eq!(expand("${XDG}/s-${UNSET}.sock", var), "/x/s-.sock");
+        assert_eq!(expand("a~b", var), "a~b");
+        assert_eq!(expand("since {}", var), "since {}");
+        assert_eq!(expand("${a b}${XDG}", var), "${a b}/x");
+    }
+
+    #[test]
+    fn stdin_options_accept_short_and_full_keys() {
+        let json = r#"{"layout": "minimal", "STATUSLINE_MAX_WIDTH": 80, "show_git": false, "theme": "nord"}"#;
+        let opts = stdin_options(&serde_json::from_str(json).unwrap());
+        assert_eq!(opts["STATUSLINE_LAYOUT"], "minimal");
+        assert_eq!(opts["STATUSLINE_MAX_WIDTH"], "80");
+        assert_eq!(opts["STATUSLINE_SHOW_GIT"], "false");
+        assert_eq!(opts.len(), 3);
+    }
+
+    #[test]
+    fn protected_branches_default_to_main_and_master() {
+        let cfg = from_options(&HashMap::new());
+        assert_eq!(cfg.protected_branches, "main,master");
+        let off = HashMap::from([("STATUSLINE_PROTECTED_BRANCHES".to_string(), String::new())]);
+        assert_eq!(from_options(&off).protected_branches, "");
+    }
+
+    #[test]
+    fn choices_outside_the_set_keep_the_default() {
+        let opts = |v: &str| {
+            [
+                "STATUSLINE_DAY_BOUNDARY",
+                "STATUSLINE_CUMULATIVE_UNIT",
+                "STATUSLINE_CUMULATIVE_FORMAT",
+                "STATUSLINE_CUMULATIVE_SCOPE",
+            ]
+            .iter()
+            .map(|k| (k.to_string(), v.to_string()))
+            .collect()
+        };
+        let cfg = from_options(&opts("Midnight"));
+        assert_eq!(cfg.day_boundary, DAY_BOUNDARIES[0]);
+        assert_eq!(cfg.cumulative_unit, CUMULATIVE_UNITS[0]);
+        assert_eq!(cfg.cumulative_format, CUMULATIVE_FORMATS[0]);
+        assert_eq!(cfg.cumulative_scope, CUMULATIVE_SCOPES[0]);
+        assert_eq!(from_options(&opts("midnight")).day_boundary, "midnight");
+        assert_eq!(from_options(&opts("both")).cumulative_unit, "both");
+    }
+
+    #[test]
+    fn stdin_options_drop_side_effect_keys() {
+        let json = r#"{"post_hook": "rm -rf ~", "write_to": "/etc/motd",
+            "telemetry": true, "telemetry_url": "https://example.com",
+            "STATUSLINE_DIGEST_WEBHOOK": "https://example.com", "lang": "de"}"#;
+        let opts = stdin_options(&serde_json::from_str(json).unwrap());
+        assert_eq!(opts.len(), 1, "{:?}", opts);
+        assert_eq!(opts["STATUSLINE_LANG"], "de");
+        assert!(OPTION_KEYS.iter().all(|k| ENV_KEYS.contains(k)));
+    }
+
+    #[test]
+    fn disabled_segments_need_no_caches() {
+        let mut cfg = load_with(&[], &HashMap::new());
+        cfg.mood = false;
+        cfg.files_touched = false;
+        assert!(cfg.needs_models() && cfg.needs_cumulative());
+        let args: Vec<String> = ["--no-model-bars", "--no-tokens", "--no-cumulative"]
+            .map(String::from)
+            .to_vec();
+        let mut cfg = load_with(&args, &HashMap::new());
+        cfg.mood = false;
+        cfg.files_touched = false;
+        assert!(!cfg.needs_models() && !cfg.needs_cumulative());
+        cfg.mood = true;
+        assert!(cfg.needs_models());
+        let mut cfg = load_with(&["--no-line2".to_string()], &HashMap::new());
+        cfg.mood = false;
+        cfg.files_touched = false;
+        cfg.show_model_bars = false;
+        assert!(!cfg.needs_models() && !cfg.needs_cumulative());
+    }
+
+    #[test]
+    fn env_file_lines() {
+        let parse = |line: &str| {
+            parse_line(line).map(|(k, v)| match v {
+                Value::Literal(v) => (k, format!("'{}'", v)),
+                Value::Expand(v) => (k, v),
+            })
+        };
+        let kv = |k: &str, v: &str| Some((k.to_string(), v.to_string()));
+        assert_eq!(
+            parse("STATUSLINE_LAYOUT=minimal"),
+            kv("STATUSLINE_LAYOUT", "minimal")
+        );
+        assert_eq!(
+            parse("export STATUSLINE_MOOD = true"),
+            kv("STATUSLINE_MOOD", "true")
+        );
+        assert_eq!(parse("A=\"x # y\" # note"), kv("A", "x # y"));
+        assert_eq!(
+            parse(r#"A="say \"hi\" \\ \n""#),
+            kv("A", r#"say "hi" \ \n"#)
+        );
+        assert_eq!(parse("A='${HOME} \\' # note"), kv("A", "'${HOME} \\'"));
+        assert_eq!(parse(r"A='it'\''s' # note"), kv("A", "'it's'"));
+        assert_eq!(parse("A=on # note"), kv("A", "on"));
+        assert_eq!(parse("A=#ff8800"), kv("A", "#ff8800"));
+        assert_eq!(parse("A="), kv("A", ""));
+        assert_eq!(parse("A=\"open"), None);
+        assert_eq!(parse("A='open"), None);
+        assert_eq!(parse("=x"), None);
+        assert_eq!(parse("just words"), None);
+    }
+
+    #[test]
+    fn env_file_crlf_bom_and_last_writer() {
+        let dir = env::temp_dir().join(format!("statusline-envfile-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("statusline.env");
+        fs::write(
+            &file,
+            "\u{feff}# saved on Windows\r\nexport STATUSLINE_LAYOUT=\"minimal\"\r\n\
+             STATUSLINE_MAX_WIDTH=80\r\nnonsense\r\nSTATUSLINE_MAX_WIDTH=120 # wider\r\n",
+        )
+        .unwrap();
+        let vals = load_env_file(&file);
+        assert_eq!(vals["STATUSLINE_LAYOUT"], "minimal");
+        assert_eq!(vals["STATUSLINE_MAX_WIDTH"], "120");
+        assert_eq!(vals.len(), 2);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn include_layers_files_and_skips_cycles() {
+        let dir = env::temp_dir().join(format!("statusline-include-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(
+            dir.join("sub/base.env"),
+            "STATUSLINE_LAYOUT=minimal\nSTATUSLINE_MAX_WIDTH=80\ninclude = ../main.env\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("main.env"),
+            "include = [\"./sub/base.env\"]\nSTATUSLINE_MAX_WIDTH=120\n",
+        )
+        .unwrap();
+        let vals = load_env_file(&dir.join("main.env"));
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(vals["STATUSLINE_LAYOUT"], "minimal");
+        assert_eq!(vals["STATUSLINE_MAX_WIDTH"], "120");
+        assert!(!vals.contains_key("include"));
     }
-    vals
 }
@@ -0,0 +1,104 @@
+//! Per-project deadline (`statusline deadline set "2025-02-01 17:00"`),
+//! rendered as a countdown segment. Stored in
+//! `<cache dir>/deadline-<project hash>.json`.
+
+use crate::cache;
+use crate::clock;
+use crate::config::{self, Config};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize)]
+struct Deadline {
+    at: i64,
+    label: String,
+}
+
+pub fn path(project_dir: &str) -> PathBuf {
+    cache::cache_dir().join(format!(
+        "deadline-{}.json",
+        cache::file_id(&cache::project_hash(project_dir))
+    ))
+}
+
+/// The project's deadline (unix seconds), if one is set.
+pub fn read(project_dir: &str) -> Option<i64> {
+    let data = fs::read_to_string(path(project_dir)).ok()?;
+    serde_json::from_str::<Deadline>(&data).ok().map(|d| d.at)
+}
+
+/// Parse `YYYY-MM-DD HH:MM` (local time); a bare date means 23:59.
+fn parse(text: &str) -> Option<i64> {
+    let (date, time) = text
+        .trim()
+        .split_once(' ')
+        .unwrap_or((text.trim(), "23:59"));
+    let mut d = date.splitn(3, '-').map(|p| p.parse::<i32>().ok());
+    let (year, month, day) = (d.next()??, d.next()??, d.next()??);
+    let (h, m) = time.trim().split_once(':')?;
+    let (hour, minute) = (h.parse::<i32>().ok()?, m.parse::<i32>().ok()?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return None;
+    }
+    clock::local_unix(year, month, day, hour, minute)
+}
+
+/// `statusline deadline set "YYYY-MM-DD HH:MM" | clear | show [--project DIR]`
+pub fn run(cfg: &Config) -> Result<String, String> {
+    let args = &cfg.command_args;
+    let project = match config::arg_value(args, "--project") {
+        Some(p) => p,
+        None => env::current_dir()
+            .map(|d| d.to_string_lossy().to_string())
+            .map_err(|e| e.to_string())?,
+    };
+    let file = path(&project);
+    match args.first().map(String::as_str) {
+        Some("set") => {
+            let text = args
+                .get(1)
+                .ok_or("missing date, e.g. \"2025-02-01 17:00\"")?;
+            let at =
+                parse(text).ok_or_else(|| format!("cannot parse {:?} (YYYY-MM-DD HH:MM)", text))?;
+            let _ = fs::create_dir_all(cache::cache_dir());
+            let data = serde_json::to_string(&Deadline {
+                at,
+                label: text.clone(),
+            })
+            .map_err(|e| e.to_string())?;
+            fs::write(&file, data)
+                .map_err(|e| format!("cannot write {}: {}", file.display(), e))?;
+            Ok(format!("[ok] Deadline for {}: {}", project, text))
+        }
+        Some("clear") => match fs::remove_file(&file) {
+            Ok(()) => Ok(format!("[ok] Cleared deadline for {}", project)),
+            Err(_) => Ok(format!("[--] No deadline for {}", project)),
+        },
+        Some("show") | None => {
+            let data =
+                fs::read_to_string(&file).map_err(|_| format!("no deadline for {}", project))?;
+            let d: Deadline = serde_json::from_str(&data).map_err(|e| e.to_string())?;
+            Ok(d.label)
+        }
+        Some(other) => Err(format!("unknown deadline command: {}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_accepts_date_with_optional_time() {
+        assert_eq!(
+            parse("2025-02-01 17:00"),
+            clock::local_unix(2025, 2, 1, 17, 0)
+        );
+        assert_eq!(parse("2025-02-01"), clock::local_unix(2025, 2, 1, 23, 59));
+        assert_eq!(parse("2025-13-01"), None);
+        assert_eq!(parse("2025-02-30"), None);
+        assert_eq!(parse("tomorrow"), None);
+    }
+}
@@ -0,0 +1,36 @@
+use crate::cache;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The log is truncated once it grows past this size.
+const MAX_LOG_SIZE: u64 = 256 * 1024;
+
+/// Path of the local debug log (never written to stdout).
+pub fn path() -> PathBuf {
+    cache::cache_dir().join("debug.log")
+}
+
+/// Append a timestamped entry to the debug log.
+pub fn write(msg: &str) {
+    let path = path();
+    let _ = fs::create_dir_all(cache::cache_dir());
+    let oversized = fs::metadata(&path)
+        .map(|m| m.len() > MAX_LOG_SIZE)
+        .unwrap_or(false);
+    let mut opts = OpenOptions::new();
+    opts.create(true);
+    if oversized {
+        opts.write(true).truncate(true);
+    } else {
+        opts.append(true);
+    }
+    let ts = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    if let Ok(mut f) = opts.open(&path) {
+        let _ = writeln!(f, "[{}] {}", ts, msg);
+    }
+}
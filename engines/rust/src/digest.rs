@@ -0,0 +1,195 @@
+//! End-of-day digest (STATUSLINE_DIGEST): after a day ends, the next render
+//! writes `<cache dir>/digest/<date>.md` with that day's sessions, costs and
+//! top projects in the background, and posts it to STATUSLINE_DIGEST_WEBHOOK.
+
+use crate::cache;
+use crate::clock;
+use crate::config::Config;
+use crate::debuglog;
+use crate::format;
+use crate::history::{self, SessionUsage};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// Projects listed in the digest.
+const TOP_PROJECTS: usize = 5;
+
+/// A claimed digest that has not been written by then is tried again.
+const RETRY_AFTER: Duration = Duration::from_secs(15 * 60);
+
+fn digest_dir() -> PathBuf {
+    cache::cache_dir().join("digest")
+}
+
+/// Date and `from..to` bounds (unix seconds) of the last day that has ended.
+fn yesterday() -> (String, i64, i64) {
+    let to = clock::day_start(clock::now_unix());
+    let from = clock::day_start(to - 1);
+    (clock::local_date(from), from, to)
+}
+
+fn marker() -> PathBuf {
+    digest_dir().join("last")
+}
+
+/// Claim yesterday's digest, so only one render spawns the writer; cheap
+/// enough for the render path. The day counts as done once `write`
+/// succeeds.
+pub fn claim() -> bool {
+    let (date, _, _) = yesterday();
+    cache::claim_daily(&marker(), &date, RETRY_AFTER)
+}
+
+/// Write yesterday's digest and post it to the webhook (internal mode).
+/// Days without recorded usage get no digest.
+pub fn write(cfg: &Config) {
+    let (date, from, to) = yesterday();
+    let mut usage = history::usage_between(from as u64, to as u64);
+    if usage.is_empty() {
+        cache::finish_daily(&marker(), &date);
+        return;
+    }
+    if cfg.privacy {
+        // Snapshots recorded before privacy was on still hold paths
+        for u in usage.iter_mut().filter(|u| u.project.starts_with('/')) {
+            u.project = cache::private_name(&u.project);
+        }
+    }
+    let text = markdown(&date, usage, &env::var("HOME").unwrap_or_default());
+    let _ = fs::create_dir_all(digest_dir());
+    let path = digest_dir().join(format!("{}.md", date));
+    if let Err(e) = fs::write(&path, &text) {
+        debuglog::write(&format!("digest {}: {}", path.display(), e));
+        return;
+    }
+    if !cfg.digest_webhook.is_empty() && !cfg.offline && !post(&cfg.digest_webhook, &text) {
+        debuglog::write("digest webhook: POST failed");
+        return;
+    }
+    cache::finish_daily(&marker(), &date);
+}
+
+/// POST `{"text": ...}` (Slack/Discord/Teams-style incoming webhooks).
+fn post(url: &str, text: &str) -> bool {
+    let body = serde_json::json!({ "text": text }).to_string();
+    post_json(url, &body)
+}
+
+/// POST a JSON body with curl. The body goes on stdin (`--data-binary @-`),
+/// out of the process list.
+pub fn post_json(url: &str, body: &str) -> bool {
+    let child = Command::new("curl")
+        .args([
+            "-fsS",
+            "--max-time",
+            "10",
+            "-H",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+            url,
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    let Ok(mut child) = child else {
+        return false;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(body.as_bytes());
+    }
+    child.wait().is_ok_and(|s| s.success())
+}
+
+fn short_path(path: &str, home: &str) -> String {
+    match path.strip_prefix(home) {
+        Some(rest) if !home.is_empty() => format!("~{}", rest),
+        _ => path.to_string(),
+    }
+}
+
+fn markdown(date: &str, mut usage: Vec<SessionUsage>, home: &str) -> String {
+    usage.sort_by(|a, b| b.cost.total_cmp(&a.cost));
+    let total: f64 = usage.iter().map(|u| u.cost).sum();
+    let tokens: u64 = usage.iter().map(|u| u.tokens).sum();
+
+    let mut projects: HashMap<&str, (f64, usize)> = HashMap::new();
+    for u in &usage {
+        let p = projects.entry(&u.project).or_default();
+        p.0 += u.cost;
+        p.1 += 1;
+    }
+    let mut projects: Vec<_> = projects.into_iter().collect();
+    projects.sort_by(|a, b| b.1 .0.total_cmp(&a.1 .0).then_with(|| a.0.cmp(b.0)));
+
+    let mut out = format!(
+        "# Claude usage {}\n\n{} across {} session{}, {} tokens\n\n## Top projects\n\n| Project | Cost | Sessions |\n|---|---:|---:|\n",
+        date,
+        format::fmt_cost(total),
+        usage.len(),
+        if usage.len() == 1 { "" } else { "s" },
+        format::fmt_k(tokens as i64)
+    );
+    for (project, (cost, n)) in projects.iter().take(TOP_PROJECTS) {
+        out += &format!(
+            "| {} | {} | {} |\n",
+            short_path(project, home),
+            format::fmt_cost(*cost),
+            n
+        );
+    }
+    out +=
+        "\n## Sessions\n\n| Time | Project | Branch | Cost | Tokens |\n|---|---|---|---:|---:|\n";
+    for u in &usage {
+        let (a, b) = (clock::local(u.first as i64), clock::local(u.last as i64));
+        out += &format!(
+            "| {:02}:{:02}-{:02}:{:02} | {} | {} | {} | {} |\n",
+            a.hour,
+            a.minute,
+            b.hour,
+            b.minute,
+            short_path(&u.project, home),
+            if u.branch.is_empty() { "-" } else { &u.branch },
+            format::fmt_cost(u.cost),
+            format::fmt_k(u.tokens as i64)
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(project: &str, cost: f64) -> SessionUsage {
+        SessionUsage {
+            project: project.into(),
+            branch: "main".into(),
+            cost,
+            tokens: 12_000,
+            first: 0,
+            last: 0,
+        }
+    }
+
+    #[test]
+    fn markdown_ranks_projects_by_cost() {
+        let md = markdown(
+            "2026-10-15",
+            vec![
+                usage("/home/u/a", 1.5),
+                usage("/home/u/b", 8.0),
+                usage("/home/u/a", 2.5),
+            ],
+            "/home/u",
+        );
+        assert!(md.starts_with("# Claude usage 2026-10-15\n\n$12 across 3 sessions, 36k tokens\n"));
+        assert!(md.contains("| ~/b | $8.0 | 1 |\n| ~/a | $4.0 | 2 |\n"));
+    }
+}
@@ -0,0 +1,63 @@
+use crate::cache;
+use crate::config;
+use crate::debuglog;
+use crate::settings;
+use std::fs;
+
+/// Lines of the debug log shown by `statusline doctor`.
+const LOG_TAIL_LINES: usize = 20;
+
+/// `statusline doctor`: installation health and recent errors.
+pub fn run() -> String {
+    let mut out = format!("statusline {} (rust)\n\n", crate::VERSION);
+
+    let cfg_path = config::config_path();
+    if cfg_path.is_file() {
+        out.push_str(&format!("  [ok] config: {}\n", cfg_path.display()));
+    } else {
+        out.push_str(&format!(
+            "  [i ] config: {} not found (defaults active)\n",
+            cfg_path.display()
+        ));
+    }
+
+    let settings_path = settings::settings_path();
+    let configured = fs::read_to_string(&settings_path)
+        .ok()
+        .and_then(|d| serde_json::from_str::<serde_json::Value>(&d).ok())
+        .is_some_and(|v| v.get("statusLine").is_some_and(|s| s.is_object()));
+    if configured {
+        out.push_str(&format!(
+            "  [ok] statusLine set in {}\n",
+            settings_path.display()
+        ));
+    } else {
+        out.push_str(&format!(
+            "  [!!] statusLine missing in {} (run: statusline install-hook)\n",
+            settings_path.display()
+        ));
+    }
+
+    let cache_dir = cache::cache_dir();
+    if cache_dir.is_dir() {
+        out.push_str(&format!("  [ok] cache: {}\n", cache_dir.display()));
+    } else {
+        out.push_str(&format!(
+            "  [i ] cache: {} not created yet\n",
+            cache_dir.display()
+        ));
+    }
+
+    let log_path = debuglog::path();
+    match fs::read_to_string(&log_path) {
+        Ok(log) if !log.trim().is_empty() => {
+            out.push_str(&format!("\nRecent errors ({}):\n", log_path.display()));
+            let lines: Vec<&str> = log.lines().collect();
+            for line in &lines[lines.len().saturating_sub(LOG_TAIL_LINES)..] {
+                out.push_str(&format!("  {}\n", line));
+            }
+        }
+        _ => out.push_str("\n  [ok] no errors logged\n"),
+    }
+    out
+}
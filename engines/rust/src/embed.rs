@@ -0,0 +1,72 @@
+//! Rendering for hosts that embed the engine: the session JSON and options
+//! in, both lines out. `render` involves no environment, config file,
+//! repository or caches (the WASM build); `render_live` reads them like the
+//! binary does (editor plugins over the C ABI in `ffi`).
+
+use crate::cache;
+use crate::config::{self, Config};
+use crate::models;
+use crate::render;
+use crate::session::{self, Session};
+use crate::sources::Sources;
+use std::collections::HashMap;
+
+/// Render `session_json` with `options_json`, an object of config keys as in
+/// the session's `statusline_options` (`{"layout": "dense", "lang": "de"}`).
+/// Invalid options render with the defaults, an invalid session as empty.
+pub fn render(session_json: &str, options_json: &str) -> String {
+    let (sess, options) = parse(session_json, options_json);
+    let cfg = config::from_options(&options);
+    render::render_with(&sess, &cfg, &Sources::NONE).0
+}
+
+/// Like `render`, with the options on top of the config file and
+/// environment, and git state and caches read from disk. Caches are
+/// refreshed by the binary's runs, not here, so nothing is spawned.
+pub fn render_live(session_json: &str, options_json: &str) -> String {
+    let (mut sess, options) = parse(session_json, options_json);
+    let cfg = config::load_with(&[], &options);
+    // Aliases are fixed by the first render that has any, as at startup
+    if !cfg.model_aliases.is_empty() {
+        models::init(&cfg.model_aliases);
+    }
+    cache::set_private(cfg.privacy);
+    locate_transcript(&mut sess, &cfg);
+    render::render_with(&sess, &cfg, &Sources::LIVE).0
+}
+
+/// The session, and the session's own options with `options_json` on top.
+fn parse(session_json: &str, options_json: &str) -> (Session, HashMap<String, String>) {
+    let options: HashMap<String, serde_json::Value> =
+        serde_json::from_str(options_json).unwrap_or_default();
+    let sess = session::parse(session_json.as_bytes());
+    let mut layers = config::stdin_options(&sess.statusline_options);
+    layers.extend(config::stdin_options(&options));
+    (sess, layers)
+}
+
+fn locate_transcript(sess: &mut Session, cfg: &Config) {
+    if cfg.needs_models() || cfg.awaiting_secs > 0 {
+        sess.transcript_path = cache::locate_transcript(
+            &sess.transcript_path,
+            &cache::transcript_roots(&cfg.transcript_roots),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_from_json_alone() {
+        let session = r#"{"model": {"display_name": "Claude Opus 4.6"},
+            "context_window": {"used_percentage": 42},
+            "cost": {"total_cost_usd": 8.42}}"#;
+        let out = render(session, r#"{"layout": "minimal"}"#);
+        assert_eq!(crate::render::strip_ansi(&out), "O 42% $8.4\n\n");
+
+        let out = render(session, "not json");
+        assert!(out.starts_with("\x1b[36mOpus 4.6"), "{:?}", out);
+    }
+}
@@ -0,0 +1,142 @@
+//! `--explain`: render once, then list every segment with the reason it is
+//! shown or hidden: the setting (and config layer) that turned it off, or
+//! the data it is still missing.
+
+use crate::cache;
+use crate::config::{self, Config};
+use crate::segments::{Segment, SEGMENTS};
+use crate::session::Session;
+use std::collections::HashMap;
+
+/// One line per segment: name, `shown` / `off` / `hidden`, and why.
+pub fn report(sess: &Session, cfg: &Config, args: &[String]) -> String {
+    let mut out = String::new();
+    if cfg.layout == "minimal" {
+        out.push_str("layout minimal: only model, context, cost and git render\n");
+    }
+    let options = config::stdin_options(&sess.statusline_options);
+    for seg in SEGMENTS {
+        let (state, why) = explain(seg, sess, cfg, args, &options);
+        out.push_str(&format!("{:<11} {:<7} {}\n", seg.name, state, why));
+    }
+    out
+}
+
+fn explain(
+    seg: &Segment,
+    sess: &Session,
+    cfg: &Config,
+    args: &[String],
+    options: &HashMap<String, String>,
+) -> (&'static str, String) {
+    if !(seg.get)(cfg) {
+        return ("off", setting(seg.env_key, seg.flag, args, options));
+    }
+    if seg.line == 2 && !cfg.line2 {
+        return (
+            "off",
+            format!(
+                "line 2 is off: {}",
+                setting("STATUSLINE_LINE2", "--no-line2", args, options)
+            ),
+        );
+    }
+    match missing(seg.name, sess, cfg) {
+        Some(why) => ("hidden", why),
+        None => ("shown", setting(seg.env_key, seg.flag, args, options)),
+    }
+}
+
+/// The flag or key (with its layer) that decides a segment's switch.
+fn setting(key: &str, flag: &str, args: &[String], options: &HashMap<String, String>) -> String {
+    if args.iter().any(|a| a == flag) {
+        return format!("{} (command line)", flag);
+    }
+    match config::source(key, options) {
+        Some((layer, value)) => format!("{}={} ({})", key, value, layer),
+        None => format!("{} (default)", key),
+    }
+}
+
+/// Why an enabled segment has nothing to show, if it doesn't.
+fn missing(name: &str, sess: &Session, cfg: &Config) -> Option<String> {
+    match name {
+        "model_bars" => match cache::read_models(&sess.id()) {
+            None if sess.id().is_empty() => Some("no session id or transcript path".into()),
+            None => Some(
+                "model cache miss: per-model stats are computed from the transcript in the background"
+                    .into(),
+            ),
+            Some(ms) if ms.opus_out.max(ms.sonnet_out).max(ms.haiku_out) == 0 => {
+                Some("no per-model output in the transcript yet".into())
+            }
+            Some(_) => None,
+        },
+        "git" if !cfg!(feature = "git") => Some("built without the `git` feature".into()),
+        "git" => {
+            let cwd = std::env::current_dir().ok()?.to_string_lossy().to_string();
+            match crate::git::get(&cwd, &sess.workspace.project_dir, cfg) {
+                Some(gs) if !gs.branch.is_empty() => None,
+                _ => Some(format!(
+                    "no branch in {}: not a git repository, or HEAD is detached",
+                    cwd
+                )),
+            }
+        }
+        "diff" if sess.cost.total_lines_added == 0.0 && sess.cost.total_lines_removed == 0.0 => {
+            Some("no lines added or removed yet".into())
+        }
+        "speed" if sess.cost.total_api_duration_ms == 0.0 => {
+            Some("no API time in the session JSON yet".into())
+        }
+        "speed" if sess.context_window.total_output_tokens == 0.0 => {
+            Some("no output tokens yet".into())
+        }
+        "cumulative" => match cache::read_cumulative(&sess.workspace.project_dir) {
+            (None, None) if cfg.no_spawn => Some(
+                "cumulative cache miss, and --no-spawn keeps the background job from filling it"
+                    .into(),
+            ),
+            (None, None) => Some(
+                "cumulative cache miss: costs are summed from all transcripts in the background"
+                    .into(),
+            ),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_names_absent_session_data() {
+        let cfg = config::load(&[]);
+        let mut sess = Session::default();
+        assert_eq!(
+            missing("speed", &sess, &cfg).as_deref(),
+            Some("no API time in the session JSON yet")
+        );
+        sess.cost.total_api_duration_ms = 1000.0;
+        sess.context_window.total_output_tokens = 10.0;
+        assert_eq!(missing("speed", &sess, &cfg), None);
+        assert!(missing("diff", &sess, &cfg).is_some());
+        assert_eq!(missing("cost", &sess, &cfg), None);
+    }
+
+    #[test]
+    fn names_the_session_options_layer() {
+        let options = HashMap::from([("STATUSLINE_SHOW_COST".to_string(), "false".to_string())]);
+        assert_eq!(
+            setting("STATUSLINE_SHOW_COST", "--no-cost", &[], &options),
+            "STATUSLINE_SHOW_COST=false (session options)"
+        );
+        let args = vec!["--no-cost".to_string()];
+        assert_eq!(
+            setting("STATUSLINE_SHOW_COST", "--no-cost", &args, &options),
+            "--no-cost (command line)"
+        );
+    }
+}
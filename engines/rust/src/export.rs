@@ -0,0 +1,213 @@
+//! ANSI → HTML/SVG snapshot export (`statusline render --format html|svg`).
+//! Colors and metrics follow scripts/ansi2svg.py (Catppuccin Mocha / Latte).
+
+struct Theme {
+    bg: &'static str,
+    border: &'static str,
+    default: &'static str,
+    dim: &'static str,
+    cyan: &'static str,
+    magenta: &'static str,
+    green: &'static str,
+    yellow: &'static str,
+    red: &'static str,
+}
+
+const DARK: Theme = Theme {
+    bg: "#1e1e2e",
+    border: "#313244",
+    default: "#cdd6f4",
+    dim: "#6c7086",
+    cyan: "#89dceb",
+    magenta: "#cba6f7",
+    green: "#a6e3a1",
+    yellow: "#f9e2af",
+    red: "#f38ba8",
+};
+
+const LIGHT: Theme = Theme {
+    bg: "#eff1f5",
+    border: "#ccd0da",
+    default: "#4c4f69",
+    dim: "#9ca0b0",
+    cyan: "#179299",
+    magenta: "#8839ef",
+    green: "#40a02b",
+    yellow: "#df8e1d",
+    red: "#d20f39",
+};
+
+const FONT: &str = "'JetBrains Mono', 'Fira Code', 'SF Mono', Menlo, Consolas, monospace";
+const FONT_SIZE: u32 = 13;
+const LINE_HEIGHT: u32 = 20;
+const PADDING_X: u32 = 20;
+const CHAR_WIDTH: f64 = 7.8;
+
+/// Split one line of ANSI text into (css class, text) runs.
+pub fn parse_ansi(line: &str) -> Vec<(&'static str, String)> {
+    let mut runs = Vec::new();
+    let mut class = "default";
+    let mut text = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.peek() == Some(&'[') {
+            chars.next();
+            let mut code = String::new();
+            for c in chars.by_ref() {
+                if c == 'm' {
+                    break;
+                }
+                code.push(c);
+            }
+            if !text.is_empty() {
+                runs.push((class, std::mem::take(&mut text)));
+            }
+            for part in code.split(';') {
+                class = match part {
+                    "2" => "dim",
+                    "31" => "red",
+                    "32" => "green",
+                    "33" => "yellow",
+                    "35" => "magenta",
+                    "36" => "cyan",
+                    _ => "default",
+                };
+            }
+        } else {
+            text.push(c);
+        }
+    }
+    if !text.is_empty() {
+        runs.push((class, text));
+    }
+    runs
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn lines(output: &str) -> Vec<&str> {
+    output.lines().filter(|l| !l.is_empty()).collect()
+}
+
+fn css(t: &Theme, prop: &str) -> String {
+    [
+        ("default", t.default),
+        ("dim", t.dim),
+        ("cyan", t.cyan),
+        ("magenta", t.magenta),
+        ("green", t.green),
+        ("yellow", t.yellow),
+        ("red", t.red),
+    ]
+    .iter()
+    .map(|(c, v)| format!("    .{} {{ {}: {}; }}\n", c, prop, v))
+    .collect()
+}
+
+/// Standalone HTML document with the status line as styled spans.
+pub fn to_html(output: &str, light: bool) -> String {
+    let t = if light { &LIGHT } else { &DARK };
+    let mut body = String::new();
+    for line in lines(output) {
+        for (class, text) in parse_ansi(line) {
+            body.push_str(&format!(
+                "<span class=\"{}\">{}</span>",
+                class,
+                escape(&text)
+            ));
+        }
+        body.push('\n');
+    }
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<style>\n    \
+         pre.statusline {{ background: {}; border: 1px solid {}; border-radius: 10px; \
+         padding: 12px {}px; font-family: {}; font-size: {}px; line-height: {}px; }}\n{}\
+         </style>\n</head>\n<body>\n<pre class=\"statusline\">{}</pre>\n</body>\n</html>\n",
+        t.bg,
+        t.border,
+        PADDING_X,
+        FONT,
+        FONT_SIZE,
+        LINE_HEIGHT,
+        css(t, "color"),
+        body
+    )
+}
+
+/// SVG image of the status line on a terminal-style background.
+pub fn to_svg(output: &str, light: bool) -> String {
+    let t = if light { &LIGHT } else { &DARK };
+    let rows = lines(output);
+    let max_chars = rows
+        .iter()
+        .map(|l| {
+            parse_ansi(l)
+                .iter()
+                .map(|(_, s)| s.chars().count())
+                .sum::<usize>()
+        })
+        .max()
+        .unwrap_or(0);
+    let width = (max_chars as f64 * CHAR_WIDTH).round() as u32 + 2 * PADDING_X;
+    let height = rows.len() as u32 * LINE_HEIGHT + 24;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n\
+         <style>\n    text, tspan {{ font-family: {f}; font-size: {fs}px; white-space: pre; }}\n{c}</style>\n\
+         <rect fill=\"{bg}\" width=\"{w}\" height=\"{h}\" rx=\"10\"/>\n\
+         <rect fill=\"none\" stroke=\"{b}\" stroke-width=\"1\" x=\"0.5\" y=\"0.5\" width=\"{w1}\" height=\"{h1}\" rx=\"10\"/>\n",
+        w = width,
+        h = height,
+        w1 = width - 1,
+        h1 = height - 1,
+        f = FONT,
+        fs = FONT_SIZE,
+        c = css(t, "fill"),
+        bg = t.bg,
+        b = t.border,
+    );
+    let mut y = 12 + LINE_HEIGHT - 5;
+    for line in rows {
+        let tspans: String = parse_ansi(line)
+            .iter()
+            .map(|(class, text)| format!("<tspan class=\"{}\">{}</tspan>", class, escape(text)))
+            .collect();
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\">{}</text>\n",
+            PADDING_X, y, tspans
+        ));
+        y += LINE_HEIGHT;
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ansi_runs() {
+        let runs = parse_ansi("\x1b[36mOpus\x1b[0m \x1b[2m|\x1b[0m $1");
+        let want = [
+            ("cyan", "Opus"),
+            ("default", " "),
+            ("dim", "|"),
+            ("default", " $1"),
+        ];
+        assert_eq!(runs.len(), want.len());
+        for ((c, t), (wc, wt)) in runs.iter().zip(want) {
+            assert_eq!((*c, t.as_str()), (wc, wt));
+        }
+    }
+
+    #[test]
+    fn html_escapes_text() {
+        assert!(to_html("a<b>&\n", false).contains("a&lt;b&gt;&amp;"));
+    }
+}
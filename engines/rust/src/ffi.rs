@@ -0,0 +1,77 @@
+//! C ABI (feature `ffi`) for editor plugins that render in-process instead
+//! of spawning the binary on every refresh. See `include/statusline.h`.
+//! The WASM build exports the same two functions, rendering without files.
+
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+
+/// Both status lines for the session JSON `json`, with `config_json` an
+/// object of config keys as in `statusline_options`. Either may be null.
+/// The result is owned by the caller and goes back to `statusline_free`;
+/// it is empty, never null, if rendering failed.
+///
+/// # Safety
+///
+/// `json` and `config_json` must be null or NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn statusline_render(
+    json: *const c_char,
+    config_json: *const c_char,
+) -> *mut c_char {
+    let (json, config_json) = (text(json), text(config_json));
+    // A panic must not unwind into the caller's runtime
+    let out = panic::catch_unwind(AssertUnwindSafe(|| {
+        if cfg!(target_arch = "wasm32") {
+            crate::embed::render(&json, &config_json)
+        } else {
+            crate::embed::render_live(&json, &config_json)
+        }
+    }))
+    .unwrap_or_default();
+    // Rendered text has no NUL bytes; an empty string stands in if it ever does
+    CString::new(out).unwrap_or_default().into_raw()
+}
+
+/// Release a string from `statusline_render`.
+///
+/// # Safety
+///
+/// `s` must come from `statusline_render` and be released once.
+#[no_mangle]
+pub unsafe extern "C" fn statusline_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+unsafe fn text(s: *const c_char) -> String {
+    if s.is_null() {
+        return String::new();
+    }
+    CStr::from_ptr(s).to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_and_free() {
+        let json = CString::new(r#"{"model": {"display_name": "Claude Sonnet 4.5"}}"#).unwrap();
+        let options = CString::new(r#"{"layout": "minimal", "show_git": false}"#).unwrap();
+        unsafe {
+            let out = statusline_render(json.as_ptr(), options.as_ptr());
+            let text = CStr::from_ptr(out).to_string_lossy().into_owned();
+            assert!(
+                crate::render::strip_ansi(&text).starts_with("S 0%"),
+                "{:?}",
+                text
+            );
+            statusline_free(out);
+
+            let out = statusline_render(std::ptr::null(), std::ptr::null());
+            assert!(!CStr::from_ptr(out).to_bytes().is_empty());
+            statusline_free(out);
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! `statusline files`: the files the agent edited or wrote in the most
+//! recently rendered session, relative to its project directory. The list
+//! comes from the model cache, which collects Edit/Write tool calls.
+
+use crate::cache;
+use crate::session;
+use std::fs;
+
+pub fn run() -> Result<String, String> {
+    let raw = fs::read(cache::last_session_path()).map_err(|_| "no session rendered yet")?;
+    let sess = session::parse(&raw[..]);
+    let stats = cache::read_models(&sess.id())
+        .ok_or("no model cache for the latest session yet: it is filled in the background")?;
+    if stats.files.is_empty() {
+        return Ok("no files edited in this session".into());
+    }
+    let project_dir = &sess.workspace.project_dir;
+    Ok(stats
+        .files
+        .iter()
+        .map(|f| relative(f, project_dir))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// `path` below `project_dir` without the directory; others unchanged.
+fn relative<'a>(path: &'a str, project_dir: &str) -> &'a str {
+    match path.strip_prefix(project_dir.trim_end_matches('/')) {
+        Some(rest) if !project_dir.is_empty() && rest.starts_with('/') => &rest[1..],
+        _ => path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_to_project_dir() {
+        assert_eq!(
+            relative("/work/app/src/main.rs", "/work/app"),
+            "src/main.rs"
+        );
+        assert_eq!(
+            relative("/work/app/src/main.rs", "/work/app/"),
+            "src/main.rs"
+        );
+        assert_eq!(
+            relative("/work/apple/x.rs", "/work/app"),
+            "/work/apple/x.rs"
+        );
+        assert_eq!(relative("/tmp/notes.md", ""), "/tmp/notes.md");
+    }
+}
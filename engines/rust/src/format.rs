@@ -11,8 +11,33 @@ pub fn fmt_k(n: i64) -> String {
     }
 }
 
+/// Format an ahead/behind count; a walk that stopped at its limit shows the
+/// limit with a `+` (1000 -> "1k+", 250 -> "250+").
+pub fn fmt_commits(n: i32, capped: bool) -> String {
+    if capped {
+        format!("{}+", fmt_k(n as i64).replace(".0k", "k"))
+    } else {
+        n.to_string()
+    }
+}
+
+/// Coarse age in the largest whole unit: 12m, 5h, 3d.
+pub fn fmt_age(secs: i64) -> String {
+    let secs = secs.max(0);
+    if secs >= 86_400 {
+        format!("{}d", secs / 86_400)
+    } else if secs >= 3600 {
+        format!("{}h", secs / 3600)
+    } else {
+        format!("{}m", secs / 60)
+    }
+}
+
 /// Format cost: >=1000->"$1.8k", >=100->"$374", >=10->"$14", >=1->"$8.4", <1->"$0.12".
+/// NaN, infinite and negative costs show as "$0.00".
 pub fn fmt_cost(c: f64) -> String {
+    // `> 0.0` also drops -0.0, which would print as "$-0.00"
+    let c = if c.is_finite() && c > 0.0 { c } else { 0.0 };
     if c >= 1000.0 {
         format!("${:.1}k", c / 1000.0)
     } else if c >= 10.0 {
@@ -26,7 +51,7 @@ pub fn fmt_cost(c: f64) -> String {
 
 /// Format milliseconds: >=60min->"4h0m", <60min->"15m".
 pub fn fmt_duration(ms: i64) -> String {
-    let min = ms / 60_000;
+    let min = ms.max(0) / 60_000;
     if min >= 60 {
         format!("{}h{}m", min / 60, min % 60)
     } else {
@@ -34,6 +59,90 @@ pub fn fmt_duration(ms: i64) -> String {
     }
 }
 
+/// Group digits with commas: 1234567->"1,234,567".
+pub fn fmt_grouped(n: i64) -> String {
+    let digits = n.unsigned_abs().to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3 + 1);
+    if n < 0 {
+        out.push('-');
+    }
+    for (i, d) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(d);
+    }
+    out
+}
+
+/// Precision knobs for rendered values (STATUSLINE_COST_CENTS,
+/// STATUSLINE_PCT_DECIMALS, STATUSLINE_TOKEN_WIDTH, STATUSLINE_TOKENS_EXACT,
+/// STATUSLINE_DURATION_SECONDS).
+/// The defaults match the plain formatters above.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Precision {
+    /// Always two decimals for costs below $1000, e.g. `$14.20`.
+    pub cents: bool,
+    /// Decimals for the context percentage (truncated, not rounded).
+    pub pct_decimals: usize,
+    /// Token counts with at most this many digits are shown exactly.
+    pub token_width: usize,
+    /// Full comma-grouped token counts (`1,234,567`), ignoring `token_width`.
+    pub tokens_exact: bool,
+    /// Durations shorter than this many minutes include seconds (0 = off).
+    pub seconds_below: u64,
+}
+
+impl Default for Precision {
+    fn default() -> Self {
+        Precision {
+            cents: false,
+            pct_decimals: 0,
+            token_width: 3,
+            tokens_exact: false,
+            seconds_below: 0,
+        }
+    }
+}
+
+impl Precision {
+    pub fn cost(&self, c: f64) -> String {
+        if self.cents && c > 0.0 && c < 1000.0 {
+            format!("${:.2}", c)
+        } else {
+            fmt_cost(c)
+        }
+    }
+
+    pub fn tokens(&self, n: i64) -> String {
+        if self.tokens_exact {
+            fmt_grouped(n)
+        } else if n.unsigned_abs().to_string().len() <= self.token_width {
+            n.to_string()
+        } else {
+            fmt_k(n)
+        }
+    }
+
+    /// Like `fmt_duration`, but `42s` / `1m20s` below `seconds_below` minutes.
+    pub fn duration(&self, ms: i64) -> String {
+        if ms >= self.seconds_below as i64 * 60_000 {
+            return fmt_duration(ms);
+        }
+        let secs = ms.max(0) / 1000;
+        if secs < 60 {
+            format!("{}s", secs)
+        } else {
+            format!("{}m{}s", secs / 60, secs % 60)
+        }
+    }
+
+    pub fn pct(&self, pct: f64) -> String {
+        let scale = 10f64.powi(self.pct_decimals as i32);
+        format!("{:.*}", self.pct_decimals, (pct * scale).trunc() / scale)
+    }
+}
+
 const BARS: [&str; 8] = [
     "\u{2581}", "\u{2582}", "\u{2583}", "\u{2584}", "\u{2585}", "\u{2586}", "\u{2587}", "\u{2588}",
 ];
@@ -43,10 +152,20 @@ pub fn bar_char(val: i64, max: i64) -> &'static str {
     if val <= 0 || max <= 0 {
         return "";
     }
+    // i128: val * 8 overflows i64 for counts near the top of the range
+    let (val, max) = (val as i128, max as i128);
     let level = ((val * 8 + max / 2) / max).clamp(1, 8);
     BARS[(level - 1) as usize]
 }
 
+/// Sparkline of percentages (0-100), one bar character per sample.
+pub fn sparkline(values: &[u8]) -> String {
+    values
+        .iter()
+        .map(|&v| bar_char(v.max(1) as i64, 100))
+        .collect()
+}
+
 const BRANCH_PREFIXES: &[(&str, &str)] = &[
     ("feature/", "\u{2605}"),
     ("feat/", "\u{2605}"),
@@ -56,14 +175,116 @@ const BRANCH_PREFIXES: &[(&str, &str)] = &[
     ("docs/", "\u{00a7}"),
 ];
 
-/// Replace known branch prefixes with icons.
-pub fn shorten_branch(name: &str) -> String {
-    for &(prefix, icon) in BRANCH_PREFIXES {
-        if let Some(rest) = name.strip_prefix(prefix) {
-            return format!("{}{}", icon, rest);
+/// Replace the longest matching branch prefix with its icon. `icons` is
+/// STATUSLINE_BRANCH_ICONS: `off`, or comma-separated `prefix=icon` pairs
+/// that add to the built-in ones and win over them; an empty icon leaves
+/// that prefix alone. A `*` component in a prefix matches any one, as in
+/// `users/*/`.
+pub fn shorten_branch(name: &str, icons: &str) -> String {
+    match branch_icon(name, icons) {
+        Some((len, icon)) => format!("{}{}", icon, &name[len..]),
+        None => name.to_string(),
+    }
+}
+
+/// Length of the prefix shorten_branch replaces, and its icon.
+fn branch_icon<'a>(name: &str, icons: &'a str) -> Option<(usize, &'a str)> {
+    if icons == "off" {
+        return None;
+    }
+    let custom = icons
+        .split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(prefix, icon)| (prefix.trim(), icon.trim()));
+    let mut best: Option<(usize, &str)> = None;
+    for (prefix, icon) in BRANCH_PREFIXES.iter().copied().chain(custom) {
+        if let Some(len) = prefix_len(name, prefix) {
+            if best.is_none_or(|(longest, _)| len >= longest) {
+                best = Some((len, icon));
+            }
         }
     }
-    name.to_string()
+    best.filter(|(_, icon)| !icon.is_empty())
+}
+
+/// The shortened branch name in at most `max_len` runes (STATUSLINE_BRANCH_TRUNCATE):
+/// `tail` cuts the end, `head` the start, `middle` keeps both ends, and
+/// `smart` keeps the prefix icon and the last path component.
+pub fn branch_label(name: &str, icons: &str, max_len: usize, strategy: &str) -> String {
+    let full = shorten_branch(name, icons);
+    if full.chars().count() <= max_len {
+        return full;
+    }
+    match strategy {
+        "head" => truncate_start(&full, max_len),
+        "middle" => truncate_middle(&full, max_len),
+        "smart" => {
+            let (icon, rest) = match branch_icon(name, icons) {
+                Some((len, icon)) => (icon, &name[len..]),
+                None => ("", name),
+            };
+            let last = rest.rsplit('/').next().unwrap_or(rest);
+            let room = max_len.saturating_sub(icon.chars().count());
+            format!("{}{}", icon, truncate_middle(last, room))
+        }
+        _ => truncate(&full, max_len),
+    }
+}
+
+/// Keep the last max_len runes, ellipsis first.
+fn truncate_start(s: &str, max_len: usize) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    if chars.len() <= max_len {
+        return s.to_string();
+    }
+    if max_len == 0 {
+        return String::new();
+    }
+    let tail: String = chars[chars.len() - (max_len - 1)..].iter().collect();
+    format!("\u{2026}{}", tail)
+}
+
+/// Keep both ends of s in max_len runes, ellipsis between.
+fn truncate_middle(s: &str, max_len: usize) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    if chars.len() <= max_len {
+        return s.to_string();
+    }
+    if max_len == 0 {
+        return String::new();
+    }
+    let keep = max_len - 1;
+    let head: String = chars[..keep.div_ceil(2)].iter().collect();
+    let tail: String = chars[chars.len() - keep / 2..].iter().collect();
+    format!("{}\u{2026}{}", head, tail)
+}
+
+/// Bytes of `name` covered by `prefix`, if it is one.
+fn prefix_len(name: &str, prefix: &str) -> Option<usize> {
+    let mut len = 0;
+    for part in prefix.split_inclusive('/') {
+        let rest = &name[len..];
+        len += match part {
+            "*/" => rest.find('/').filter(|&end| end > 0)? + 1,
+            "*" => rest.len(),
+            _ => rest.starts_with(part).then_some(part.len())?,
+        };
+    }
+    (len > 0).then_some(len)
+}
+
+/// The first ticket id in a branch name: the match of the first of the
+/// space-separated `patterns` that matches, and the id for a tracker URL
+/// (capture group 1, else the whole match). Invalid patterns are skipped.
+pub fn ticket(branch: &str, patterns: &str) -> Option<(String, String)> {
+    patterns.split_whitespace().find_map(|p| {
+        let caps = regex_lite::Regex::new(p).ok()?.captures(branch)?;
+        let text = caps.get(0)?.as_str().to_string();
+        let id = caps
+            .get(1)
+            .map_or_else(|| text.clone(), |m| m.as_str().to_string());
+        Some((text, id))
+    })
 }
 
 /// Truncate to max_len runes with ellipsis.
@@ -86,10 +307,10 @@ pub fn round_to_even(x: f64) -> i64 {
     let rounded = x.round();
     // Check if we're exactly at .5
     if (x - x.floor() - 0.5).abs() < f64::EPSILON {
-        // Round to even
+        // Round to even (toward zero, since round() goes away from zero)
         let r = rounded as i64;
         if r % 2 != 0 {
-            return r - 1;
+            return r - r.signum();
         }
     }
     rounded as i64
@@ -99,6 +320,60 @@ pub fn round_to_even(x: f64) -> i64 {
 mod tests {
     use super::*;
 
+    // --- Precision ---
+    #[test]
+    fn precision_defaults_match_plain_formatters() {
+        let p = Precision::default();
+        assert_eq!(p.cost(8.42), fmt_cost(8.42));
+        assert_eq!(p.tokens(523), "523");
+        assert_eq!(p.tokens(45231), fmt_k(45231));
+        assert_eq!(p.pct(38.9), "38");
+        assert_eq!(p.duration(42_000), "0m");
+    }
+    #[test]
+    fn duration_seconds_below_threshold() {
+        let p = Precision {
+            seconds_below: 5,
+            ..Precision::default()
+        };
+        assert_eq!(p.duration(0), "0s");
+        assert_eq!(p.duration(59_999), "59s");
+        assert_eq!(p.duration(60_000), "1m0s");
+        assert_eq!(p.duration(80_000), "1m20s");
+        assert_eq!(p.duration(299_999), "4m59s");
+        assert_eq!(p.duration(300_000), "5m");
+    }
+    #[test]
+    fn precision_knobs() {
+        let p = Precision {
+            cents: true,
+            pct_decimals: 1,
+            token_width: 6,
+            tokens_exact: false,
+            seconds_below: 0,
+        };
+        assert_eq!(p.cost(14.2), "$14.20");
+        assert_eq!(p.cost(1834.0), "$1.8k");
+        assert_eq!(p.pct(89.96), "89.9");
+        assert_eq!(p.tokens(288_431), "288431");
+        assert_eq!(p.tokens(1_234_567), "1.2M");
+        let exact = Precision {
+            tokens_exact: true,
+            ..p
+        };
+        assert_eq!(exact.tokens(1_234_567), "1,234,567");
+    }
+
+    // --- fmt_grouped ---
+    #[test]
+    fn fmt_grouped_inserts_commas() {
+        assert_eq!(fmt_grouped(0), "0");
+        assert_eq!(fmt_grouped(999), "999");
+        assert_eq!(fmt_grouped(1000), "1,000");
+        assert_eq!(fmt_grouped(123_456), "123,456");
+        assert_eq!(fmt_grouped(-1_234_567), "-1,234,567");
+    }
+
     // --- fmt_k ---
     #[test]
     fn fmt_k_zero() {
@@ -128,10 +403,25 @@ mod tests {
         assert_eq!(fmt_k(15_000_000), "15.0M");
     }
     #[test]
+    fn fmt_commits_marks_capped_walks() {
+        assert_eq!(fmt_commits(42, false), "42");
+        assert_eq!(fmt_commits(1000, true), "1k+");
+        assert_eq!(fmt_commits(2500, true), "2.5k+");
+        assert_eq!(fmt_commits(250, true), "250+");
+    }
+    #[test]
     fn fmt_k_negative() {
         assert_eq!(fmt_k(-100), "-100");
     }
 
+    #[test]
+    fn fmt_age_uses_largest_unit() {
+        assert_eq!(fmt_age(45), "0m");
+        assert_eq!(fmt_age(12 * 60 + 5), "12m");
+        assert_eq!(fmt_age(5 * 3600 + 59 * 60), "5h");
+        assert_eq!(fmt_age(3 * 86_400 + 7200), "3d");
+    }
+
     // --- fmt_cost ---
     #[test]
     fn fmt_cost_zero() {
@@ -209,21 +499,66 @@ mod tests {
     // --- shorten_branch ---
     #[test]
     fn shorten_branch_feature() {
-        assert_eq!(shorten_branch("feature/login"), "\u{2605}login");
-        assert_eq!(shorten_branch("feat/auth"), "\u{2605}auth");
+        assert_eq!(shorten_branch("feature/login", ""), "\u{2605}login");
+        assert_eq!(shorten_branch("feat/auth", ""), "\u{2605}auth");
     }
     #[test]
     fn shorten_branch_fix() {
-        assert_eq!(shorten_branch("fix/crash"), "\u{2726}crash");
+        assert_eq!(shorten_branch("fix/crash", ""), "\u{2726}crash");
     }
     #[test]
     fn shorten_branch_no_prefix() {
-        assert_eq!(shorten_branch("main"), "main");
-        assert_eq!(shorten_branch("develop"), "develop");
+        assert_eq!(shorten_branch("main", ""), "main");
+        assert_eq!(shorten_branch("develop", ""), "develop");
+    }
+    #[test]
+    fn shorten_branch_custom_icons() {
+        let icons = "users/*/=\u{263a},hotfix/=\u{1f525},feat/=";
+        assert_eq!(shorten_branch("users/jane/login", icons), "\u{263a}login");
+        assert_eq!(shorten_branch("hotfix/db", icons), "\u{1f525}db");
+        assert_eq!(shorten_branch("feat/auth", icons), "feat/auth");
+        assert_eq!(shorten_branch("feature/auth", icons), "\u{2605}auth");
+        assert_eq!(shorten_branch("users//x", icons), "users//x");
+        assert_eq!(
+            shorten_branch("feature/ui/nav", "feature/ui/=\u{25a3}"),
+            "\u{25a3}nav"
+        );
+        assert_eq!(shorten_branch("feature/login", "off"), "feature/login");
+    }
+    #[test]
+    fn branch_label_strategies() {
+        let name = "feature/auth/session-refresh";
+        let label = |strategy| branch_label(name, "", 12, strategy);
+        assert_eq!(label("tail"), "\u{2605}auth/sessi\u{2026}");
+        assert_eq!(label("head"), "\u{2026}ion-refresh");
+        assert_eq!(label("middle"), "\u{2605}auth/\u{2026}fresh");
+        assert_eq!(label("smart"), "\u{2605}sessi\u{2026}fresh");
+        assert_eq!(
+            branch_label(name, "", 20, "smart"),
+            "\u{2605}session-refresh"
+        );
+        assert_eq!(branch_label("main", "", 12, "head"), "main");
     }
     #[test]
     fn shorten_branch_empty() {
-        assert_eq!(shorten_branch(""), "");
+        assert_eq!(shorten_branch("", ""), "");
+    }
+
+    // --- ticket ---
+    #[test]
+    fn ticket_from_branch() {
+        let patterns = "[A-Z][A-Z0-9]+-[0-9]+ #([0-9]+)";
+        let t = |b: &str| ticket(b, patterns);
+        assert_eq!(
+            t("feature/ABC-123-login"),
+            Some(("ABC-123".into(), "ABC-123".into()))
+        );
+        assert_eq!(t("fix/#456-crash"), Some(("#456".into(), "456".into())));
+        assert_eq!(t("main"), None);
+        assert_eq!(
+            ticket("PROJ-1", "( [A-Z]+-[0-9]+"),
+            Some(("PROJ-1".into(), "PROJ-1".into()))
+        );
     }
 
     // --- truncate ---
@@ -276,5 +611,145 @@ mod tests {
     fn round_to_even_negative() {
         assert_eq!(round_to_even(-0.5), 0);
         assert_eq!(round_to_even(-1.5), -2);
+        assert_eq!(round_to_even(-2.5), -2);
+        assert_eq!(round_to_even(-3.5), -4);
+    }
+
+    // --- sparkline ---
+    #[test]
+    fn sparkline_levels() {
+        assert_eq!(sparkline(&[0, 50, 100]), "\u{2581}\u{2584}\u{2588}");
+        assert_eq!(sparkline(&[]), "");
+    }
+
+    // --- Properties ---
+    use proptest::prelude::*;
+
+    /// Value shown by fmt_k ("1.2k" -> 1200).
+    fn unfmt_k(s: &str) -> f64 {
+        match s.chars().last() {
+            Some('k') => s[..s.len() - 1].parse::<f64>().unwrap() * 1e3,
+            Some('M') => s[..s.len() - 1].parse::<f64>().unwrap() * 1e6,
+            _ => s.parse().unwrap(),
+        }
+    }
+
+    /// Value shown by fmt_cost ("$1.8k" -> 1800).
+    fn unfmt_cost(s: &str) -> f64 {
+        unfmt_k(s.strip_prefix('$').unwrap())
+    }
+
+    /// Minutes shown by fmt_duration ("4h5m" -> 245).
+    fn unfmt_duration(s: &str) -> i64 {
+        let s = s.strip_suffix('m').unwrap();
+        match s.split_once('h') {
+            Some((h, m)) => h.parse::<i64>().unwrap() * 60 + m.parse::<i64>().unwrap(),
+            None => s.parse().unwrap(),
+        }
+    }
+
+    fn level(bar: &str) -> usize {
+        BARS.iter().position(|b| *b == bar).map_or(0, |i| i + 1)
+    }
+
+    #[test]
+    fn widest_below_next_unit() {
+        assert_eq!(fmt_k(999_999_999), "1000.0M");
+        assert_eq!(fmt_cost(999_999.0), "$1000.0k");
+    }
+
+    #[test]
+    fn fmt_cost_non_finite() {
+        assert_eq!(fmt_cost(f64::NAN), "$0.00");
+        assert_eq!(fmt_cost(f64::INFINITY), "$0.00");
+        assert_eq!(fmt_cost(f64::NEG_INFINITY), "$0.00");
+        assert_eq!(fmt_cost(-3.0), "$0.00");
+        let cents = Precision {
+            cents: true,
+            ..Precision::default()
+        };
+        assert_eq!(cents.cost(f64::NAN), "$0.00");
+        assert_eq!(cents.cost(-0.5), "$0.00");
+        assert_eq!(cents.cost(-0.0), "$0.00");
+    }
+
+    proptest! {
+        #[test]
+        fn fmt_k_monotonic(a in 0i64.., b in 0i64..) {
+            let (lo, hi) = (a.min(b), a.max(b));
+            prop_assert!(unfmt_k(&fmt_k(lo)) <= unfmt_k(&fmt_k(hi)));
+        }
+
+        #[test]
+        fn fmt_k_width(n in 0i64..) {
+            // "9223372036854.8M" at i64::MAX; "1000.0M" just below a billion
+            prop_assert!(fmt_k(n).len() <= 16);
+            if n < 1_000_000_000 {
+                prop_assert!(fmt_k(n).len() <= 7, "{}", fmt_k(n));
+            }
+        }
+
+        #[test]
+        fn fmt_k_any_i64(n in any::<i64>()) {
+            prop_assert!(!fmt_k(n).is_empty());
+        }
+
+        #[test]
+        fn fmt_cost_monotonic(a in 0.0..1e12f64, b in 0.0..1e12f64) {
+            let (lo, hi) = (a.min(b), a.max(b));
+            prop_assert!(unfmt_cost(&fmt_cost(lo)) <= unfmt_cost(&fmt_cost(hi)));
+        }
+
+        #[test]
+        fn fmt_cost_width(c in 0.0..1e6f64) {
+            // "$1000.0k" just below a million
+            prop_assert!(fmt_cost(c).len() <= 8, "{}", fmt_cost(c));
+        }
+
+        #[test]
+        fn fmt_cost_any_f64(c in any::<f64>()) {
+            let out = fmt_cost(c);
+            prop_assert!(out.starts_with('$'));
+            prop_assert!(!out.contains("NaN") && !out.contains("inf") && !out.contains('-'));
+        }
+
+        #[test]
+        fn fmt_duration_monotonic(a in any::<i64>(), b in any::<i64>()) {
+            let (lo, hi) = (a.min(b), a.max(b));
+            prop_assert!(unfmt_duration(&fmt_duration(lo)) <= unfmt_duration(&fmt_duration(hi)));
+        }
+
+        #[test]
+        fn fmt_duration_round_trips_minutes(ms in 0i64..) {
+            prop_assert_eq!(unfmt_duration(&fmt_duration(ms)), ms / 60_000);
+        }
+
+        #[test]
+        fn bar_char_monotonic(a in any::<i64>(), b in any::<i64>(), max in 1i64..) {
+            let (lo, hi) = (a.min(b), a.max(b));
+            prop_assert!(level(bar_char(lo, max)) <= level(bar_char(hi, max)));
+        }
+
+        #[test]
+        fn bar_char_any_i64(val in any::<i64>(), max in any::<i64>()) {
+            let bar = bar_char(val, max);
+            prop_assert!(bar.is_empty() || level(bar) > 0);
+            prop_assert_eq!(bar.is_empty(), val <= 0 || max <= 0);
+        }
+
+        #[test]
+        fn truncate_fits(s in "\\PC{0,40}", max_len in 0usize..50) {
+            let out = truncate(&s, max_len);
+            let len = out.chars().count();
+            prop_assert!(len <= max_len);
+            if s.chars().count() <= max_len {
+                prop_assert_eq!(&out, &s);
+            } else if let Some(kept) = out.strip_suffix('\u{2026}') {
+                prop_assert_eq!(len, max_len);
+                prop_assert!(s.starts_with(kept));
+            } else {
+                prop_assert!(max_len == 0 && out.is_empty());
+            }
+        }
     }
 }
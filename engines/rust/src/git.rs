@@ -1,27 +1,90 @@
+use crate::config::Config;
+#[cfg(feature = "git")]
+use crate::{cache, clock};
+#[cfg(feature = "git")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "git")]
 use std::fs;
+use std::path::Path;
 
+#[cfg_attr(not(feature = "git"), allow(dead_code))]
+#[derive(Clone, Default)]
 pub struct GitState {
     pub branch: String,
+    /// Commit id of HEAD (hex)
+    pub head: String,
     pub dirty: bool,
     pub ahead: i32,
     pub behind: i32,
+    /// The ahead/behind walk stopped at STATUSLINE_GIT_WALK_LIMIT
+    pub ahead_capped: bool,
+    pub behind_capped: bool,
     pub stash: i32,
+    /// Unix time and message of the most recent stash
+    pub stash_time: i64,
+    pub stash_message: String,
     pub in_worktree: bool,
     pub worktree_name: String,
+    /// How HEAD is signed (gpg, ssh, x509), empty when it is not; only
+    /// looked at with STATUSLINE_SIGNED
+    pub signature: String,
+    /// Staged files over STATUSLINE_LARGE_FILE_MB that HEAD does not have
+    pub large_staged: i32,
+    /// LFS files whose checkout is still the pointer (STATUSLINE_LFS_POINTERS)
+    pub lfs_pointers: i32,
+    /// The user's commits since local midnight (STATUSLINE_COMMITS_TODAY)
+    pub commits_today: i32,
+}
+
+/// Built without the `git` feature: there is never a git state.
+#[cfg(not(feature = "git"))]
+pub fn get(_cwd: &str, _project_dir: &str, _cfg: &Config) -> Option<GitState> {
+    None
+}
+
+/// Built without the `git` feature: CI status has no commit to look up.
+#[cfg(not(feature = "git"))]
+pub fn ci_target(_cwd: &str) -> Option<(String, String)> {
+    None
+}
+
+/// HEAD's commit id and the URL of the remote it would fetch from (the
+/// branch's remote, else `origin`), for STATUSLINE_CI.
+#[cfg(feature = "git")]
+pub fn ci_target(cwd: &str) -> Option<(String, String)> {
+    let repo = gix::discover(cwd).ok()?;
+    let head = repo.head_id().ok()?.to_string();
+    let remote = repo
+        .find_default_remote(gix::remote::Direction::Fetch)?
+        .ok()?;
+    let url = remote.url(gix::remote::Direction::Fetch)?;
+    Some((head, url.to_bstring().to_string()))
 }
 
 /// Get the git state for the given working directory, or None if not in a repo.
-pub fn get(cwd: &str) -> Option<GitState> {
+/// With STATUSLINE_DIRTY_SCOPE=project only changes under `project_dir` make
+/// it dirty.
+#[cfg(feature = "git")]
+pub fn get(cwd: &str, project_dir: &str, cfg: &Config) -> Option<GitState> {
     let repo = gix::discover(cwd).ok()?;
 
     let mut state = GitState {
         branch: String::new(),
+        head: String::new(),
         dirty: false,
         ahead: 0,
         behind: 0,
+        ahead_capped: false,
+        behind_capped: false,
         stash: 0,
+        stash_time: 0,
+        stash_message: String::new(),
         in_worktree: false,
         worktree_name: String::new(),
+        commits_today: 0,
+        signature: String::new(),
+        large_staged: 0,
+        lfs_pointers: 0,
     };
 
     // Branch name — get the symbolic ref name from HEAD
@@ -33,123 +96,293 @@ pub fn get(cwd: &str) -> Option<GitState> {
     state.branch = referent.shorten().to_string();
 
     // Dirty check via index stat comparison
-    state.dirty = check_dirty(&repo);
+    let scope = match cfg.dirty_scope.as_str() {
+        "project" => repo
+            .workdir()
+            .and_then(|w| project_pathspec(w, Path::new(project_dir))),
+        _ => None,
+    };
+    state.dirty = check_dirty(&repo, scope, cfg);
 
     // Ahead/behind
     let head_commit = head.peel_to_commit().ok()?;
     let head_id = head_commit.id;
-    let (ahead, behind) = get_ahead_behind(&repo, head_id, &state.branch);
+    state.head = head_id.to_string();
+    if cfg.signed {
+        if let Ok(Some((sig, _))) = head_commit.signature() {
+            state.signature = signature_kind(&sig.to_string()).to_string();
+        }
+    }
+    let ((ahead, ahead_capped), (behind, behind_capped)) =
+        get_ahead_behind(&repo, head_id, &state.branch, cfg);
     state.ahead = ahead;
     state.behind = behind;
+    state.ahead_capped = ahead_capped;
+    state.behind_capped = behind_capped;
 
     // Worktree detection
-    detect_worktree(&repo, &mut state);
+    detect_worktree(&repo, &cfg.worktree_pattern, &mut state);
 
     // Stash count (file-based, same as Go)
-    state.stash = count_stash(&repo);
+    let (stash, latest) = read_stash(&repo);
+    state.stash = stash;
+    if let Some((time, message)) = latest {
+        state.stash_time = time;
+        state.stash_message = message;
+    }
+
+    if cfg.large_file_mb > 0 || cfg.lfs_pointers {
+        let max_bytes = cfg.large_file_mb.saturating_mul(1024 * 1024);
+        (state.large_staged, state.lfs_pointers) = large_files(&repo, max_bytes, cfg.lfs_pointers);
+    }
+
+    if cfg.commits_today {
+        state.commits_today = commits_today(&repo, head_id, cfg.git_walk_limit);
+    }
 
     Some(state)
 }
 
-fn check_dirty(repo: &gix::Repository) -> bool {
+#[cfg(feature = "git")]
+fn check_dirty(repo: &gix::Repository, pathspec: Option<String>, cfg: &Config) -> bool {
     // Use gix status platform for a proper dirty check
-    let status = match repo.status(gix::progress::Discard) {
+    let mut status = match repo.status(gix::progress::Discard) {
         Ok(s) => s,
         Err(_) => return false,
     };
+    if skip_untracked(repo, &cfg.untracked) {
+        // No directory walk at all, only tracked files are compared
+        status = status.untracked_files(gix::status::UntrackedFiles::None);
+    }
 
-    let iter = match status.into_index_worktree_iter(None) {
+    let iter = match status.into_index_worktree_iter(pathspec.map(gix::bstr::BString::from)) {
         Ok(iter) => iter,
         Err(_) => return false,
     };
 
-    // If there's any entry, the repo is dirty
+    // If there's any entry not matched by STATUSLINE_DIRTY_IGNORE, the repo is dirty
     for item in iter {
-        match item {
-            Ok(_) => return true,
-            Err(_) => continue,
+        let Ok(item) = item else { continue };
+        let mut path = item.rela_path().to_string();
+        // Untracked directories are reported as one entry, without a slash
+        if repo.workdir().is_some_and(|w| w.join(&path).is_dir()) {
+            path.push('/');
+        }
+        if !ignored(&path, &cfg.dirty_ignore) {
+            return true;
         }
     }
 
     false
 }
 
+#[cfg(feature = "git")]
 fn get_ahead_behind(
     repo: &gix::Repository,
     head_id: gix::ObjectId,
     branch_name: &str,
-) -> (i32, i32) {
+    cfg: &Config,
+) -> ((i32, bool), (i32, bool)) {
+    let none = ((0, false), (0, false));
+    // Upstream, else the configured default branch for local-only branches
+    let upstream_id = match upstream_id(repo, branch_name).or_else(|| {
+        default_branch_refs(&cfg.default_branch)
+            .iter()
+            .find_map(|name| peeled_id(repo, name))
+    }) {
+        Some(id) => id,
+        None => return none,
+    };
+
+    if head_id == upstream_id {
+        return none;
+    }
+
+    // Unrelated histories have no divergence to show
+    let merge_base: gix::ObjectId = match repo.merge_base(head_id, upstream_id) {
+        Ok(mb) => mb.into(),
+        Err(_) => return none,
+    };
+
+    // Ahead: commits only on HEAD; behind: commits only on upstream. A side
+    // that is an ancestor of the other has nothing of its own.
+    let limit = cfg.git_walk_limit;
+    let ahead = match merge_base == head_id {
+        true => (0, false),
+        false => count_commits(repo, head_id, upstream_id, limit),
+    };
+    let behind = match merge_base == upstream_id {
+        true => (0, false),
+        false => count_commits(repo, upstream_id, head_id, limit),
+    };
+
+    (ahead, behind)
+}
+
+/// Index size above which STATUSLINE_UNTRACKED=auto skips the untracked walk.
+/// gix reads neither fsmonitor nor the untracked cache, so on repos this big
+/// the directory walk alone can blow the render budget.
+#[cfg(feature = "git")]
+const AUTO_UNTRACKED_MAX_FILES: usize = 100_000;
+
+#[cfg(feature = "git")]
+fn skip_untracked(repo: &gix::Repository, mode: &str) -> bool {
+    match mode {
+        "no" => true,
+        "auto" => repo
+            .index_or_empty()
+            .is_ok_and(|index| index.entries().len() > AUTO_UNTRACKED_MAX_FILES),
+        _ => false,
+    }
+}
+
+/// Whether `path` (relative to the worktree top) matches one of the
+/// comma-separated gitignore-style `patterns`: `name` at any depth, `dir/`
+/// for everything below a directory, a leading or inner `/` anchors at the
+/// top, and `*` and `?` glob within one path component.
+#[cfg_attr(not(feature = "git"), allow(dead_code))]
+fn ignored(path: &str, patterns: &str) -> bool {
+    let parts: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+    let is_dir = path.ends_with('/');
+    patterns
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .any(|pattern| {
+            let dir_only = pattern.ends_with('/');
+            let pattern = pattern.trim_end_matches('/');
+            let anchored = pattern.contains('/');
+            let pat: Vec<&str> = pattern.trim_start_matches('/').split('/').collect();
+            let starts = if anchored { 0..1 } else { 0..parts.len() };
+            starts.into_iter().any(|start| {
+                let end = start + pat.len();
+                end <= parts.len()
+                    && (!dir_only || end < parts.len() || is_dir)
+                    && pat.iter().zip(&parts[start..end]).all(|(p, c)| glob(p, c))
+            })
+        })
+}
+
+/// Whether `branch` matches one of the comma-separated names or globs in
+/// `patterns` (STATUSLINE_PROTECTED_BRANCHES); `*` also spans `/`.
+pub fn is_protected(branch: &str, patterns: &str) -> bool {
+    patterns
+        .split(',')
+        .map(str::trim)
+        .any(|p| !p.is_empty() && glob(p, branch))
+}
+
+/// `*` (any run) and `?` (one character) wildcard match of a whole string.
+fn glob(pattern: &str, text: &str) -> bool {
+    let (p, t): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut pi, mut ti) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ti));
+            pi += 1;
+        } else if let Some((star, from)) = backtrack {
+            // Let the last `*` swallow one more character
+            backtrack = Some((star, from + 1));
+            pi = star + 1;
+            ti = from + 1;
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// Pathspec for the project dir inside the worktree, relative to the top so
+/// it does not depend on the process cwd. None when the project is the whole
+/// worktree or lies outside it.
+#[cfg_attr(not(feature = "git"), allow(dead_code))]
+fn project_pathspec(workdir: &Path, project_dir: &Path) -> Option<String> {
+    if project_dir.as_os_str().is_empty() {
+        return None;
+    }
+    let canonical = |p: &Path| std::fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+    let (workdir, project_dir) = (canonical(workdir), canonical(project_dir));
+    let rel = project_dir.strip_prefix(&workdir).ok()?;
+    if rel.as_os_str().is_empty() {
+        return None;
+    }
+    Some(format!(":(top){}", rel.to_string_lossy()))
+}
+
+/// Tip of the branch's upstream (`branch.<name>.remote` and `.merge`).
+#[cfg(feature = "git")]
+fn upstream_id(repo: &gix::Repository, branch_name: &str) -> Option<gix::ObjectId> {
     // Read branch config for upstream
     let config = repo.config_snapshot();
 
     let remote_key = format!("branch.{}.remote", branch_name);
     let merge_key = format!("branch.{}.merge", branch_name);
 
-    let remote: String = match config.string(&remote_key) {
-        Some(v) => v.to_string(),
-        None => return (0, 0),
-    };
-    let merge_ref: String = match config.string(&merge_key) {
-        Some(v) => v.to_string(),
-        None => return (0, 0),
-    };
+    let remote = config.string(&remote_key)?.to_string();
+    let merge_ref = config.string(&merge_key)?.to_string();
 
     // Convert merge ref (refs/heads/main) to remote tracking ref (refs/remotes/origin/main)
     let short_name = merge_ref.strip_prefix("refs/heads/").unwrap_or(&merge_ref);
     let upstream_ref = format!("refs/remotes/{}/{}", remote, short_name);
+    peeled_id(repo, &upstream_ref)
+}
 
-    let upstream_id = match repo.find_reference(&upstream_ref) {
-        Ok(r) => match r.into_fully_peeled_id() {
-            Ok(id) => id.detach(),
-            Err(_) => return (0, 0),
-        },
-        Err(_) => return (0, 0),
-    };
+#[cfg(feature = "git")]
+fn peeled_id(repo: &gix::Repository, name: &str) -> Option<gix::ObjectId> {
+    let reference = repo.find_reference(name).ok()?;
+    reference.into_fully_peeled_id().ok().map(|id| id.detach())
+}
 
-    if head_id == upstream_id {
-        return (0, 0);
+/// Refs to try for STATUSLINE_DEFAULT_BRANCH, in order: `auto` follows
+/// origin/HEAD, a short name is a remote-tracking branch or a local one.
+#[cfg_attr(not(feature = "git"), allow(dead_code))]
+fn default_branch_refs(name: &str) -> Vec<String> {
+    match name.trim() {
+        "" => Vec::new(),
+        "auto" => vec!["refs/remotes/origin/HEAD".into()],
+        full if full.starts_with("refs/") => vec![full.into()],
+        short => vec![
+            format!("refs/remotes/{}", short),
+            format!("refs/heads/{}", short),
+        ],
     }
-
-    // Find merge base and count ahead/behind
-    let merge_base = match repo.merge_base(head_id, upstream_id) {
-        Ok(mb) => mb.into(),
-        Err(_) => return (0, 0),
-    };
-
-    // Count ahead: commits from HEAD to merge base
-    let ahead = count_commits(repo, head_id, merge_base);
-    // Count behind: commits from upstream to merge base
-    let behind = count_commits(repo, upstream_id, merge_base);
-
-    (ahead, behind)
 }
 
-fn count_commits(repo: &gix::Repository, from: gix::ObjectId, to: gix::ObjectId) -> i32 {
-    let platform = repo.rev_walk([from]);
+/// Commits reachable from `from` but not from `hidden`, like `git rev-list
+/// --count from ^hidden`, so merges across both sides are not counted twice.
+/// Stops after `limit` commits (0 = no limit); the flag tells whether it did.
+#[cfg(feature = "git")]
+fn count_commits(
+    repo: &gix::Repository,
+    from: gix::ObjectId,
+    hidden: gix::ObjectId,
+    limit: usize,
+) -> (i32, bool) {
+    let platform = repo.rev_walk([from]).with_hidden([hidden]);
     let iter = match platform.all() {
         Ok(iter) => iter,
-        Err(_) => return 0,
+        Err(_) => return (0, false),
     };
 
     let mut count = 0;
-    let limit = 1000;
     for info in iter {
-        let info = match info {
-            Ok(i) => i,
-            Err(_) => break,
-        };
-        if info.id == to {
+        if info.is_err() {
             break;
         }
-        count += 1;
-        if count >= limit {
-            break;
+        if limit > 0 && count == limit {
+            return (count as i32, true);
         }
+        count += 1;
     }
-    count
+    (count as i32, false)
 }
 
-fn detect_worktree(repo: &gix::Repository, state: &mut GitState) {
+#[cfg(feature = "git")]
+fn detect_worktree(repo: &gix::Repository, patterns: &str, state: &mut GitState) {
     // Check if this is a linked worktree
     let kind = repo.kind();
     let is_linked = matches!(kind, gix::repository::Kind::LinkedWorkTree);
@@ -157,29 +390,318 @@ fn detect_worktree(repo: &gix::Repository, state: &mut GitState) {
     if is_linked {
         state.in_worktree = true;
 
-        // Try to extract worktree name from the worktree path
         if let Some(work_dir) = repo.workdir() {
-            let toplevel = work_dir.to_string_lossy().to_string();
-            let toplevel = toplevel.trim_end_matches('/');
-
-            // Check if path contains /.worktrees/
-            if let Some(idx) = toplevel.find("/.worktrees/") {
-                state.worktree_name = toplevel[idx + "/.worktrees/".len()..].to_string();
-            } else {
-                state.worktree_name = toplevel.to_string();
+            // git_dir is where the worktree's `.git` file points:
+            // <common dir>/worktrees/<id>
+            state.worktree_name =
+                worktree_name(&work_dir.to_string_lossy(), repo.git_dir(), patterns);
+        }
+    }
+}
+
+/// Display name of a linked worktree: the path after the first of the
+/// comma-separated `patterns` it contains (e.g. `/.worktrees/`), else the
+/// worktree id git gave it (the name of its git dir), else the directory name.
+#[cfg_attr(not(feature = "git"), allow(dead_code))]
+fn worktree_name(toplevel: &str, git_dir: &Path, patterns: &str) -> String {
+    let toplevel = toplevel.trim_end_matches('/');
+    for pattern in patterns.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        if let Some(idx) = toplevel.find(pattern) {
+            let rest = &toplevel[idx + pattern.len()..];
+            if !rest.is_empty() {
+                return rest.to_string();
+            }
+        }
+    }
+    git_dir
+        .file_name()
+        .or_else(|| Path::new(toplevel).file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| toplevel.to_string())
+}
+
+#[cfg(feature = "git")]
+#[derive(Serialize, Deserialize)]
+struct CommitsToday {
+    head: String,
+    since: i64,
+    email: String,
+    count: i32,
+}
+
+/// Commits since local midnight on HEAD's history authored by `user.email`
+/// (all authors when it is unset). The walk stops at the first older commit
+/// or after `limit` commits, and the result is cached per repository until
+/// HEAD, the day or the email changes.
+#[cfg(feature = "git")]
+fn commits_today(repo: &gix::Repository, head_id: gix::ObjectId, limit: usize) -> i32 {
+    use gix::revision::walk::Sorting;
+    use gix::traverse::commit::simple::CommitTimeOrder;
+
+    let since = clock::day_start(clock::now_unix());
+    let email = repo
+        .config_snapshot()
+        .string("user.email")
+        .map(|e| e.to_string())
+        .unwrap_or_default();
+    let path = cache::cache_dir().join(format!(
+        "commits-today-{}.json",
+        cache::file_id(&cache::project_hash(&repo.common_dir().to_string_lossy()))
+    ));
+    let cached: Option<CommitsToday> = fs::read_to_string(&path)
+        .ok()
+        .and_then(|d| serde_json::from_str(&d).ok());
+    let head = head_id.to_string();
+    if let Some(c) = cached.filter(|c| c.head == head && c.since == since && c.email == email) {
+        return c.count;
+    }
+
+    let walk = repo
+        .rev_walk([head_id])
+        .sorting(Sorting::ByCommitTimeCutoff {
+            order: CommitTimeOrder::NewestFirst,
+            seconds: since,
+        });
+    let mut count = 0;
+    if let Ok(iter) = walk.all() {
+        for info in iter.take(if limit > 0 { limit } else { usize::MAX }) {
+            let Some(commit) = info.ok().and_then(|i| i.object().ok()) else {
+                break;
+            };
+            let mine = commit
+                .author()
+                .is_ok_and(|a| email.is_empty() || a.email.eq_ignore_ascii_case(email.as_bytes()));
+            if mine {
+                count += 1;
             }
         }
     }
+
+    let _ = fs::create_dir_all(cache::cache_dir());
+    let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    let entry = CommitsToday {
+        head,
+        since,
+        email,
+        count,
+    };
+    if fs::write(&tmp, serde_json::to_string(&entry).unwrap_or_default()).is_ok() {
+        let _ = fs::rename(&tmp, &path);
+    }
+    count
 }
 
-fn count_stash(repo: &gix::Repository) -> i32 {
+#[cfg(feature = "git")]
+fn read_stash(repo: &gix::Repository) -> (i32, Option<(i64, String)>) {
     let common_dir = repo.common_dir().to_path_buf();
     let stash_log = common_dir.join("logs").join("refs").join("stash");
 
     let content = match fs::read_to_string(&stash_log) {
         Ok(c) => c,
-        Err(_) => return 0,
+        Err(_) => return (0, None),
+    };
+
+    let count = content.lines().filter(|l| !l.trim().is_empty()).count() as i32;
+    (count, latest_stash(&content))
+}
+
+/// Staged files over `max_bytes` (0 = unchecked) that are new or changed
+/// against HEAD, and with `lfs` the LFS-tracked files whose checkout is still
+/// the pointer text. LFS-tracked files are never large: their blob is the
+/// pointer.
+#[cfg(feature = "git")]
+fn large_files(repo: &gix::Repository, max_bytes: u64, lfs: bool) -> (i32, i32) {
+    let (Some(workdir), Ok(index)) = (repo.workdir(), repo.index_or_empty()) else {
+        return (0, 0);
+    };
+    let tree = repo.head_tree().ok();
+    let attributes = fs::read_to_string(workdir.join(".gitattributes")).unwrap_or_default();
+    let lfs_tracked = lfs_patterns(&attributes);
+    let (mut large, mut pointers) = (0, 0);
+    for entry in index.entries() {
+        let path = entry.path(&index).to_string();
+        // The index keeps the size modulo 4 GiB, so larger files can slip by
+        let size = u64::from(entry.stat.size);
+        let in_lfs = !lfs_tracked.is_empty() && ignored(&path, &lfs_tracked);
+        if in_lfs {
+            // Pointer files are ~130 bytes, pulled content rarely that small
+            if lfs && size < 1024 && is_lfs_pointer(&workdir.join(&path)) {
+                pointers += 1;
+            }
+        } else if max_bytes > 0 && size > max_bytes {
+            let committed = tree
+                .as_ref()
+                .and_then(|t| t.lookup_entry_by_path(&path).ok().flatten())
+                .is_some_and(|e| e.object_id() == entry.id);
+            if !committed {
+                large += 1;
+            }
+        }
+    }
+    (large, pointers)
+}
+
+/// Patterns of `.gitattributes` lines with `filter=lfs`, comma-separated for
+/// `ignored`.
+#[cfg_attr(not(feature = "git"), allow(dead_code))]
+fn lfs_patterns(attributes: &str) -> String {
+    attributes
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pattern = fields.next().filter(|p| !p.starts_with('#'))?;
+            fields.any(|a| a == "filter=lfs").then_some(pattern)
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(feature = "git")]
+fn is_lfs_pointer(path: &Path) -> bool {
+    use std::io::Read;
+    let mut head = [0u8; 40];
+    fs::File::open(path)
+        .and_then(|mut f| f.read(&mut head))
+        .is_ok_and(|n| head[..n].starts_with(b"version https://git-lfs.github.com/spec"))
+}
+
+/// Signature scheme from the armor of a commit's `gpgsig` header. Only the
+/// presence is checked: gix cannot verify signatures (`git log
+/// --show-signature` does).
+#[cfg_attr(not(feature = "git"), allow(dead_code))]
+fn signature_kind(sig: &str) -> &'static str {
+    if sig.contains("BEGIN SSH SIGNATURE") {
+        "ssh"
+    } else if sig.contains("BEGIN SIGNED MESSAGE") {
+        "x509"
+    } else {
+        "gpg"
+    }
+}
+
+/// Time and message of the newest entry (the last line) of the stash reflog:
+/// `<old> <new> <name> <<email>> <time> <tz>\t<message>`. The message loses
+/// git's `On <branch>: ` prefix; automatic ones (`WIP on <branch>: ...`)
+/// become `WIP`.
+#[cfg_attr(not(feature = "git"), allow(dead_code))]
+fn latest_stash(log: &str) -> Option<(i64, String)> {
+    let line = log.lines().rev().find(|l| !l.trim().is_empty())?;
+    let (entry, message) = line.split_once('\t').unwrap_or((line, ""));
+    let time = entry.rsplit(' ').nth(1)?.parse().ok()?;
+    let message = match message.split_once(": ") {
+        Some((prefix, _)) if prefix.starts_with("WIP on ") => "WIP".to_string(),
+        Some((_, text)) => text.to_string(),
+        None => message.to_string(),
     };
+    Some((time, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    content.lines().filter(|l| !l.trim().is_empty()).count() as i32
+    #[test]
+    fn default_branch_refs_resolution_order() {
+        assert!(default_branch_refs("").is_empty());
+        assert_eq!(default_branch_refs("auto"), ["refs/remotes/origin/HEAD"]);
+        assert_eq!(
+            default_branch_refs("origin/main"),
+            ["refs/remotes/origin/main", "refs/heads/origin/main"]
+        );
+        assert_eq!(default_branch_refs("refs/heads/main"), ["refs/heads/main"]);
+    }
+
+    #[test]
+    fn lfs_patterns_from_gitattributes() {
+        let attributes = "# assets\n*.psd filter=lfs diff=lfs merge=lfs -text\n\
+                          *.rs text\nmodels/** filter=lfs -text\n";
+        assert_eq!(lfs_patterns(attributes), "*.psd,models/**");
+        assert!(ignored("art/cover.psd", &lfs_patterns(attributes)));
+        assert_eq!(lfs_patterns("*.rs text"), "");
+    }
+
+    #[test]
+    fn signature_kind_from_armor() {
+        assert_eq!(signature_kind("-----BEGIN PGP SIGNATURE-----\n..."), "gpg");
+        assert_eq!(signature_kind("-----BEGIN SSH SIGNATURE-----\n..."), "ssh");
+        assert_eq!(
+            signature_kind("-----BEGIN SIGNED MESSAGE-----\n..."),
+            "x509"
+        );
+    }
+
+    #[test]
+    fn latest_stash_reads_the_last_reflog_entry() {
+        let log = "0000 1111 A U Thor <a@x> 1700000000 +0100\tWIP on main: 1111 fix parser\n\
+                   1111 2222 A U Thor <a@x> 1700086400 +0100\tOn feat/x: half-done retry\n";
+        assert_eq!(
+            latest_stash(log),
+            Some((1700086400, "half-done retry".into()))
+        );
+        assert_eq!(
+            latest_stash(log.lines().next().unwrap()),
+            Some((1700000000, "WIP".into()))
+        );
+        assert_eq!(latest_stash(""), None);
+    }
+
+    #[test]
+    fn protected_branches_match_names_and_globs() {
+        let patterns = "main, master, release/*";
+        assert!(is_protected("main", patterns));
+        assert!(is_protected("release/2.1", patterns));
+        assert!(!is_protected("maintenance", patterns));
+        assert!(!is_protected("feature/release", patterns));
+        assert!(!is_protected("main", ""));
+    }
+
+    #[test]
+    fn ignored_follows_gitignore_style_patterns() {
+        let patterns = "node_modules/, .claude/, *.lock, /build, docs/gen/*.md";
+        assert!(ignored("web/node_modules/x/index.js", patterns));
+        assert!(ignored("node_modules/", patterns));
+        assert!(!ignored("src/node_modules", patterns));
+        assert!(ignored(".claude/settings.json", patterns));
+        assert!(ignored("api/Cargo.lock", patterns));
+        assert!(ignored("build/out.o", patterns));
+        assert!(!ignored("web/build/out.o", patterns));
+        assert!(ignored("docs/gen/api.md", patterns));
+        assert!(!ignored("docs/gen/api.txt", patterns));
+        assert!(!ignored("src/main.rs", patterns));
+        assert!(!ignored("src/main.rs", ""));
+    }
+
+    #[test]
+    fn project_pathspec_is_relative_to_the_top() {
+        let top = Path::new("/src/mono");
+        assert_eq!(
+            project_pathspec(top, Path::new("/src/mono/apps/web")),
+            Some(":(top)apps/web".into())
+        );
+        assert_eq!(project_pathspec(top, Path::new("/src/mono")), None);
+        assert_eq!(project_pathspec(top, Path::new("/src/other")), None);
+        assert_eq!(project_pathspec(top, Path::new("")), None);
+    }
+
+    #[test]
+    fn worktree_name_prefers_pattern_then_git_dir() {
+        let git_dir = Path::new("/src/app/.git/worktrees/feature-x");
+        assert_eq!(
+            worktree_name("/src/app/.worktrees/fix/login", git_dir, "/.worktrees/"),
+            "fix/login"
+        );
+        assert_eq!(
+            worktree_name("/src/wt/feature-x-2/", git_dir, "/.worktrees/,/wt/"),
+            "feature-x-2"
+        );
+        // ../wt/, bare repo + worktrees/<name>, git's default layout
+        assert_eq!(
+            worktree_name("/src/app-feature", git_dir, "/.worktrees/"),
+            "feature-x"
+        );
+        assert_eq!(
+            worktree_name("/src/app-feature", Path::new("/"), ""),
+            "app-feature"
+        );
+    }
 }
@@ -0,0 +1,144 @@
+//! Git `prepare-commit-msg` hook that adds an `AI-Cost: $1.20 / 42k tok`
+//! trailer with the Claude usage since the previous commit.
+//!
+//! `statusline hook install|uninstall` manages the hook script in the current
+//! repository; git then calls `statusline hook prepare-commit-msg`. Usage
+//! comes from the STATUSLINE_HISTORY snapshots of sessions in the repository.
+
+use crate::config::Config;
+use crate::format;
+use crate::history;
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+const MARKER: &str = "# Installed by statusline hook install";
+const TRAILER: &str = "AI-Cost";
+
+/// `statusline hook <install|uninstall|prepare-commit-msg> ...`
+pub fn run(cfg: &Config) -> Result<String, String> {
+    let args = &cfg.command_args;
+    match args.first().map(String::as_str) {
+        Some("install") => install(args.iter().any(|a| a == "--force")),
+        Some("uninstall") => uninstall(),
+        Some("prepare-commit-msg") => {
+            let file = args.get(1).ok_or("missing commit message file")?;
+            prepare_commit_msg(Path::new(file), args.get(2).map(String::as_str))
+        }
+        _ => Err("usage: statusline hook install [--force] | uninstall | prepare-commit-msg FILE [SOURCE]".into()),
+    }
+}
+
+fn git(args: &[&str]) -> Result<String, String> {
+    let out = Command::new("git")
+        .args(args)
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| format!("cannot run git: {}", e))?;
+    if !out.status.success() {
+        return Err(format!("git {} failed", args.join(" ")));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+fn hook_path() -> Result<PathBuf, String> {
+    let path = git(&["rev-parse", "--git-path", "hooks/prepare-commit-msg"])
+        .map_err(|_| "not inside a git repository".to_string())?;
+    Ok(PathBuf::from(path))
+}
+
+fn script() -> String {
+    let exe = env::current_exe()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|_| "statusline".into());
+    format!(
+        "#!/bin/sh\n{}\n'{}' hook prepare-commit-msg \"$@\" || true\n",
+        MARKER,
+        exe.replace('\'', "'\\''")
+    )
+}
+
+fn install(force: bool) -> Result<String, String> {
+    let path = hook_path()?;
+    if let Ok(existing) = fs::read_to_string(&path) {
+        if !existing.contains(MARKER) && !force {
+            return Err(format!(
+                "{} exists and was not installed by statusline (use --force to replace it)",
+                path.display()
+            ));
+        }
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+    }
+    fs::write(&path, script()).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+        .map_err(|e| format!("cannot chmod {}: {}", path.display(), e))?;
+    Ok(format!("[ok] Installed {}", path.display()))
+}
+
+fn uninstall() -> Result<String, String> {
+    let path = hook_path()?;
+    match fs::read_to_string(&path) {
+        Err(_) => Ok(format!("[--] {} not found", path.display())),
+        Ok(existing) if !existing.contains(MARKER) => Ok(format!(
+            "[--] {} was not installed by statusline, left alone",
+            path.display()
+        )),
+        Ok(_) => {
+            fs::remove_file(&path)
+                .map_err(|e| format!("cannot remove {}: {}", path.display(), e))?;
+            Ok(format!("[ok] Removed {}", path.display()))
+        }
+    }
+}
+
+/// `AI-Cost` trailer value, or None when there was no usage.
+fn trailer(cost: f64, tokens: u64) -> Option<String> {
+    (cost >= 0.005).then(|| format!("${:.2} / {} tok", cost, format::fmt_k(tokens as i64)))
+}
+
+/// Add the trailer to the message file. Merges and squashes are left alone,
+/// and so is a message whose trailer is already there (amend).
+fn prepare_commit_msg(file: &Path, source: Option<&str>) -> Result<String, String> {
+    if matches!(source, Some("merge" | "squash")) {
+        return Ok(String::new());
+    }
+    let msg =
+        fs::read_to_string(file).map_err(|e| format!("cannot read {}: {}", file.display(), e))?;
+    if msg.lines().any(|l| l.starts_with(&format!("{}:", TRAILER))) {
+        return Ok(String::new());
+    }
+    let root = git(&["rev-parse", "--show-toplevel"])?;
+    // No commit yet: count everything recorded for the repository
+    let since = git(&["log", "-1", "--format=%ct"])
+        .ok()
+        .and_then(|t| t.parse().ok())
+        .unwrap_or(0);
+    let (cost, tokens) = history::usage_since(Path::new(&root), since);
+    let Some(value) = trailer(cost, tokens) else {
+        return Ok(String::new());
+    };
+    let file = file.to_string_lossy();
+    git(&[
+        "interpret-trailers",
+        "--in-place",
+        "--trailer",
+        &format!("{}: {}", TRAILER, value),
+        &file,
+    ])?;
+    Ok(String::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trailer_formats_cost_and_tokens() {
+        assert_eq!(trailer(1.2, 42_000).as_deref(), Some("$1.20 / 42k tok"));
+        assert_eq!(trailer(0.0, 0), None);
+    }
+}
@@ -0,0 +1,180 @@
+//! Inline-image sparklines for terminals with kitty or sixel graphics
+//! (feature `graphics`, selected by STATUSLINE_GRAPHICS).
+
+use std::env;
+
+/// Pixels per sample and image height.
+const COL_PX: usize = 4;
+const HEIGHT_PX: usize = 12;
+/// Kitty graphics payloads are sent in chunks of at most 4096 bytes.
+const KITTY_CHUNK: usize = 4096;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Protocol {
+    Kitty,
+    Sixel,
+}
+
+/// Resolve the STATUSLINE_GRAPHICS setting (`kitty`, `sixel` or `auto`).
+/// `auto` only picks a protocol for terminals known to support it, and never
+/// inside tmux, which does not pass graphics through by default.
+pub fn protocol(setting: &str) -> Option<Protocol> {
+    match setting {
+        "kitty" => Some(Protocol::Kitty),
+        "sixel" => Some(Protocol::Sixel),
+        "auto" => detect(
+            &var("TERM"),
+            &var("TERM_PROGRAM"),
+            env::var_os("KITTY_WINDOW_ID").is_some(),
+            env::var_os("TMUX").is_some(),
+        ),
+        _ => None,
+    }
+}
+
+fn var(name: &str) -> String {
+    env::var(name).unwrap_or_default()
+}
+
+fn detect(term: &str, program: &str, kitty_window: bool, tmux: bool) -> Option<Protocol> {
+    if tmux {
+        return None;
+    }
+    if kitty_window || term == "xterm-kitty" || matches!(program, "WezTerm" | "ghostty") {
+        Some(Protocol::Kitty)
+    } else if term.contains("sixel") || matches!(term, "foot" | "mlterm") {
+        Some(Protocol::Sixel)
+    } else {
+        None
+    }
+}
+
+/// Sparkline of percentages (0-100) as an inline image in `rgb`.
+pub fn sparkline(values: &[u8], rgb: (u8, u8, u8), proto: Protocol) -> String {
+    if values.is_empty() {
+        return String::new();
+    }
+    let width = values.len() * COL_PX;
+    // Column heights in pixels, at least one so zero still shows a baseline
+    let heights: Vec<usize> = values
+        .iter()
+        .map(|&v| ((v.min(100) as usize * HEIGHT_PX + 50) / 100).max(1))
+        .collect();
+    let filled =
+        |x: usize, y: usize| y >= HEIGHT_PX - heights[x / COL_PX] && x % COL_PX != COL_PX - 1;
+    match proto {
+        Protocol::Kitty => kitty(width, values.len().div_ceil(2), rgb, filled),
+        Protocol::Sixel => sixel(width, rgb, filled),
+    }
+}
+
+fn kitty(
+    width: usize,
+    cols: usize,
+    rgb: (u8, u8, u8),
+    filled: impl Fn(usize, usize) -> bool,
+) -> String {
+    let mut pixels = Vec::with_capacity(width * HEIGHT_PX * 4);
+    for y in 0..HEIGHT_PX {
+        for x in 0..width {
+            let a = if filled(x, y) { 255 } else { 0 };
+            pixels.extend_from_slice(&[rgb.0, rgb.1, rgb.2, a]);
+        }
+    }
+    let payload = base64(&pixels);
+    let chunks: Vec<&str> = payload
+        .as_bytes()
+        .chunks(KITTY_CHUNK)
+        .map(|c| std::str::from_utf8(c).unwrap_or_default())
+        .collect();
+    let mut out = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = if i + 1 < chunks.len() { 1 } else { 0 };
+        if i == 0 {
+            out.push_str(&format!(
+                "\x1b_Ga=T,f=32,s={},v={},c={},r=1,q=2,m={};{}\x1b\\",
+                width, HEIGHT_PX, cols, more, chunk
+            ));
+        } else {
+            out.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+        }
+    }
+    out
+}
+
+fn sixel(width: usize, rgb: (u8, u8, u8), filled: impl Fn(usize, usize) -> bool) -> String {
+    // Sixel colors are given in percent
+    let pct = |c: u8| c as usize * 100 / 255;
+    let mut out = format!(
+        "\x1bPq\"1;1;{};{}#1;2;{};{};{}",
+        width,
+        HEIGHT_PX,
+        pct(rgb.0),
+        pct(rgb.1),
+        pct(rgb.2)
+    );
+    // Each sixel row encodes a band of six pixel rows
+    for band in 0..HEIGHT_PX.div_ceil(6) {
+        out.push_str("#1");
+        for x in 0..width {
+            let mut bits = 0u8;
+            for bit in 0..6 {
+                let y = band * 6 + bit;
+                if y < HEIGHT_PX && filled(x, y) {
+                    bits |= 1 << bit;
+                }
+            }
+            out.push((63 + bits) as char);
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_padding() {
+        assert_eq!(base64(b"a"), "YQ==");
+        assert_eq!(base64(b"ab"), "YWI=");
+        assert_eq!(base64(b"abc"), "YWJj");
+    }
+
+    #[test]
+    fn detect_terminals() {
+        assert_eq!(
+            detect("xterm-kitty", "", false, false),
+            Some(Protocol::Kitty)
+        );
+        assert_eq!(detect("foot", "", false, false), Some(Protocol::Sixel));
+        assert_eq!(detect("xterm-kitty", "", true, true), None);
+        assert_eq!(
+            detect("xterm-256color", "Apple_Terminal", false, false),
+            None
+        );
+    }
+}
@@ -0,0 +1,329 @@
+//! Usage history (STATUSLINE_HISTORY): cost snapshots per session with the
+//! git branch at the time, and `statusline stats [--by-branch]`.
+//!
+//! Each session appends to `<cache dir>/history/<session>.jsonl` whenever its
+//! cost or branch changes. A session's cost is cumulative, so the increase
+//! since the previous snapshot is attributed to the branch of the new one.
+
+use crate::cache;
+use crate::config::Config;
+use crate::format;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    ts: u64,
+    project: String,
+    branch: String,
+    cost: f64,
+    #[serde(default)]
+    tokens: u64,
+}
+
+fn history_dir() -> PathBuf {
+    cache::cache_dir().join("history")
+}
+
+/// Tokens used by all sessions this week, kept current by `record` so the
+/// plan pace segment reads one small file instead of every history file.
+#[derive(Serialize, Deserialize)]
+struct WeekTokens {
+    /// Week start (unix seconds) the count belongs to
+    since: u64,
+    tokens: u64,
+}
+
+fn week_tokens_path() -> PathBuf {
+    cache::cache_dir().join("week-tokens.json")
+}
+
+/// Append a snapshot unless cost and branch are unchanged since the last one.
+pub fn record(session_id: &str, project_dir: &str, branch: &str, cost: f64, tokens: u64) {
+    if session_id.is_empty() || cost <= 0.0 {
+        return;
+    }
+    let dir = history_dir();
+    let path = dir.join(format!("{}.jsonl", cache::file_id(session_id)));
+    let last = fs::read_to_string(&path).ok().and_then(|d| {
+        d.lines()
+            .last()
+            .and_then(|l| serde_json::from_str::<Snapshot>(l).ok())
+    });
+    if last
+        .as_ref()
+        .is_some_and(|s| s.branch == branch && s.cost == cost)
+    {
+        return;
+    }
+    // Counted like session_usage: a lower count means a restarted counter
+    let added = match &last {
+        Some(s) if tokens >= s.tokens => tokens - s.tokens,
+        _ => tokens,
+    };
+    let snap = Snapshot {
+        ts: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        // Hashed file names would be moot with the path inside
+        project: if cache::is_private() {
+            cache::private_name(project_dir)
+        } else {
+            project_dir.to_string()
+        },
+        branch: branch.to_string(),
+        cost,
+        tokens,
+    };
+    let _ = fs::create_dir_all(&dir);
+    if let Ok(mut f) = OpenOptions::new().create(true).append(true).open(&path) {
+        let line = serde_json::to_string(&snap).unwrap_or_default();
+        let _ = writeln!(f, "{}", line);
+    }
+    let since = crate::clock::week_start(snap.ts as i64) as u64;
+    cache::update_json(&week_tokens_path(), |prev| {
+        count_week(prev, since, added, || tokens_since(since))
+    });
+}
+
+/// The week count after adding `added` tokens. A new week starts from zero;
+/// a missing count is seeded from the history (`seed`), which already holds
+/// the new snapshot.
+fn count_week(
+    prev: Option<WeekTokens>,
+    since: u64,
+    added: u64,
+    seed: impl FnOnce() -> u64,
+) -> WeekTokens {
+    let tokens = match prev {
+        Some(w) if w.since == since => w.tokens + added,
+        Some(_) => added,
+        None => seed(),
+    };
+    WeekTokens { since, tokens }
+}
+
+/// Tokens used by all sessions since `since`, the start of the current week,
+/// as counted by `record`. Zero until this week's first snapshot.
+pub fn week_tokens(since: u64) -> u64 {
+    fs::read_to_string(week_tokens_path())
+        .ok()
+        .and_then(|d| serde_json::from_str::<WeekTokens>(&d).ok())
+        .filter(|w| w.since == since)
+        .map_or(0, |w| w.tokens)
+}
+
+#[derive(Default)]
+struct Total {
+    cost: f64,
+    sessions: usize,
+}
+
+/// Add one session's snapshots (in order) to the per-key totals.
+fn attribute(
+    snaps: &[Snapshot],
+    key: impl Fn(&Snapshot) -> String,
+    totals: &mut HashMap<String, Total>,
+) {
+    let mut prev = 0.0;
+    let mut seen = Vec::new();
+    for s in snaps {
+        let k = key(s);
+        let t = totals.entry(k.clone()).or_default();
+        // A lower cost means the session restarted its counter
+        t.cost += if s.cost >= prev {
+            s.cost - prev
+        } else {
+            s.cost
+        };
+        prev = s.cost;
+        if !seen.contains(&k) {
+            t.sessions += 1;
+            seen.push(k);
+        }
+    }
+}
+
+/// Each session's id and snapshots, oldest first.
+fn sessions() -> Vec<(String, Vec<Snapshot>)> {
+    let Ok(entries) = fs::read_dir(history_dir()) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|e| {
+            let id = e.path().file_stem()?.to_string_lossy().to_string();
+            let data = fs::read_to_string(e.path()).ok()?;
+            let snaps = data
+                .lines()
+                .filter_map(|l| serde_json::from_str(l).ok())
+                .collect();
+            Some((id, snaps))
+        })
+        .collect()
+}
+
+/// Cost and tokens added after `since` (unix seconds) by sessions in `root`.
+pub fn usage_since(root: &Path, since: u64) -> (f64, u64) {
+    sessions().iter().fold((0.0, 0), |acc, (_, snaps)| {
+        let (cost, tokens) = session_usage(snaps, |s| {
+            s.ts > since && Path::new(&s.project).starts_with(root)
+        });
+        (acc.0 + cost, acc.1 + tokens)
+    })
+}
+
+/// Tokens used by all sessions since `since` (unix seconds).
+pub fn tokens_since(since: u64) -> u64 {
+    usage_between(since, u64::MAX)
+        .iter()
+        .map(|u| u.tokens)
+        .sum()
+}
+
+/// One session's usage within a time window.
+pub struct SessionUsage {
+    pub project: String,
+    pub branch: String,
+    pub cost: f64,
+    pub tokens: u64,
+    /// First and last snapshot in the window (unix seconds)
+    pub first: u64,
+    pub last: u64,
+}
+
+/// Usage per session with snapshots in `from..to` (unix seconds).
+pub fn usage_between(from: u64, to: u64) -> Vec<SessionUsage> {
+    let in_window = |s: &Snapshot| s.ts >= from && s.ts < to;
+    sessions()
+        .into_iter()
+        .filter_map(|(_, snaps)| {
+            let window: Vec<&Snapshot> = snaps.iter().filter(|s| in_window(s)).collect();
+            let (first, last) = (window.first()?, window.last()?);
+            let (cost, tokens) = session_usage(&snaps, in_window);
+            Some(SessionUsage {
+                project: last.project.clone(),
+                branch: last.branch.clone(),
+                cost,
+                tokens,
+                first: first.ts,
+                last: last.ts,
+            })
+        })
+        .collect()
+}
+
+/// Cost and tokens of the snapshots `keep` selects, each counted as the
+/// increase over the session's previous snapshot.
+fn session_usage(snaps: &[Snapshot], keep: impl Fn(&Snapshot) -> bool) -> (f64, u64) {
+    let (mut cost, mut tokens) = (0.0, 0);
+    let (mut prev_cost, mut prev_tokens) = (0.0, 0);
+    for s in snaps {
+        if keep(s) {
+            cost += if s.cost >= prev_cost {
+                s.cost - prev_cost
+            } else {
+                s.cost
+            };
+            tokens += s.tokens.checked_sub(prev_tokens).unwrap_or(s.tokens);
+        }
+        prev_cost = s.cost;
+        prev_tokens = s.tokens;
+    }
+    (cost, tokens)
+}
+
+/// `statusline stats [--by-branch]`: cost per project, or per git branch.
+pub fn run(cfg: &Config) -> Result<String, String> {
+    let by_branch = cfg.command_args.iter().any(|a| a == "--by-branch");
+    let mut totals: HashMap<String, Total> = HashMap::new();
+    for (_, snaps) in sessions() {
+        attribute(
+            &snaps,
+            |s| match (by_branch, s.branch.is_empty()) {
+                (true, true) => "(no branch)".to_string(),
+                (true, false) => s.branch.clone(),
+                (false, _) => s.project.clone(),
+            },
+            &mut totals,
+        );
+    }
+    if totals.is_empty() {
+        return Err("no history yet (set STATUSLINE_HISTORY=true)".into());
+    }
+    Ok(table(totals))
+}
+
+fn table(totals: HashMap<String, Total>) -> String {
+    let mut rows: Vec<(String, Total)> = totals.into_iter().collect();
+    rows.sort_by(|a, b| b.1.cost.total_cmp(&a.1.cost).then_with(|| a.0.cmp(&b.0)));
+    rows.iter()
+        .map(|(k, t)| {
+            format!(
+                "{:>8}  {:>3} session{}  {}",
+                format::fmt_cost(t.cost),
+                t.sessions,
+                if t.sessions == 1 { " " } else { "s" },
+                k
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snap(branch: &str, cost: f64) -> Snapshot {
+        Snapshot {
+            ts: 0,
+            project: "/p".into(),
+            branch: branch.into(),
+            cost,
+            tokens: (cost * 1000.0) as u64,
+        }
+    }
+
+    #[test]
+    fn cost_increase_goes_to_current_branch() {
+        let mut totals = HashMap::new();
+        let snaps = [snap("main", 1.0), snap("feat", 3.0), snap("main", 3.5)];
+        attribute(&snaps, |s| s.branch.clone(), &mut totals);
+        attribute(&[snap("feat", 2.0)], |s| s.branch.clone(), &mut totals);
+        assert_eq!(totals["main"].cost, 1.5);
+        assert_eq!(totals["main"].sessions, 1);
+        assert_eq!(totals["feat"].cost, 4.0);
+        assert_eq!(totals["feat"].sessions, 2);
+        assert_eq!(
+            table(totals),
+            "    $4.0    2 sessions  feat\n    $1.5    1 session   main"
+        );
+    }
+
+    #[test]
+    fn usage_counts_increase_over_previous_snapshot() {
+        let mut snaps = vec![snap("main", 1.0), snap("main", 3.0), snap("main", 3.5)];
+        snaps[0].ts = 10;
+        snaps[1].ts = 20;
+        snaps[2].ts = 30;
+        let (cost, tokens) = session_usage(&snaps, |s| s.ts > 10);
+        assert_eq!(cost, 2.5);
+        assert_eq!(tokens, 2500);
+    }
+
+    #[test]
+    fn week_count_adds_within_the_week_and_resets_after() {
+        let seeded = count_week(None, 100, 5, || 42);
+        assert_eq!(seeded.tokens, 42);
+        let same = count_week(Some(seeded), 100, 5, || unreachable!());
+        assert_eq!((same.since, same.tokens), (100, 47));
+        let next = count_week(Some(same), 200, 5, || unreachable!());
+        assert_eq!((next.since, next.tokens), (200, 5));
+    }
+}
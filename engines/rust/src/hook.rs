@@ -0,0 +1,121 @@
+//! Post-processing hook (STATUSLINE_POST_HOOK=/path/to/script).
+//!
+//! The script gets `{"output": "<rendered ANSI>", "segments": {...}}` on stdin
+//! and its stdout replaces the output. It runs without a shell, and on
+//! failure, empty output or timeout the original output is kept.
+
+use crate::debuglog;
+use crate::rules::Values;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The hook runs on the render path, so it gets a tight budget.
+const TIMEOUT: Duration = Duration::from_millis(200);
+const POLL: Duration = Duration::from_millis(2);
+
+pub fn run(script: &str, output: &str, values: &Values) -> Option<String> {
+    let input = serde_json::json!({ "output": output, "segments": values }).to_string();
+    let mut child = Command::new(script)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| debuglog::write(&format!("post hook {}: {}", script, e)))
+        .ok()?;
+
+    // Feed stdin and drain stdout on threads so neither side can block. The
+    // reader is not joined: a process the script left in the background can
+    // hold stdout open long after the script exits.
+    let mut stdin = child.stdin.take()?;
+    thread::spawn(move || {
+        let _ = stdin.write_all(input.as_bytes());
+    });
+    let mut stdout = child.stdout.take()?;
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stdout.read_to_end(&mut buf);
+        let _ = tx.send(buf);
+    });
+
+    let deadline = Instant::now() + TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(POLL),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                debuglog::write(&format!("post hook {}: timed out", script));
+                return None;
+            }
+        }
+    };
+    let Ok(out) = rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) else {
+        debuglog::write(&format!("post hook {}: output not closed in time", script));
+        return None;
+    };
+    if !status.success() || out.is_empty() {
+        debuglog::write(&format!(
+            "post hook {}: {}, {} bytes",
+            script,
+            status,
+            out.len()
+        ));
+        return None;
+    }
+    Some(String::from_utf8_lossy(&out).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+
+    fn script(name: &str, body: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("statusline-hook-{}", std::process::id()));
+        let _ = fs::create_dir_all(&dir);
+        let path = dir.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn output_replaces_the_line() {
+        let path = script(
+            "upper",
+            r#"sed 's/.*"output":"\([^"]*\)".*/\1/' | tr a-z A-Z"#,
+        );
+        let out = run(path.to_str().unwrap(), "opus", &Values::default());
+        assert_eq!(out.as_deref().map(str::trim_end), Some("OPUS"));
+    }
+
+    #[test]
+    fn background_processes_do_not_hold_up_the_render() {
+        let path = script("detach", "echo replaced\nsleep 10 &");
+        let start = Instant::now();
+        assert_eq!(
+            run(path.to_str().unwrap(), "opus", &Values::default()),
+            None
+        );
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn slow_scripts_are_killed() {
+        let path = script("slow", "sleep 10");
+        let start = Instant::now();
+        assert_eq!(
+            run(path.to_str().unwrap(), "opus", &Values::default()),
+            None
+        );
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+}
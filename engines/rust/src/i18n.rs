@@ -0,0 +1,150 @@
+//! Message catalog for the few textual labels. STATUSLINE_LANG picks a
+//! translation (default English, so output matches the other engines), and
+//! STATUSLINE_LABELS overrides single strings: `in=↓,out=↑`. Labels with
+//! `{}` are templates for a value.
+
+/// Label keys, in catalog column order.
+const KEYS: [&str; 12] = [
+    "in", "out", "stash", "tok/s", "break", "since", "started", "files", "calendar", "uncached",
+    "periods", "today",
+];
+
+const CATALOG: &[(&str, [&str; 12])] = &[
+    (
+        "en",
+        [
+            "in",
+            "out",
+            "stash",
+            "tok/s",
+            "break",
+            "since {}",
+            "started {} ago",
+            "files",
+            "d/wk/mo",
+            "uncached",
+            "day/wk/mo",
+            "today",
+        ],
+    ),
+    (
+        "de",
+        [
+            "ein",
+            "aus",
+            "Stash",
+            "Tok/s",
+            "Pause",
+            "seit {}",
+            "gestartet vor {}",
+            "Dateien",
+            "T/Wo/Mo",
+            "ohne Cache",
+            "Tag/Wo/Mon",
+            "heute",
+        ],
+    ),
+    (
+        "fr",
+        [
+            "entrée",
+            "sortie",
+            "remise",
+            "tok/s",
+            "pause",
+            "depuis {}",
+            "lancé il y a {}",
+            "fichiers",
+            "j/sem/mois",
+            "sans cache",
+            "jour/sem/mois",
+            "aujourd'hui",
+        ],
+    ),
+    (
+        "es",
+        [
+            "entrada",
+            "salida",
+            "reserva",
+            "tok/s",
+            "descanso",
+            "desde {}",
+            "iniciado hace {}",
+            "archivos",
+            "d/sem/mes",
+            "sin caché",
+            "día/sem/mes",
+            "hoy",
+        ],
+    ),
+    (
+        "ru",
+        [
+            "вход",
+            "выход",
+            "stash",
+            "ток/с",
+            "перерыв",
+            "с {}",
+            "начато {} назад",
+            "файлов",
+            "д/нед/мес",
+            "без кэша",
+            "день/нед/мес",
+            "сегодня",
+        ],
+    ),
+    (
+        "ja",
+        [
+            "入力",
+            "出力",
+            "退避",
+            "tok/s",
+            "休憩",
+            "{}から",
+            "{}前に開始",
+            "ファイル",
+            "日/週/月",
+            "キャッシュなし",
+            "日/週/月",
+            "今日",
+        ],
+    ),
+];
+
+/// The label for `key` under `lang` (`de`, `de_DE.UTF-8` and `de-AT` all
+/// select German) with `overrides` applied. Unknown keys come back as given.
+pub fn label(lang: &str, overrides: &str, key: &str) -> String {
+    for pair in overrides.split(',') {
+        if let Some((k, v)) = pair.split_once('=') {
+            if k.trim() == key {
+                return v.trim().to_string();
+            }
+        }
+    }
+    let Some(col) = KEYS.iter().position(|k| *k == key) else {
+        return key.to_string();
+    };
+    let lang = lang.split(['_', '-', '.']).next().unwrap_or("");
+    CATALOG
+        .iter()
+        .find(|(l, _)| *l == lang)
+        .unwrap_or(&CATALOG[0])
+        .1[col]
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn label_prefers_override_then_language() {
+        assert_eq!(label("", "", "stash"), "stash");
+        assert_eq!(label("de_DE.UTF-8", "", "in"), "ein");
+        assert_eq!(label("de", "in=\u{2193}", "in"), "\u{2193}");
+        assert_eq!(label("xx", "", "tok/s"), "tok/s");
+    }
+}
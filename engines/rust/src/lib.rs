@@ -0,0 +1,86 @@
+//! The statusline engine. The `statusline` binary (main.rs) is the CLI over
+//! these modules; `embed` renders a session from JSON, exported as a C ABI
+//! by the `ffi` feature and for wasm32-unknown-unknown by `wasm`, where only
+//! the modules a render needs are built.
+
+/// The release version (the repository's VERSION file, see build.rs).
+pub const VERSION: &str = env!("STATUSLINE_VERSION");
+
+pub mod account;
+pub mod background;
+pub mod cache;
+pub mod ci;
+pub mod clock;
+pub mod config;
+pub mod deadline;
+pub mod debuglog;
+pub mod embed;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod format;
+pub mod git;
+#[cfg(feature = "graphics")]
+pub mod graphics;
+pub mod history;
+pub mod i18n;
+pub mod models;
+pub mod pending;
+#[cfg(feature = "plugins")]
+pub mod plugins;
+pub mod pomodoro;
+pub mod quiet;
+pub mod render;
+pub mod rules;
+pub mod session;
+pub mod sources;
+pub mod symbols;
+pub mod trace;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+// Subcommands and output adapters, which need a terminal, processes or the
+// filesystem
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bell;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bundle;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod digest;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod doctor;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod explain;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod export;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod files;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod githook;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod hook;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod memo;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod output;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod parity;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod schema;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod segments;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod serve;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod settings;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod sync;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod telemetry;
+#[cfg(all(feature = "tui", not(target_arch = "wasm32")))]
+pub mod tui;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod update;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod watch;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod wizard;
@@ -1,26 +1,37 @@
-mod background;
-mod cache;
-mod config;
-mod format;
-mod git;
-mod render;
-mod session;
-
+#[cfg(feature = "tui")]
+use statusline::tui;
+use statusline::{
+    background, bell, bundle, cache, ci, clock, config, deadline, debuglog, digest, doctor,
+    explain, export, files, githook, history, hook, memo, models, output, parity, pomodoro, quiet,
+    render, schema, segments, serve, session, settings, sync, telemetry, trace, update, watch,
+    wizard,
+};
 use std::io;
-use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set once the status line reached stdout, so a later panic adds no fallback.
+static RENDERED: AtomicBool = AtomicBool::new(false);
 
 fn main() {
-    // Panic hook: never crash the render cycle
-    std::panic::set_hook(Box::new(|_| {
-        let _ = io::Write::write_all(&mut io::stdout(), b"\n\n");
+    // Panic hook: never crash the render cycle. Details go to the debug log,
+    // never stdout.
+    std::panic::set_hook(Box::new(|info| {
+        let bt = std::backtrace::Backtrace::force_capture();
+        debuglog::write(&format!("panic: {}\n{}", info, bt));
     }));
 
     let result = std::panic::catch_unwind(|| {
         let args: Vec<String> = std::env::args().skip(1).collect();
         let cfg = config::load(&args);
+        trace::init(cfg.trace);
+        models::init(&cfg.model_aliases);
+        cache::set_private(cfg.privacy);
+        if cfg.self_profile {
+            trace::profile();
+        }
 
         if cfg.show_version {
-            let version = env!("CARGO_PKG_VERSION");
+            let version = statusline::VERSION;
             let _ = io::Write::write_all(
                 &mut io::stdout(),
                 format!("statusline {} (rust)\n", version).as_bytes(),
@@ -33,40 +44,316 @@ fn main() {
             return;
         }
 
+        if cfg.describe_segments {
+            let _ = io::Write::write_all(&mut io::stdout(), segments::describe(&cfg).as_bytes());
+            return;
+        }
+
+        match cfg.command.as_str() {
+            "init" => {
+                wizard::run();
+                return;
+            }
+            "install-hook" => {
+                let command = config::arg_value(&cfg.command_args, "--command")
+                    .unwrap_or_else(|| settings::DEFAULT_COMMAND.to_string());
+                report(settings::install(&settings::settings_path(), &command));
+                return;
+            }
+            "uninstall-hook" => {
+                report(settings::uninstall(&settings::settings_path()));
+                return;
+            }
+            "self-update" => {
+                report(update::run(&cfg));
+                return;
+            }
+            "render" => {
+                report(render_snapshot(&cfg));
+                return;
+            }
+            "watch" => {
+                watch::run(&cfg);
+                return;
+            }
+            "serve" => {
+                report(serve::run(&cfg));
+                return;
+            }
+            "telemetry" => {
+                report(telemetry::run(&cfg));
+                return;
+            }
+            "sync" => {
+                report(sync::run(&cfg));
+                return;
+            }
+            "tui" => {
+                #[cfg(feature = "tui")]
+                if let Err(e) = tui::run() {
+                    report(Err(e));
+                }
+                #[cfg(not(feature = "tui"))]
+                report(Err(
+                    "built without the `tui` feature (cargo build --features tui)".into(),
+                ));
+                return;
+            }
+            "config" => {
+                report(schema::run(&cfg.command_args));
+                return;
+            }
+            "doctor" => {
+                let _ = io::Write::write_all(&mut io::stdout(), doctor::run().as_bytes());
+                return;
+            }
+            "debug-bundle" => {
+                report(bundle::run(&cfg));
+                return;
+            }
+            "parity" => {
+                report(parity::run(&cfg));
+                return;
+            }
+            "stats" => {
+                report(history::run(&cfg));
+                return;
+            }
+            "deadline" => {
+                report(deadline::run(&cfg));
+                return;
+            }
+            "files" => {
+                report(files::run());
+                return;
+            }
+            "pomo" => {
+                report(pomodoro::run(&cfg));
+                return;
+            }
+            "hook" => {
+                // prepare-commit-msg runs inside `git commit`: stay quiet on success
+                match githook::run(&cfg) {
+                    Ok(msg) if msg.is_empty() => {}
+                    result => report(result),
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        // Internal mode: write yesterday's digest
+        if cfg.internal_digest {
+            digest::write(&cfg);
+            return;
+        }
+
+        // Internal mode: send yesterday's telemetry report
+        if cfg.internal_telemetry {
+            telemetry::send(&cfg);
+            return;
+        }
+
+        // Internal mode: fetch the CI status of HEAD
+        if cfg.internal_ci {
+            ci::refresh(&cfg);
+            return;
+        }
+
         // Internal mode: refresh model cache
         if cfg.internal_refresh_models {
             background::refresh_model_cache(
                 &cfg.internal_session_id,
                 &cfg.internal_transcript_path,
+                None,
+                None,
             );
+            trace::finish();
             return;
         }
 
-        let sess = session::parse(io::stdin());
+        // Interactive invocation: nothing will arrive on stdin
+        let mode = output::Output::parse(&cfg.output);
+        if io::IsTerminal::is_terminal(&io::stdin()) {
+            // Prompt modes run from PS1 on every prompt: no usage hint there
+            let local = render::render_local(&cfg, mode == output::Output::Ansi);
+            let _ = io::Write::write_all(&mut io::stdout(), output::apply(mode, &local).as_bytes());
+            return;
+        }
+
+        let (raw, mut sess) = {
+            let _span = trace::span("session.parse");
+            let raw = session::read_raw(io::stdin());
+            let sess = session::parse(&raw[..]);
+            (raw, sess)
+        };
+        // Options in the session JSON rank with command-line flags
+        let cfg = if sess.statusline_options.is_empty() {
+            cfg
+        } else {
+            config::load_with(&args, &config::stdin_options(&sess.statusline_options))
+        };
+        cache::set_private(cfg.privacy);
+        // Bedrock, Vertex and gateway sessions often report no cost: estimate it from
+        // the transcript's token usage at list prices
+        let estimate_cost = sess.cost.total_cost_usd == 0.0
+            && models::provider(&sess.model.id) != models::Provider::Anthropic;
+        // Segments that are off cost nothing: no transcript lookup, no model cache
+        let needs_models = cfg.needs_models() || estimate_cost;
+        if needs_models || cfg.awaiting_secs > 0 {
+            sess.transcript_path = cache::locate_transcript(
+                &sess.transcript_path,
+                &cache::transcript_roots(&cfg.transcript_roots),
+            );
+        }
+
+        let session_id = sess.id();
+
+        // Identical inputs: replay the last output and skip all other work
+        let memo_key = (cfg.render_cache && !cfg.self_profile && !cfg.explain)
+            .then(|| memo::key(&raw, &args, &sess, &cfg));
+        if let Some(hit) = memo_key.as_deref().and_then(memo::get) {
+            let _ = io::Write::write_all(&mut io::stdout(), hit.as_bytes());
+            RENDERED.store(true, Ordering::Relaxed);
+            // Still activity for the break reminder, which records it when
+            // it renders
+            if cfg.break_after > 0 {
+                cache::touch_activity(clock::now_unix());
+            }
+            if !cfg.write_to.is_empty() {
+                output::write_to(&cfg.write_to, &hit);
+            }
+            return;
+        }
+
+        // Small transcripts: compute model stats before rendering so a fresh
+        // session shows per-model data on its first render
+        let inline_models = needs_models && background::transcript_is_small(&sess.transcript_path);
+        if inline_models {
+            background::refresh_model_cache_inline(&session_id, &sess.transcript_path);
+        } else if needs_models {
+            background::warm_start(&session_id, &sess.transcript_path, cfg.warm_start_kb);
+        }
+
+        if estimate_cost {
+            if let Some(ms) = cache::read_models(&session_id).filter(|ms| ms.est_cost > 0.0) {
+                sess.cost.total_cost_usd = ms.est_cost;
+                sess.cost.estimated = true;
+            }
+        }
 
         // Render output
-        let output = render::render(&sess, &cfg);
+        let (output, branch) = {
+            let _span = trace::span("render");
+            let (rendered, values) = render::render_values(&sess, &cfg);
+            let rendered = if !cfg.post_hook.is_empty() && background::spawn_allowed(cfg.no_spawn) {
+                let _span = trace::span("post_hook");
+                hook::run(&cfg.post_hook, &rendered, &values).unwrap_or(rendered)
+            } else {
+                rendered
+            };
+            (output::apply(mode, &rendered), values.branch)
+        };
         let _ = io::Write::write_all(&mut io::stdout(), output.as_bytes());
+        RENDERED.store(true, Ordering::Relaxed);
+        // Dry run: explain the segments, skip history, bells and jobs
+        if cfg.explain {
+            let report = explain::report(&sess, &cfg, &args);
+            let _ = io::Write::write_all(&mut io::stdout(), format!("\n{}", report).as_bytes());
+            return;
+        }
+        if !cfg.write_to.is_empty() {
+            output::write_to(&cfg.write_to, &output);
+        }
+        if let Some(key) = &memo_key {
+            memo::put(key, &output);
+        }
+        cache::write_last_session(&raw);
+        if !cfg.bell.is_empty() {
+            bell::check(
+                &cfg.bell,
+                &session_id,
+                sess.context_window.used_percentage,
+                quiet::active(&cfg.quiet_hours),
+            );
+        }
+        if cfg.history || cfg.digest || cfg.telemetry || cfg.plan_tokens > 0 {
+            history::record(
+                &session_id,
+                &sess.workspace.project_dir,
+                &branch,
+                sess.cost.total_cost_usd,
+                (sess.context_window.total_input_tokens + sess.context_window.total_output_tokens)
+                    as u64,
+            );
+        }
 
         // Fire-and-forget background jobs
-        let session_id = if !sess.transcript_path.is_empty() {
-            let base = Path::new(&sess.transcript_path)
-                .file_name()
-                .map(|f| f.to_string_lossy().to_string())
-                .unwrap_or_default();
-            base.strip_suffix(".jsonl").unwrap_or(&base).to_string()
-        } else {
-            String::new()
-        };
-
-        background::spawn_cumulative_stats(&sess.workspace.project_dir);
-        if !session_id.is_empty() && !sess.transcript_path.is_empty() {
-            background::spawn_model_refresh(&session_id, &sess.transcript_path);
+        if background::spawn_allowed(cfg.no_spawn) {
+            if cfg.needs_cumulative() {
+                background::spawn_cumulative_stats(&sess.workspace.project_dir, &cfg);
+            }
+            if needs_models
+                && !inline_models
+                && !session_id.is_empty()
+                && !sess.transcript_path.is_empty()
+            {
+                background::spawn_model_refresh(&session_id, &sess.transcript_path);
+            }
+            // Quiet hours postpone the digest, and with it the webhook
+            if cfg.digest && !quiet::active(&cfg.quiet_hours) && digest::claim() {
+                background::spawn_digest();
+            }
+            if cfg.telemetry && !cfg.telemetry_url.is_empty() && !cfg.offline && telemetry::claim()
+            {
+                background::spawn_telemetry();
+            }
+        } else if needs_models && !inline_models {
+            // Sandbox-safe: no fork/exec, refresh the model cache inline
+            background::refresh_model_cache_inline(&session_id, &sess.transcript_path);
+        }
+        if cfg.self_profile {
+            let _ = io::Write::write_all(&mut io::stderr(), trace::profile_report().as_bytes());
         }
+        trace::finish();
     });
 
-    if result.is_err() {
-        let _ = io::Write::write_all(&mut io::stdout(), b"\n\n");
+    if result.is_err() && !RENDERED.load(Ordering::Relaxed) {
+        let _ = io::Write::write_all(
+            &mut io::stdout(),
+            "statusline error \u{2013} run doctor\n\n".as_bytes(),
+        );
+    }
+}
+
+/// `statusline render [--format ansi|html|svg] [--theme dark|light] [--input F]`
+fn render_snapshot(cfg: &config::Config) -> Result<String, String> {
+    let raw = match config::arg_value(&cfg.command_args, "--input") {
+        Some(p) => std::fs::read(&p).map_err(|e| format!("cannot read {}: {}", p, e))?,
+        None => session::read_raw(io::stdin()),
+    };
+    let sess = session::parse(&raw[..]);
+    let output = render::render(&sess, cfg);
+    let light = config::arg_value(&cfg.command_args, "--theme").as_deref() == Some("light");
+    match config::arg_value(&cfg.command_args, "--format").as_deref() {
+        None | Some("ansi") => Ok(output.trim_end_matches('\n').to_string()),
+        Some("html") => Ok(export::to_html(&output, light).trim_end().to_string()),
+        Some("svg") => Ok(export::to_svg(&output, light).trim_end().to_string()),
+        Some(other) => Err(format!("unknown format: {} (ansi, html, svg)", other)),
+    }
+}
+
+/// Print a subcommand result; errors go to stderr with a non-zero exit.
+fn report(result: Result<String, String>) {
+    match result {
+        Ok(msg) => {
+            let _ = io::Write::write_all(&mut io::stdout(), format!("{}\n", msg).as_bytes());
+        }
+        Err(e) => {
+            let _ =
+                io::Write::write_all(&mut io::stderr(), format!("statusline: {}\n", e).as_bytes());
+            std::process::exit(1);
+        }
     }
 }
 
@@ -74,8 +361,31 @@ fn print_help() {
     let _ = io::Write::write_all(
         &mut io::stderr(),
         b"Usage: statusline [OPTIONS]\n\
+          \x20      statusline <COMMAND>\n\
           Reads JSON from stdin, outputs formatted status bar.\n\
           \n\
+          Commands:\n\
+          \x20 init             Interactive setup (writes ~/.claude/statusline.env)\n\
+          \x20 install-hook     Add statusLine to ~/.claude/settings.json [--command CMD]\n\
+          \x20 uninstall-hook   Remove statusLine from ~/.claude/settings.json\n\
+          \x20 self-update      Install the latest release [--check] [--force]\n\
+          \x20 render           Render stdin as a snapshot [--format ansi|html|svg] [--theme light]\n\
+          \x20 watch            Re-render the latest session periodically [--interval 2s]\n\
+          \x20 serve            JSON of the latest session's segment values [--http 127.0.0.1:7878]\n\
+          \x20 tui              Full-screen session dashboard (feature `tui`)\n\
+          \x20 config schema    Print a JSON Schema for ~/.claude/statusline.env\n\
+          \x20 config explain KEY  Show a key's value per config layer and which one wins\n\
+          \x20 doctor           Check installation health, show recent errors\n\
+          \x20 debug-bundle     Collect a sanitized tarball for bug reports [--input F] [--output F]\n\
+          \x20 parity --input F Diff this engine's output against the Go/Python/Bash engines\n\
+          \x20 stats            Cost per project from STATUSLINE_HISTORY [--by-branch]\n\
+          \x20 sync             Share transcripts across machines: push | pull --remote host:path\n\
+          \x20 files            Files edited in the latest session, relative to its project\n\
+          \x20 deadline         Per-project countdown: set \"YYYY-MM-DD HH:MM\" | clear | show\n\
+          \x20 pomo             Pomodoro timer: start [--work 25] [--break 5] | stop | status\n\
+          \x20 telemetry preview  Show the opt-in daily report that STATUSLINE_TELEMETRY sends\n\
+          \x20 hook             Git AI-Cost commit trailer: install [--force] | uninstall\n\
+          \n\
           Options:\n\
           \x20 --no-model       Hide model name\n\
           \x20 --no-model-bars  Hide model mix bars\n\
@@ -89,6 +399,14 @@ fn print_help() {
           \x20 --no-speed       Hide throughput (tok/s)\n\
           \x20 --no-cumulative  Hide cumulative costs\n\
           \x20 --no-color       Disable ANSI colors\n\
+          \x20 --offline        Never touch the network (STATUSLINE_OFFLINE)\n\
+          \x20 --no-spawn       No background processes (STATUSLINE_NO_SPAWN)\n\
+          \x20 --output MODE    ansi, zsh-prompt, bash-prompt, zellij, wezterm, lualine, plain-verbose (STATUSLINE_OUTPUT)\n\
+          \x20 --layout NAME    default or classic (two lines), swapped (line 2 first), dense (one line), minimal (one short line) (STATUSLINE_LAYOUT)\n\
+          \x20 --write-to PATH  Also write the output to a file or FIFO (STATUSLINE_WRITE_TO)\n\
+          \x20 --self-profile   Print time spent per segment/stage to stderr\n\
+          \x20 --explain        Render, then list why each segment is shown or hidden\n\
+          \x20 --describe-segments  Print segment metadata as JSON\n\
           \x20 --version        Show version\n\
           \x20 --help           Show this help\n\
           \n\
@@ -0,0 +1,160 @@
+//! Render memoization (STATUSLINE_RENDER_CACHE=true).
+//!
+//! Claude Code re-renders often with identical data. The last output is kept
+//! in `<cache dir>/render-cache` next to a hash of everything it was built
+//! from: the session JSON, args and environment, the config file, a cheap git
+//! fingerprint (HEAD, refs, index) and the mtimes of the data caches. Worktree
+//! edits that don't touch the index only show up once one of those changes.
+
+use crate::cache;
+use crate::config::{self, Config};
+use crate::session::Session;
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+fn path() -> PathBuf {
+    cache::cache_dir().join("render-cache")
+}
+
+/// Hash of all render inputs.
+pub fn key(raw: &[u8], args: &[String], sess: &Session, cfg: &Config) -> String {
+    let mut h = DefaultHasher::new();
+    crate::VERSION.hash(&mut h);
+    raw.hash(&mut h);
+    args.hash(&mut h);
+    let mut vars: Vec<(String, String)> = env::vars()
+        .filter(|(k, _)| {
+            k.starts_with("STATUSLINE_")
+                || matches!(
+                    k.as_str(),
+                    "NO_COLOR"
+                        | "TERM"
+                        | "TERM_PROGRAM"
+                        | "KITTY_WINDOW_ID"
+                        | "TMUX"
+                        | "CLAUDE_CONFIG_DIR"
+                        | "CLAUDE_CODE_USE_BEDROCK"
+                        | "CLAUDE_CODE_USE_VERTEX"
+                        | "ANTHROPIC_API_KEY"
+                )
+        })
+        .collect();
+    vars.sort();
+    vars.hash(&mut h);
+    // Resolved file values, so edits to included files count too
+    let mut file_vals: Vec<(String, String)> = config::file_values().into_iter().collect();
+    file_vals.sort();
+    file_vals.hash(&mut h);
+    if let Ok(cwd) = env::current_dir() {
+        cwd.hash(&mut h);
+        if cfg.show_git {
+            git_fingerprint(&cwd, &mut h);
+        }
+    }
+    for path in [
+        cache::models_path(&sess.id()),
+        cache::cumulative_path(&sess.workspace.project_dir),
+        cache::cache_dir().join("all.json"),
+    ] {
+        mtime(&path).hash(&mut h);
+    }
+    if cfg.account || !cfg.account_expect.is_empty() {
+        mtime(&crate::account::claude_json_path()).hash(&mut h);
+    }
+    if cfg.ci {
+        mtime(&crate::ci::path()).hash(&mut h);
+    }
+    let deadline = mtime(&crate::deadline::path(&sess.workspace.project_dir));
+    deadline.hash(&mut h);
+    // Staleness markers, the break reminder, quiet hours, the session start,
+    // the cost projection, plan pace, commits today, the stash age, the
+    // deadline countdown and CI refreshes depend on the clock
+    if cfg.stale_secs > 0
+        || cfg.break_after > 0
+        || !cfg.quiet_hours.is_empty()
+        || matches!(cfg.session_start.as_str(), "clock" | "ago")
+        || !cfg.workday_end.is_empty()
+        || cfg.plan_tokens > 0
+        || cfg.commits_today
+        || cfg.stash_detail == "age"
+        || cfg.ci
+        || deadline.is_some()
+    {
+        (crate::clock::now_unix() / 60).hash(&mut h);
+    }
+    // A running pomodoro counts down by the second, and pending tool calls
+    // become overdue by the second
+    if crate::pomodoro::path().exists() || cfg.awaiting_secs > 0 {
+        crate::clock::now_unix().hash(&mut h);
+    }
+    format!("{:016x}", h.finish())
+}
+
+/// Cached output for `key`, if the last render had the same inputs.
+pub fn get(key: &str) -> Option<String> {
+    let data = fs::read_to_string(path()).ok()?;
+    let (k, output) = data.split_once('\n')?;
+    (k == key).then(|| output.to_string())
+}
+
+pub fn put(key: &str, output: &str) {
+    let path = path();
+    let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+    let _ = fs::create_dir_all(cache::cache_dir());
+    if fs::write(&tmp, format!("{}\n{}", key, output)).is_ok() {
+        let _ = fs::rename(&tmp, &path);
+    }
+}
+
+fn mtime(path: &Path) -> Option<(u64, u32, u64)> {
+    let m = fs::metadata(path).ok()?;
+    let t = m.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((t.as_secs(), t.subsec_nanos(), m.len()))
+}
+
+/// HEAD, the branch ref, packed refs, stash, fetch state and index stats,
+/// read straight from the git dir (no gix, no object access).
+fn git_fingerprint(cwd: &Path, h: &mut DefaultHasher) {
+    let Some(git_dir) = find_git_dir(cwd) else {
+        return;
+    };
+    let common = fs::read_to_string(git_dir.join("commondir"))
+        .map(|c| git_dir.join(c.trim()))
+        .unwrap_or_else(|_| git_dir.clone());
+    let head = fs::read_to_string(git_dir.join("HEAD")).unwrap_or_default();
+    head.hash(h);
+    if let Some(r) = head.trim().strip_prefix("ref: ") {
+        fs::read_to_string(common.join(r))
+            .unwrap_or_default()
+            .hash(h);
+    }
+    mtime(&git_dir.join("index")).hash(h);
+    for name in [
+        "packed-refs",
+        "refs/stash",
+        "logs/refs/stash",
+        "FETCH_HEAD",
+        "config",
+    ] {
+        mtime(&common.join(name)).hash(h);
+    }
+}
+
+fn find_git_dir(start: &Path) -> Option<PathBuf> {
+    for dir in start.ancestors() {
+        let dot_git = dir.join(".git");
+        if dot_git.is_dir() {
+            return Some(dot_git);
+        }
+        // Linked worktree: `.git` is a file pointing at the real git dir
+        if let Ok(content) = fs::read_to_string(&dot_git) {
+            let target = content.trim().strip_prefix("gitdir: ")?;
+            return Some(dir.join(target));
+        }
+    }
+    None
+}
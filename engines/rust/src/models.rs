@@ -0,0 +1,195 @@
+//! Model ids: provider detection, normalization to Anthropic API ids, family
+//! bucketing and list prices for cost estimates.
+//!
+//! Bedrock ids look like `anthropic.claude-sonnet-4-20250514-v1:0` (with an
+//! optional `us.`/`eu.`/`apac.` cross-region prefix), Vertex ids like
+//! `claude-sonnet-4@20250514`. Both normalize to `claude-sonnet-4-20250514`.
+//! Gateway ids (`anthropic/claude-sonnet-4`) lose their route prefix, and
+//! STATUSLINE_MODEL_ALIASES maps any other name to a Claude id.
+
+use std::sync::OnceLock;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Provider {
+    Anthropic,
+    Bedrock,
+    Vertex,
+    /// OpenRouter, LiteLLM and other proxies, or an id mapped by an alias
+    Gateway,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Family {
+    Opus,
+    Sonnet,
+    Haiku,
+}
+
+/// USD per token: input, output, cache read, cache write.
+pub struct Price {
+    pub input: f64,
+    pub output: f64,
+    pub cache_read: f64,
+    pub cache_write: f64,
+}
+
+/// Same table as cumulative-stats.sh.
+const OPUS: Price = Price {
+    input: 15e-6,
+    output: 75e-6,
+    cache_read: 1.875e-6,
+    cache_write: 18.75e-6,
+};
+const SONNET: Price = Price {
+    input: 3e-6,
+    output: 15e-6,
+    cache_read: 0.3e-6,
+    cache_write: 3.75e-6,
+};
+const HAIKU: Price = Price {
+    input: 0.8e-6,
+    output: 4e-6,
+    cache_read: 0.08e-6,
+    cache_write: 1e-6,
+};
+
+/// STATUSLINE_MODEL_ALIASES, parsed once at startup by `init`.
+static ALIASES: OnceLock<Vec<(String, String)>> = OnceLock::new();
+
+/// Parse `from=to` pairs separated by commas. A `from` ending in `*` matches
+/// by prefix, e.g. `corp-opus*=claude-opus-4`.
+pub fn init(spec: &str) {
+    let aliases = spec
+        .split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(from, to)| (from.trim().to_string(), to.trim().to_string()))
+        .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+        .collect();
+    let _ = ALIASES.set(aliases);
+}
+
+fn alias(id: &str) -> Option<&'static str> {
+    ALIASES.get()?.iter().find_map(|(from, to)| {
+        let hit = match from.strip_suffix('*') {
+            Some(prefix) => id.starts_with(prefix),
+            None => id == from,
+        };
+        hit.then_some(to.as_str())
+    })
+}
+
+/// Gateway ids (OpenRouter, LiteLLM) prefix the model with a route:
+/// `anthropic/claude-sonnet-4`, `bedrock/anthropic.claude-...`.
+fn strip_route(id: &str) -> &str {
+    match id.rsplit_once('/') {
+        Some((_, name)) if name.contains("claude-") => name,
+        _ => id,
+    }
+}
+
+pub fn provider(id: &str) -> Provider {
+    if alias(id).is_some() || id.contains('/') {
+        Provider::Gateway
+    } else if id.contains("anthropic.claude-") {
+        Provider::Bedrock
+    } else if id.starts_with("claude-") && id.contains('@') {
+        Provider::Vertex
+    } else {
+        Provider::Anthropic
+    }
+}
+
+/// Anthropic API form of a model id; ids that are not Claude models on a
+/// known provider are returned unchanged.
+pub fn normalize(id: &str) -> String {
+    let name = alias(id).unwrap_or_else(|| strip_route(id));
+    if name.contains("anthropic.claude-") {
+        let name = &name[name.find("claude-").unwrap_or(0)..];
+        // Drop the `-v1:0` version suffix
+        let name = name.split(':').next().unwrap_or(name);
+        match name.rfind("-v") {
+            Some(i) if name[i + 2..].chars().all(|c| c.is_ascii_digit()) => name[..i].to_string(),
+            _ => name.to_string(),
+        }
+    } else if name.starts_with("claude-") {
+        name.replacen('@', "-", 1)
+    } else {
+        name.to_string()
+    }
+}
+
+pub fn family(id: &str) -> Option<Family> {
+    let name = id.to_lowercase();
+    if name.contains("opus") {
+        Some(Family::Opus)
+    } else if name.contains("sonnet") {
+        Some(Family::Sonnet)
+    } else if name.contains("haiku") {
+        Some(Family::Haiku)
+    } else {
+        None
+    }
+}
+
+/// List price for a model; unknown models are priced as Sonnet, like the
+/// bash cumulative stats.
+pub fn price(id: &str) -> &'static Price {
+    match family(id) {
+        Some(Family::Opus) => &OPUS,
+        Some(Family::Haiku) => &HAIKU,
+        _ => &SONNET,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_provider_ids() {
+        assert_eq!(
+            normalize("anthropic.claude-3-5-sonnet-20240620-v1:0"),
+            "claude-3-5-sonnet-20240620"
+        );
+        assert_eq!(
+            normalize("us.anthropic.claude-opus-4-20250514-v1:0"),
+            "claude-opus-4-20250514"
+        );
+        assert_eq!(
+            normalize("claude-sonnet-4@20250514"),
+            "claude-sonnet-4-20250514"
+        );
+        assert_eq!(
+            normalize("claude-3-5-haiku@20241022"),
+            "claude-3-5-haiku-20241022"
+        );
+        assert_eq!(normalize("claude-opus-4-6"), "claude-opus-4-6");
+        assert_eq!(provider("claude-opus-4-6"), Provider::Anthropic);
+        assert_eq!(
+            family("anthropic.claude-3-haiku-20240307-v1:0"),
+            Some(Family::Haiku)
+        );
+    }
+
+    #[test]
+    fn gateway_ids_and_aliases() {
+        init("corp-fast=claude-3-5-haiku, corp-opus*=claude-opus-4");
+        assert_eq!(normalize("anthropic/claude-sonnet-4"), "claude-sonnet-4");
+        assert_eq!(
+            normalize("bedrock/us.anthropic.claude-opus-4-20250514-v1:0"),
+            "claude-opus-4-20250514"
+        );
+        assert_eq!(
+            normalize("vertex_ai/claude-sonnet-4@20250514"),
+            "claude-sonnet-4-20250514"
+        );
+        assert_eq!(
+            provider("openrouter/anthropic/claude-3.7-sonnet"),
+            Provider::Gateway
+        );
+        assert_eq!(normalize("corp-fast"), "claude-3-5-haiku");
+        assert_eq!(normalize("corp-opus-2025"), "claude-opus-4");
+        assert_eq!(provider("corp-fast"), Provider::Gateway);
+        assert_eq!(normalize("gpt-4o"), "gpt-4o");
+    }
+}
@@ -0,0 +1,320 @@
+//! Output adapters (`--output`, STATUSLINE_OUTPUT): how the rendered ANSI
+//! lines are emitted for consumers other than Claude Code.
+
+use crate::export::parse_ansi;
+use rustix::fs::OFlags;
+use serde_json::{json, Value};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+use std::path::Path;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Output {
+    Ansi,
+    ZshPrompt,
+    BashPrompt,
+    Zellij,
+    Wezterm,
+    Lualine,
+    /// Wordy text for screen readers, produced by the renderer itself
+    PlainVerbose,
+}
+
+impl Output {
+    /// Unknown names fall back to plain ANSI so the render path never fails.
+    pub fn parse(name: &str) -> Output {
+        match name {
+            "zsh-prompt" => Output::ZshPrompt,
+            "bash-prompt" => Output::BashPrompt,
+            "zellij" => Output::Zellij,
+            "wezterm" => Output::Wezterm,
+            "lualine" => Output::Lualine,
+            "plain-verbose" => Output::PlainVerbose,
+            _ => Output::Ansi,
+        }
+    }
+}
+
+/// Adapt rendered output for the selected consumer.
+pub fn apply(out: Output, rendered: &str) -> String {
+    match out {
+        Output::Ansi => rendered.to_string(),
+        // %{...%} marks zero-width text; a literal % must be doubled
+        Output::ZshPrompt => prompt(rendered, ("%{", "%}"), |c| match c {
+            '%' => Some("%%"),
+            _ => None,
+        }),
+        // \[...\] marks zero-width text; PS1 is decoded and then expanded, so
+        // \, $ and ` need a backslash that survives decoding (\\)
+        Output::BashPrompt => prompt(rendered, ("\\[", "\\]"), |c| match c {
+            '\\' => Some("\\\\\\\\"),
+            '$' => Some("\\\\$"),
+            '`' => Some("\\\\`"),
+            _ => None,
+        }),
+        Output::Zellij => zellij(rendered),
+        Output::Wezterm => wezterm(rendered),
+        Output::Lualine => lualine(rendered),
+        // Already plain words; drop any color a local render added
+        Output::PlainVerbose => rendered
+            .lines()
+            .map(|line| {
+                parse_ansi(line)
+                    .into_iter()
+                    .map(|(_, t)| t)
+                    .collect::<String>()
+                    + "\n"
+            })
+            .collect(),
+    }
+}
+
+/// Copy the output to a file sink (`--write-to`, STATUSLINE_WRITE_TO) so
+/// pollers can read it instead of running the binary. Regular files are
+/// replaced atomically; a FIFO gets one non-blocking write and is skipped
+/// when nobody is reading.
+pub fn write_to(path: &str, content: &str) {
+    let path = Path::new(path);
+    if fs::metadata(path).is_ok_and(|m| m.file_type().is_fifo()) {
+        if let Ok(mut f) = OpenOptions::new()
+            .write(true)
+            .custom_flags(OFlags::NONBLOCK.bits() as i32)
+            .open(path)
+        {
+            let _ = f.write_all(content.as_bytes());
+        }
+        return;
+    }
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let tmp = path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()));
+    if fs::write(&tmp, content).is_ok() && fs::rename(&tmp, path).is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+}
+
+/// zjstatus format string (`#[fg=N]text`), both lines joined into one bar.
+fn zellij(rendered: &str) -> String {
+    let lines: Vec<String> = rendered
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(|line| {
+            parse_ansi(line)
+                .into_iter()
+                .map(|(class, text)| {
+                    let style = match class {
+                        "dim" => "#[dim]",
+                        "red" => "#[fg=1]",
+                        "green" => "#[fg=2]",
+                        "yellow" => "#[fg=3]",
+                        "magenta" => "#[fg=5]",
+                        "cyan" => "#[fg=6]",
+                        _ => "#[default]",
+                    };
+                    format!("{}{}", style, text)
+                })
+                .collect::<String>()
+        })
+        .collect();
+    format!(
+        "{}#[default]\n",
+        lines.join("#[default] #[dim]\u{2502}#[default] ")
+    )
+}
+
+/// JSON for WezTerm `update-status` hooks: per line, the plain text and a
+/// `format` list that can be passed straight to `wezterm.format`.
+fn wezterm(rendered: &str) -> String {
+    let lines: Vec<Value> = rendered
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(|line| {
+            let runs = parse_ansi(line);
+            let text: String = runs.iter().map(|(_, t)| t.as_str()).collect();
+            let mut format = Vec::new();
+            for (class, t) in runs {
+                let item = match class {
+                    "dim" => json!({"Attribute": {"Intensity": "Half"}}),
+                    "red" => json!({"Foreground": {"AnsiColor": "Maroon"}}),
+                    "green" => json!({"Foreground": {"AnsiColor": "Green"}}),
+                    "yellow" => json!({"Foreground": {"AnsiColor": "Olive"}}),
+                    "magenta" => json!({"Foreground": {"AnsiColor": "Purple"}}),
+                    "cyan" => json!({"Foreground": {"AnsiColor": "Teal"}}),
+                    _ => json!("ResetAttributes"),
+                };
+                format.push(item);
+                format.push(json!({ "Text": t }));
+            }
+            format.push(json!("ResetAttributes"));
+            json!({ "text": text, "format": format })
+        })
+        .collect();
+    format!("{}\n", json!({ "lines": lines }))
+}
+
+/// Highlight groups for `--output lualine`, linked to standard Neovim groups
+/// so they follow the colorscheme.
+const LUALINE_GROUPS: &[(&str, &str, &str)] = &[
+    ("dim", "StatuslineClaudeDim", "Comment"),
+    ("red", "StatuslineClaudeRed", "DiagnosticError"),
+    ("green", "StatuslineClaudeGreen", "DiagnosticOk"),
+    ("yellow", "StatuslineClaudeYellow", "DiagnosticWarn"),
+    ("magenta", "StatuslineClaudeMagenta", "Constant"),
+    ("cyan", "StatuslineClaudeCyan", "Function"),
+];
+
+/// JSON for a Neovim statusline component: both lines joined into one plain
+/// `text`, `highlights` as byte ranges of it (`start` inclusive, `end`
+/// exclusive, as `nvim_buf_add_highlight` counts), and `groups` to pass to
+/// `nvim_set_hl` for the groups used.
+fn lualine(rendered: &str) -> String {
+    let mut text = String::new();
+    let mut highlights = Vec::new();
+    let mut groups = serde_json::Map::new();
+    let mut push = |class: &str, t: &str, text: &mut String| {
+        let start = text.len();
+        text.push_str(t);
+        if let Some((_, group, link)) = LUALINE_GROUPS.iter().find(|(c, _, _)| *c == class) {
+            highlights.push(json!({"group": group, "start": start, "end": text.len()}));
+            groups.insert(group.to_string(), json!({"link": link, "default": true}));
+        }
+    };
+    for (i, line) in rendered.lines().filter(|l| !l.is_empty()).enumerate() {
+        if i > 0 {
+            text.push(' ');
+            push("dim", "\u{2502}", &mut text);
+            text.push(' ');
+        }
+        for (class, t) in parse_ansi(line) {
+            push(class, &t, &mut text);
+        }
+    }
+    format!(
+        "{}\n",
+        json!({ "text": text, "highlights": highlights, "groups": groups })
+    )
+}
+
+/// Wrap every escape sequence in the shell's zero-width markers, quote
+/// shell-special characters and drop trailing newlines.
+fn prompt(
+    rendered: &str,
+    (open, close): (&str, &str),
+    quote: fn(char) -> Option<&'static str>,
+) -> String {
+    let mut out = String::with_capacity(rendered.len() * 2);
+    let mut chars = rendered.trim_end_matches('\n').chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            match quote(c) {
+                Some(q) => out.push_str(q),
+                None => out.push(c),
+            }
+            continue;
+        }
+        out.push_str(open);
+        out.push(c);
+        match chars.next() {
+            // CSI: parameters up to a final byte in @..~
+            Some('[') => {
+                out.push('[');
+                for c in chars.by_ref() {
+                    out.push(c);
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // APC/DCS (kitty/sixel images), OSC (hyperlinks): up to the ESC \
+            // terminator
+            Some(k @ ('_' | 'P' | ']')) => {
+                out.push(k);
+                while let Some(c) = chars.next() {
+                    out.push(c);
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        out.push('\\');
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            Some(other) => out.push(other),
+            None => {}
+        }
+        out.push_str(close);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zsh_prompt_wraps_and_escapes() {
+        let out = apply(Output::ZshPrompt, "\x1b[32m42%\x1b[0m\n\n");
+        assert_eq!(out, "%{\x1b[32m%}42%%%{\x1b[0m%}");
+    }
+
+    #[test]
+    fn bash_prompt_wraps_and_escapes() {
+        let out = apply(Output::BashPrompt, "\x1b[2m$1.50\x1b[0m\nb\n");
+        assert_eq!(out, "\\[\x1b[2m\\]\\\\$1.50\\[\x1b[0m\\]\nb");
+    }
+
+    #[test]
+    fn zellij_joins_lines() {
+        let out = apply(Output::Zellij, "\x1b[36mOpus\x1b[0m\nin:5\n");
+        assert_eq!(
+            out,
+            "#[fg=6]Opus#[default] #[dim]\u{2502}#[default] #[default]in:5#[default]\n"
+        );
+    }
+
+    #[test]
+    fn wezterm_json_lines() {
+        let out = apply(Output::Wezterm, "\x1b[36mOpus\x1b[0m $1\n\n");
+        let v: Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(v["lines"].as_array().unwrap().len(), 1);
+        assert_eq!(v["lines"][0]["text"], "Opus $1");
+        assert_eq!(
+            v["lines"][0]["format"][0]["Foreground"]["AnsiColor"],
+            "Teal"
+        );
+    }
+
+    #[test]
+    fn lualine_byte_ranges() {
+        let out = apply(
+            Output::Lualine,
+            "\x1b[36mOpus\x1b[0m 5%\n\x1b[2min\x1b[0m\n",
+        );
+        let v: Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(v["text"], "Opus 5% \u{2502} in");
+        let ranges: Vec<(&str, u64, u64)> = v["highlights"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|h| {
+                (
+                    h["group"].as_str().unwrap(),
+                    h["start"].as_u64().unwrap(),
+                    h["end"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            ranges,
+            [
+                ("StatuslineClaudeCyan", 0, 4),
+                ("StatuslineClaudeDim", 8, 11),
+                ("StatuslineClaudeDim", 12, 14)
+            ]
+        );
+        assert_eq!(v["groups"]["StatuslineClaudeCyan"]["link"], "Function");
+        assert!(v["groups"]["StatuslineClaudeRed"].is_null());
+    }
+}
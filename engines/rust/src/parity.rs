@@ -0,0 +1,193 @@
+//! `statusline parity --input fixture.json`: run the sibling Go, Python and
+//! Bash engines on the same input and diff their output against this one.
+//!
+//! Engines are looked up next to this binary, in the Claude config dir (where
+//! install.sh puts them) and in a source checkout (`engines/<name>/`). All
+//! runs get default settings: no STATUSLINE_* variables, an empty HOME and
+//! cache, and git and cumulative segments off, as in tests/test-parity.sh.
+
+use crate::cache;
+use crate::config::{self, Config};
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+struct Engine {
+    name: &'static str,
+    program: PathBuf,
+    /// Interpreter to run `program` with, if it is a script
+    interpreter: Option<&'static str>,
+}
+
+/// Sibling engines that exist on this machine.
+fn siblings() -> Vec<Engine> {
+    let self_dir = env::current_exe()
+        .ok()
+        .and_then(|e| e.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+    let install = cache::claude_dir();
+    // engines/rust/target/release/statusline -> engines/
+    let engines = self_dir.join("..").join("..").join("..");
+    let candidates: [(&str, Option<&str>, Vec<PathBuf>); 3] = [
+        (
+            "go",
+            None,
+            vec![
+                self_dir.join("statusline-go"),
+                install.join("statusline-go"),
+                engines.join("go").join("statusline"),
+            ],
+        ),
+        (
+            "python",
+            Some("python3"),
+            vec![
+                self_dir.join("statusline.py"),
+                install.join("statusline.py"),
+                engines.join("python").join("statusline.py"),
+            ],
+        ),
+        (
+            "bash",
+            Some("bash"),
+            vec![
+                install.join("statusline.sh"),
+                engines.join("bash").join("statusline.sh"),
+            ],
+        ),
+    ];
+    candidates
+        .into_iter()
+        .filter_map(|(name, interpreter, paths)| {
+            let program = paths
+                .into_iter()
+                .filter_map(|p| fs::canonicalize(p).ok())
+                .find(|p| p.is_file() && !is_wrapper(p))?;
+            Some(Engine {
+                name,
+                program,
+                interpreter,
+            })
+        })
+        .collect()
+}
+
+/// install.sh's `statusline.sh` only execs the installed engine.
+fn is_wrapper(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|s| s.lines().count() <= 3 && s.contains("exec "))
+}
+
+/// Run one engine on `input` with default settings.
+fn run_engine(program: &Path, interpreter: Option<&str>, input: &[u8]) -> Result<String, String> {
+    let home = env::temp_dir().join(format!("statusline-parity-{}", std::process::id()));
+    let _ = fs::create_dir_all(home.join(".claude"));
+    let mut cmd = match interpreter {
+        Some(i) => {
+            let mut c = Command::new(i);
+            c.arg(program);
+            c
+        }
+        None => Command::new(program),
+    };
+    for (key, _) in env::vars().filter(|(k, _)| k.starts_with("STATUSLINE_")) {
+        cmd.env_remove(key);
+    }
+    cmd.args(["--no-git", "--no-cumulative"])
+        .env_remove("CLAUDE_CONFIG_DIR")
+        .env("HOME", &home)
+        .env("XDG_CACHE_HOME", home.join("cache"))
+        .env("STATUSLINE_SHOW_GIT", "false")
+        .env("STATUSLINE_SHOW_CUMULATIVE", "false")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("cannot run {}: {}", program.display(), e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(input);
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string());
+    let _ = fs::remove_dir_all(&home);
+    Ok(String::from_utf8_lossy(&output?.stdout).to_string())
+}
+
+/// Line-by-line differences, escaped so color codes are visible.
+fn diff(ours: &str, theirs: &str, name: &str) -> Vec<String> {
+    let (a, b): (Vec<&str>, Vec<&str>) = (ours.lines().collect(), theirs.lines().collect());
+    let width = name.len().max(4);
+    (0..a.len().max(b.len()))
+        .filter(|&i| a.get(i) != b.get(i))
+        .map(|i| {
+            format!(
+                "  line {}\n    {:<w$}  {:?}\n    {:<w$}  {:?}",
+                i + 1,
+                "rust",
+                a.get(i).unwrap_or(&""),
+                name,
+                b.get(i).unwrap_or(&""),
+                w = width
+            )
+        })
+        .collect()
+}
+
+/// `statusline parity --input FILE`
+pub fn run(cfg: &Config) -> Result<String, String> {
+    let input = config::arg_value(&cfg.command_args, "--input")
+        .ok_or("usage: statusline parity --input fixture.json")?;
+    let data = fs::read(&input).map_err(|e| format!("cannot read {}: {}", input, e))?;
+    let engines = siblings();
+    if engines.is_empty() {
+        return Err("no sibling engines found (go, python, bash)".into());
+    }
+    let exe = env::current_exe().map_err(|e| e.to_string())?;
+    let ours = run_engine(&exe, None, &data)?;
+    let mut out = Vec::new();
+    let mut drift = 0;
+    for engine in &engines {
+        let theirs = run_engine(&engine.program, engine.interpreter, &data)?;
+        let lines = diff(&ours, &theirs, engine.name);
+        if lines.is_empty() {
+            out.push(format!(
+                "[ok] {} matches ({})",
+                engine.name,
+                engine.program.display()
+            ));
+        } else {
+            drift += 1;
+            out.push(format!(
+                "[!!] {} differs ({})",
+                engine.name,
+                engine.program.display()
+            ));
+            out.extend(lines);
+        }
+    }
+    let report = out.join("\n");
+    if drift > 0 {
+        Err(format!(
+            "{}\n{} of {} engines differ",
+            report,
+            drift,
+            engines.len()
+        ))
+    } else {
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_lists_changed_lines_only() {
+        assert!(diff("a\nb\n", "a\nb\n", "go").is_empty());
+        let lines = diff("a\nb\n", "a\nc\nd\n", "go");
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("  line 2\n    rust  \"b\"\n    go    \"c\""));
+    }
+}
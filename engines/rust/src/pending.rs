@@ -0,0 +1,85 @@
+//! Tool calls still waiting for their result (STATUSLINE_AWAITING_SECS): a
+//! permission prompt nobody answered, or a tool that runs long. Read from
+//! the transcript tail on the render path.
+
+use crate::cache;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Transcript bytes read from the end; a pending call is near the tail.
+const TAIL_BYTES: u64 = 64 * 1024;
+
+/// The oldest tool call without a result: tool name and unix start time.
+pub fn oldest(transcript_path: &str) -> Option<(String, i64)> {
+    if transcript_path.is_empty() {
+        return None;
+    }
+    oldest_in(&cache::read_tail(Path::new(transcript_path), TAIL_BYTES)?)
+}
+
+fn oldest_in(jsonl: &str) -> Option<(String, i64)> {
+    let mut pending: HashMap<String, (String, i64)> = HashMap::new();
+    for line in jsonl.lines() {
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        let Some(items) = entry.pointer("/message/content").and_then(|c| c.as_array()) else {
+            continue;
+        };
+        let at = entry
+            .get("timestamp")
+            .and_then(|t| t.as_str())
+            .and_then(crate::clock::parse_timestamp);
+        let str_of = |item: &serde_json::Value, key: &str| {
+            item.get(key)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+        for item in items {
+            match item.get("type").and_then(|t| t.as_str()) {
+                Some("tool_use") => {
+                    if let Some(at) = at {
+                        pending.insert(str_of(item, "id"), (str_of(item, "name"), at));
+                    }
+                }
+                Some("tool_result") => {
+                    pending.remove(&str_of(item, "tool_use_id"));
+                }
+                _ => {}
+            }
+        }
+    }
+    pending.into_values().min_by_key(|(_, at)| *at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oldest_unanswered_tool_call() {
+        let use_ = |id: &str, name: &str, ts: &str| {
+            format!(
+                r#"{{"type":"assistant","timestamp":"{}","message":{{"content":[{{"type":"tool_use","id":"{}","name":"{}","input":{{}}}}]}}}}"#,
+                ts, id, name
+            )
+        };
+        let result = |id: &str| {
+            format!(
+                r#"{{"type":"user","message":{{"content":[{{"type":"tool_result","tool_use_id":"{}"}}]}}}}"#,
+                id
+            )
+        };
+        let jsonl = [
+            use_("a", "Read", "2025-06-01T10:00:00.000Z"),
+            result("a"),
+            use_("b", "Bash", "2025-06-01T10:00:05.000Z"),
+            use_("c", "Edit", "2025-06-01T10:00:09.000Z"),
+        ]
+        .join("\n");
+        assert_eq!(oldest_in(&jsonl), Some(("Bash".into(), 1748772005)));
+        let done = format!("{}\n{}\n{}", jsonl, result("b"), result("c"));
+        assert_eq!(oldest_in(&done), None);
+    }
+}
@@ -0,0 +1,139 @@
+//! Script segments (feature `plugins`): every `*.rhai` file in
+//! `~/.claude/statusline.d/` is evaluated per render, in file name order, and
+//! its result string becomes a segment at the end of line 2 (line 1 when
+//! line 2 is off).
+//!
+//! Scripts see the segment values as `s` (`s.cost`, `s.branch`, ...) and can
+//! use `fmt_cost`, `fmt_k`, `fmt_duration`, `color(name, text)` and
+//! `dim(text)`. Errors go to the debug log and the segment is skipped.
+
+use crate::debuglog;
+use crate::format;
+use crate::rules::Values;
+use rhai::{Dynamic, Engine, Map, Scope};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Evaluation limits so a broken script cannot stall the render.
+const MAX_OPERATIONS: u64 = 100_000;
+const MAX_STRING_SIZE: usize = 4096;
+
+fn plugin_dir() -> PathBuf {
+    crate::config::config_path().with_file_name("statusline.d")
+}
+
+pub fn segments(values: &Values) -> Vec<String> {
+    run_dir(&plugin_dir(), values)
+}
+
+/// Results of the scripts in `dir`, in file name order.
+fn run_dir(dir: &Path, values: &Values) -> Vec<String> {
+    let mut files: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|e| e == "rhai"))
+            .collect(),
+        Err(_) => return Vec::new(),
+    };
+    if files.is_empty() {
+        return Vec::new();
+    }
+    files.sort();
+
+    let engine = engine();
+    let s = values_map(values);
+    let mut out = Vec::new();
+    for file in files {
+        let _span = crate::trace::span("plugin");
+        let mut scope = Scope::new();
+        scope.push_constant("s", s.clone());
+        match engine.eval_file_with_scope::<Dynamic>(&mut scope, file.clone()) {
+            Ok(v) => {
+                let text = v.to_string();
+                if !v.is_unit() && !text.is_empty() {
+                    out.push(text);
+                }
+            }
+            Err(e) => debuglog::write(&format!("plugin {}: {}", file.display(), e)),
+        }
+    }
+    out
+}
+
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_string_size(MAX_STRING_SIZE);
+    engine.register_fn("fmt_cost", format::fmt_cost);
+    engine.register_fn("fmt_k", format::fmt_k);
+    engine.register_fn("fmt_duration", format::fmt_duration);
+    engine.register_fn("color", |name: &str, text: &str| {
+        let code = match name {
+            "red" => "31",
+            "green" => "32",
+            "yellow" => "33",
+            "blue" => "34",
+            "magenta" => "35",
+            "cyan" => "36",
+            _ => "0",
+        };
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    });
+    engine.register_fn("dim", |text: &str| format!("\x1b[2m{}\x1b[0m", text));
+    engine
+}
+
+fn values_map(v: &Values) -> Map {
+    let mut m = Map::new();
+    m.insert("model".into(), v.model.clone().into());
+    m.insert("branch".into(), v.branch.clone().into());
+    m.insert("context".into(), v.context.into());
+    m.insert("cost".into(), v.cost.into());
+    m.insert("duration".into(), v.duration.into());
+    m.insert("added".into(), v.added.into());
+    m.insert("removed".into(), v.removed.into());
+    m
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn values() -> Values {
+        Values {
+            branch: "main".into(),
+            cost: 8.42,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn scripts_run_in_name_order_and_failures_are_skipped() {
+        let dir = env::temp_dir().join(format!("statusline-plugins-{}", std::process::id()));
+        let _ = fs::create_dir_all(&dir);
+        for (name, script) in [
+            ("10-cost.rhai", "fmt_cost(s.cost)"),
+            ("20-branch.rhai", r#"color("red", s.branch)"#),
+            ("30-unit.rhai", "let x = 1;"),
+            ("40-empty.rhai", r#""""#),
+            ("50-syntax.rhai", "fmt_cost("),
+            ("60-loop.rhai", "loop {}"),
+            ("70-runtime.rhai", "s.cost.nope()"),
+            ("80-last.rhai", r#"dim("ok")"#),
+            ("notes.txt", r#""not a plugin""#),
+        ] {
+            fs::write(dir.join(name), script).unwrap();
+        }
+        let out = run_dir(&dir, &values());
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(out, ["$8.4", "\x1b[31mmain\x1b[0m", "\x1b[2mok\x1b[0m"]);
+    }
+
+    #[test]
+    fn missing_directory_has_no_segments() {
+        let dir = env::temp_dir().join("statusline-plugins-missing");
+        assert!(run_dir(&dir, &values()).is_empty());
+    }
+}
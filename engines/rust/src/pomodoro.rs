@@ -0,0 +1,123 @@
+//! Pomodoro timer (`statusline pomo start|stop`), kept in
+//! `<cache dir>/pomodoro.json` and rendered as `🍅 17:32`.
+//!
+//! Work and break phases alternate from the start time until stopped, so the
+//! state is just when the timer started and the phase lengths.
+
+use crate::cache;
+use crate::clock;
+use crate::config::{self, Config};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+const WORK_MIN: i64 = 25;
+const BREAK_MIN: i64 = 5;
+
+#[derive(Serialize, Deserialize)]
+struct Timer {
+    started: i64,
+    work_secs: i64,
+    break_secs: i64,
+}
+
+pub enum Phase {
+    Work,
+    Break,
+}
+
+pub fn path() -> PathBuf {
+    cache::cache_dir().join("pomodoro.json")
+}
+
+fn read() -> Option<Timer> {
+    let data = fs::read_to_string(path()).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+/// Current phase and seconds left in it, while a timer runs.
+pub fn status(now: i64) -> Option<(Phase, i64)> {
+    read().map(|t| phase_at(&t, now))
+}
+
+fn phase_at(t: &Timer, now: i64) -> (Phase, i64) {
+    let cycle = (t.work_secs + t.break_secs).max(1);
+    let pos = (now - t.started).max(0) % cycle;
+    if pos < t.work_secs {
+        (Phase::Work, t.work_secs - pos)
+    } else {
+        (Phase::Break, cycle - pos)
+    }
+}
+
+fn minutes(args: &[String], name: &str, default: i64) -> Result<i64, String> {
+    match config::arg_value(args, name) {
+        Some(v) => v
+            .parse::<i64>()
+            .ok()
+            .filter(|m| *m > 0)
+            .map(|m| m * 60)
+            .ok_or_else(|| format!("{} expects minutes, got {:?}", name, v)),
+        None => Ok(default * 60),
+    }
+}
+
+/// `statusline pomo start [--work 25] [--break 5] | stop | status`
+pub fn run(cfg: &Config) -> Result<String, String> {
+    let args = &cfg.command_args;
+    match args.first().map(String::as_str) {
+        Some("start") => {
+            let timer = Timer {
+                started: clock::now_unix(),
+                work_secs: minutes(args, "--work", WORK_MIN)?,
+                break_secs: minutes(args, "--break", BREAK_MIN)?,
+            };
+            let _ = fs::create_dir_all(cache::cache_dir());
+            let data = serde_json::to_string(&timer).map_err(|e| e.to_string())?;
+            fs::write(path(), data).map_err(|e| format!("cannot write timer: {}", e))?;
+            Ok(format!(
+                "[ok] Pomodoro started: {}m work, {}m break",
+                timer.work_secs / 60,
+                timer.break_secs / 60
+            ))
+        }
+        Some("stop") => match fs::remove_file(path()) {
+            Ok(()) => Ok("[ok] Pomodoro stopped".into()),
+            Err(_) => Ok("[--] No pomodoro running".into()),
+        },
+        Some("status") | None => match status(clock::now_unix()) {
+            Some((phase, left)) => Ok(format!(
+                "{} {}:{:02} left",
+                match phase {
+                    Phase::Work => "work",
+                    Phase::Break => "break",
+                },
+                left / 60,
+                left % 60
+            )),
+            None => Ok("[--] No pomodoro running".into()),
+        },
+        Some(other) => Err(format!("unknown pomo command: {}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phases_alternate_from_start() {
+        let t = Timer {
+            started: 1000,
+            work_secs: 1500,
+            break_secs: 300,
+        };
+        assert!(matches!(phase_at(&t, 1000), (Phase::Work, 1500)));
+        assert!(matches!(phase_at(&t, 1000 + 1499), (Phase::Work, 1)));
+        assert!(matches!(phase_at(&t, 1000 + 1500), (Phase::Break, 300)));
+        assert!(matches!(
+            phase_at(&t, 1000 + 1800 + 10),
+            (Phase::Work, 1490)
+        ));
+    }
+}
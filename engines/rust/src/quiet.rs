@@ -0,0 +1,46 @@
+//! Quiet hours (STATUSLINE_QUIET_HOURS=22:00-07:00,12-13): while one of the
+//! local-time windows is active, attention cues (context `⚠`, the break
+//! reminder, bells and the digest webhook) are suppressed. Values render as
+//! usual.
+
+use crate::clock;
+
+/// Whether `now` (minutes since midnight) falls in one of the windows.
+/// A window whose end is before its start runs past midnight.
+fn in_windows(spec: &str, now: u32) -> bool {
+    spec.split(',').any(|w| {
+        let Some((start, end)) = w.split_once('-') else {
+            return false;
+        };
+        match (clock::minutes(start), clock::minutes(end)) {
+            (Some(s), Some(e)) if s <= e => now >= s && now < e,
+            (Some(s), Some(e)) => now >= s || now < e,
+            _ => false,
+        }
+    })
+}
+
+/// Whether quiet hours are in effect right now.
+pub fn active(spec: &str) -> bool {
+    if spec.is_empty() {
+        return false;
+    }
+    let now = clock::now();
+    in_windows(spec, now.hour * 60 + now.minute)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_may_wrap_midnight() {
+        let spec = "22:00-07:30, 12-13";
+        assert!(in_windows(spec, 23 * 60));
+        assert!(in_windows(spec, 7 * 60 + 29));
+        assert!(!in_windows(spec, 7 * 60 + 30));
+        assert!(in_windows(spec, 12 * 60 + 59));
+        assert!(!in_windows(spec, 13 * 60));
+        assert!(!in_windows("bogus", 0));
+    }
+}
@@ -1,8 +1,10 @@
 use crate::cache;
 use crate::config::Config;
 use crate::format;
+use crate::rules::Values;
 use crate::session::Session;
-use std::path::Path;
+use crate::sources::Sources;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 const DIM: &str = "\x1b[2m";
 const RST: &str = "\x1b[0m";
@@ -11,20 +13,30 @@ const GREEN: &str = "\x1b[32m";
 const YELLOW: &str = "\x1b[33m";
 const RED: &str = "\x1b[31m";
 const MAGENTA: &str = "\x1b[35m";
+/// Bright white on red, for a protected branch
+const ALERT: &str = "\x1b[97;41m";
 
 pub fn render(sess: &Session, cfg: &Config) -> String {
-    let sep = format!(" {}\u{2502}{} ", DIM, RST);
-
-    // Session ID from transcript path
-    let session_id = if !sess.transcript_path.is_empty() {
-        let base = Path::new(&sess.transcript_path)
-            .file_name()
-            .map(|f| f.to_string_lossy().to_string())
-            .unwrap_or_default();
-        base.strip_suffix(".jsonl").unwrap_or(&base).to_string()
-    } else {
-        String::new()
-    };
+    render_values(sess, cfg).0
+}
+
+/// Render, also returning the segment values (for rules and the post hook).
+pub fn render_values(sess: &Session, cfg: &Config) -> (String, Values) {
+    render_with(sess, cfg, &Sources::LIVE)
+}
+
+/// `render_values` reading the repository and caches through `src`.
+pub fn render_with(sess: &Session, cfg: &Config, src: &Sources) -> (String, Values) {
+    if cfg.output == "plain-verbose" {
+        return render_verbose(sess, cfg, src);
+    }
+    if cfg.layout == "minimal" {
+        return render_minimal(sess, cfg, src);
+    }
+    let sep = format!(" {}{}{} ", DIM, symbol(cfg, "sep"), RST);
+
+    let session_id = sess.id();
+    let quiet = crate::quiet::active(&cfg.quiet_hours);
 
     // --- Model ---
     let model = if cfg.show_model {
@@ -41,73 +53,47 @@ pub fn render(sess: &Session, cfg: &Config) -> String {
     // --- Context bar ---
     let mut pct = 0i32;
     let mut bar = String::new();
-    let mut warn = "";
+    let mut warn = String::new();
     let mut clr = GREEN;
     if cfg.show_context {
         pct = sess.context_window.used_percentage as i32;
         let filled = (pct / 10).clamp(0, 10) as usize;
         let empty = 10 - filled;
-        bar = "\u{2593}".repeat(filled) + &"\u{2591}".repeat(empty);
+        bar = symbol(cfg, "bar_full").repeat(filled)
+            + symbol(cfg, "bar_empty").repeat(empty).as_str();
         if pct >= 90 {
             clr = RED;
-            warn = " \u{26a0}";
+            warn = format!(" {}", symbol(cfg, "warn"));
         } else if pct >= 70 {
             clr = YELLOW;
-            warn = " \u{26a0}";
+            warn = format!(" {}", symbol(cfg, "warn"));
+        }
+        if quiet {
+            warn.clear();
         }
     }
 
     // --- Cost ---
     let cost_fmt = if cfg.show_cost {
-        format::fmt_cost(sess.cost.total_cost_usd)
+        cost_text(sess, &cfg.precision)
     } else {
         String::new()
     };
 
     // --- Duration ---
     let dur_fmt = if cfg.show_duration {
-        format::fmt_duration(sess.cost.total_duration_ms as i64)
+        cfg.precision.duration(sess.cost.total_duration_ms as i64)
     } else {
         String::new()
     };
 
     // --- Git ---
-    let mut git_display = String::new();
-    let mut dirty = "";
-    let mut git_extra = String::new();
-    if cfg.show_git {
-        if let Ok(cwd) = std::env::current_dir() {
-            if let Some(gs) = crate::git::get(&cwd.to_string_lossy()) {
-                if !gs.branch.is_empty() {
-                    let sb = format::truncate(&format::shorten_branch(&gs.branch), 20);
-                    if gs.in_worktree {
-                        let sw = format::truncate(&format::shorten_branch(&gs.worktree_name), 20);
-                        if sw == sb {
-                            git_display = format!("\u{2295} {}", sb);
-                        } else {
-                            git_display = format!("\u{2295}{} {}", sw, sb);
-                        }
-                    } else {
-                        git_display = sb;
-                    }
-                    if gs.dirty {
-                        dirty = "\u{25cf}";
-                    }
-                    let mut parts = Vec::new();
-                    if gs.ahead > 0 {
-                        parts.push(format!("\u{2191}{}", gs.ahead));
-                    }
-                    if gs.behind > 0 {
-                        parts.push(format!("\u{2193}{}", gs.behind));
-                    }
-                    if gs.stash > 0 {
-                        parts.push(format!("stash:{}", gs.stash));
-                    }
-                    git_extra = parts.join(" ");
-                }
-            }
-        }
-    }
+    let (git_part, branch) = git_segment(cfg, &sess.workspace.project_dir, src);
+    let ticket = if cfg.ticket {
+        ticket_part(cfg, &branch)
+    } else {
+        String::new()
+    };
 
     // --- Lines added/removed ---
     let lines_fmt = if cfg.show_diff {
@@ -125,38 +111,76 @@ pub fn render(sess: &Session, cfg: &Config) -> String {
     // --- Token data ---
     let in_tok = sess.context_window.total_input_tokens as i64;
     let out_tok = sess.context_window.total_output_tokens as i64;
-    let in_fmt = format::fmt_k(in_tok);
-    let out_fmt = format::fmt_k(out_tok);
+    let in_fmt = cfg.precision.tokens(in_tok);
+    let out_fmt = cfg.precision.tokens(out_tok);
 
     // --- Per-model stats ---
-    let model_stats = if !session_id.is_empty() {
-        cache::read_models(&session_id)
+    let model_stats = if cfg.needs_models() && !session_id.is_empty() {
+        let _span = crate::trace::span("cache.models");
+        src.cache.models(&session_id)
     } else {
         None
     };
 
+    // --- Files edited ---
+    let files_fmt = match &model_stats {
+        Some(ms) if cfg.files_touched && !ms.files.is_empty() => format!(
+            "{}{}{} {}{}",
+            DIM,
+            symbol(cfg, "files"),
+            ms.files.len(),
+            label(cfg, "files"),
+            RST
+        ),
+        _ => String::new(),
+    };
+
+    // --- Cost without caching ---
+    let cost_fmt = match &model_stats {
+        Some(ms) if cfg.uncached_cost && !cost_fmt.is_empty() && ms.uncached_cost > 0.0 => {
+            format!(
+                "{} {}({} {}){}",
+                cost_fmt,
+                DIM,
+                cfg.precision.cost(ms.uncached_cost),
+                label(cfg, "uncached"),
+                RST
+            )
+        }
+        _ => cost_fmt,
+    };
+
+    let is_stale = |age: u64| cfg.stale_secs > 0 && age > cfg.stale_secs;
+    let models_stale = model_stats.as_ref().is_some_and(|ms| is_stale(ms.age_secs));
+
     let model_mix = if let Some(ref ms) = model_stats {
+        let none = symbol(cfg, "none");
         let max_out = ms.opus_out.max(ms.sonnet_out).max(ms.haiku_out);
         if cfg.show_model_bars && max_out > 0 {
             let o_bar = format::bar_char(ms.opus_out, max_out);
             let s_bar = format::bar_char(ms.sonnet_out, max_out);
             let h_bar = format::bar_char(ms.haiku_out, max_out);
             let o_c = if o_bar.is_empty() {
-                format!("{}\u{00b7}", DIM)
+                format!("{}{}", DIM, none)
             } else {
                 format!("{}{}", MAGENTA, o_bar)
             };
             let s_c = if s_bar.is_empty() {
-                format!("{}\u{00b7}", DIM)
+                format!("{}{}", DIM, none)
             } else {
                 format!("{}{}", CYAN, s_bar)
             };
             let h_c = if h_bar.is_empty() {
-                format!("{}\u{00b7}", DIM)
+                format!("{}{}", DIM, none)
             } else {
                 format!("{}{}", GREEN, h_bar)
             };
-            format!("{}{}{}{}", o_c, s_c, h_c, RST)
+            let mix = format!("{}{}{}{}", o_c, s_c, h_c, RST);
+            if models_stale {
+                mark_stale(&mix)
+            } else {
+                mix
+            }
         } else {
             String::new()
         }
@@ -177,7 +201,7 @@ pub fn render(sess: &Session, cfg: &Config) -> String {
             } else {
                 RED
             };
-            format!("{}{} tok/s{}", speed_clr, speed_int, RST)
+            format!("{}{} {}{}", speed_clr, speed_int, label(cfg, "tok/s"), RST)
         } else {
             String::new()
         }
@@ -188,67 +212,168 @@ pub fn render(sess: &Session, cfg: &Config) -> String {
     // --- Cumulative stats ---
     let mut cum_proj = String::new();
     let mut cum_all = String::new();
-    if cfg.show_cumulative {
-        let (proj_stats, all_stats) = cache::read_cumulative(&sess.workspace.project_dir);
+    let (mut cum_proj_zero, mut cum_all_zero) = (false, false);
+    if cfg.needs_cumulative() {
+        // The 30-day on/off split means little next to today's figure alone
+        let show_split = cfg.cumulative_format != "today";
+        let (proj_stats, all_stats) = {
+            let _span = crate::trace::span("cache.cumulative");
+            src.cache.cumulative(&sess.workspace.project_dir)
+        };
+        let proj_stats = proj_stats.filter(|_| cfg.cumulative_scope != "global");
+        let all_stats = all_stats.filter(|_| cfg.cumulative_scope != "project");
         if let Some(ps) = proj_stats {
+            cum_proj_zero = ps.d1 == 0.0 && ps.d7 == 0.0 && ps.d30 == 0.0 && ps.tokens[2] == 0;
             cum_proj = format!(
-                "\u{2302} {}/{}/{}",
-                format::fmt_cost(ps.d1),
-                format::fmt_cost(ps.d7),
-                format::fmt_cost(ps.d30)
+                "{} {}",
+                symbol(cfg, "project"),
+                cumulative_periods(&ps, cfg)
             );
+            if let Some((on, off)) = ps.d30_split.filter(|_| show_split) {
+                cum_proj += &work_split(on, off, &cfg.precision, &symbol(cfg, "work"));
+            }
+            if is_stale(ps.age_secs) {
+                cum_proj = mark_stale(&cum_proj);
+            }
         }
         if let Some(als) = all_stats {
+            cum_all_zero = als.d1 == 0.0 && als.d7 == 0.0 && als.d30 == 0.0 && als.tokens[2] == 0;
             cum_all = format!(
-                "\u{03a3} {}/{}/{}",
-                format::fmt_cost(als.d1),
-                format::fmt_cost(als.d7),
-                format::fmt_cost(als.d30)
+                "{} {}",
+                symbol(cfg, "global"),
+                cumulative_periods(&als, cfg)
             );
+            if let Some((on, off)) = als.d30_split.filter(|_| show_split) {
+                cum_all += &work_split(on, off, &cfg.precision, &symbol(cfg, "work"));
+            }
+            if is_stale(als.age_secs) {
+                cum_all = mark_stale(&cum_all);
+            }
         }
     }
 
+    // --- Conditional styling ---
+    let rules = crate::rules::parse(&cfg.rules);
+    let vals = values(sess, model.clone(), branch);
+    let style = |segment: &str, text: String| crate::rules::apply(&rules, &vals, segment, text);
+
     // ======== ASSEMBLE LINE 1 ========
     let mut l1_parts: Vec<String> = Vec::new();
 
+    if cfg.mood {
+        l1_parts.push(mood(sess, cfg, model_stats.as_ref()));
+    }
+    if cfg.awaiting_secs > 0 {
+        if let Some((tool, since)) = src.cache.pending(&sess.transcript_path) {
+            let waited = crate::clock::now_unix() - since;
+            if waited >= cfg.awaiting_secs as i64 {
+                l1_parts.push(awaiting(cfg, &tool, waited));
+            }
+        }
+    }
+
+    let account = account_part(sess, cfg, src);
     if !model.is_empty() {
         let mut part = format!("{}{}{}", CYAN, model, RST);
         if !model_mix.is_empty() {
             part = format!("{} {}", part, model_mix);
         }
-        l1_parts.push(part);
+        l1_parts.push(style("model", part));
     } else if !model_mix.is_empty() {
-        l1_parts.push(model_mix.clone());
+        l1_parts.push(style("model", model_mix.clone()));
+    }
+    if let Some(account) = account {
+        l1_parts.push(account);
     }
 
     if !bar.is_empty() {
-        l1_parts.push(format!("{}{} {}%{}{}", clr, bar, pct, warn, RST));
+        let mut part = style(
+            "context",
+            format!(
+                "{}{} {}%{}{}",
+                clr,
+                bar,
+                cfg.precision.pct(sess.context_window.used_percentage),
+                warn,
+                RST
+            ),
+        );
+        if cfg.sparkline && !session_id.is_empty() {
+            let history = {
+                let _span = crate::trace::span("sparkline");
+                src.cache.push_context(&session_id, pct.clamp(0, 100) as u8)
+            };
+            if history.len() > 1 {
+                part = format!("{} {}", part, context_sparkline(&history, clr, cfg));
+            }
+        }
+        let zero = sess.context_window.used_percentage == 0.0;
+        l1_parts.extend(when_zero(cfg, "context", zero, part));
     }
     if !cost_fmt.is_empty() {
-        l1_parts.push(cost_fmt);
+        let zero = sess.cost.total_cost_usd == 0.0;
+        l1_parts.extend(when_zero(cfg, "cost", zero, style("cost", cost_fmt)));
+    }
+    if let Some(end) = crate::clock::minutes(&cfg.workday_end) {
+        let now = crate::clock::now_unix();
+        let left = crate::clock::day_start(now) + end as i64 * 60 - now;
+        if let Some(part) = projection(cfg, sess, left, end) {
+            l1_parts.push(part);
+        }
     }
     if !dur_fmt.is_empty() {
-        l1_parts.push(dur_fmt);
+        let zero = sess.cost.total_duration_ms < 60_000.0;
+        l1_parts.extend(when_zero(cfg, "duration", zero, style("duration", dur_fmt)));
     }
-    if !git_display.is_empty() {
-        let mut git_part = format!("{}{}{}", MAGENTA, git_display, RST);
-        if !dirty.is_empty() {
-            git_part = format!("{} {}{}{}", git_part, YELLOW, dirty, RST);
-        }
-        if !git_extra.is_empty() {
-            git_part = format!("{} {}{}{}", git_part, CYAN, git_extra, RST);
+    if matches!(cfg.session_start.as_str(), "clock" | "ago") {
+        let now = crate::clock::now_unix();
+        let started = now - sess.cost.total_duration_ms as i64 / 1000;
+        l1_parts.push(session_start(cfg, started, now));
+    }
+    if cfg.break_after > 0 && !quiet {
+        if let Some(part) = break_reminder(cfg, src) {
+            l1_parts.push(part);
         }
-        l1_parts.push(git_part);
+    }
+    if let Some(at) = src.cache.deadline(&sess.workspace.project_dir) {
+        l1_parts.push(countdown(cfg, at - crate::clock::now_unix()));
+    }
+    if let Some((phase, left)) = src.cache.pomodoro(crate::clock::now_unix()) {
+        l1_parts.push(pomodoro(cfg, phase, left));
+    }
+    if cfg.plan_tokens > 0 {
+        let now = crate::clock::now_unix();
+        let start = crate::clock::week_start(now);
+        let used = src.cache.tokens_since(start as u64);
+        let elapsed = (now - start) as f64 / (7 * 86_400) as f64;
+        l1_parts.push(plan_pace(cfg, used, elapsed));
+    }
+    if !git_part.is_empty() {
+        l1_parts.push(style("git", git_part));
+    }
+    if !ticket.is_empty() {
+        l1_parts.push(style("ticket", ticket));
     }
     if !lines_fmt.is_empty() {
-        l1_parts.push(lines_fmt);
+        l1_parts.push(style("diff", lines_fmt));
+    }
+    if !files_fmt.is_empty() {
+        l1_parts.push(style("files", files_fmt));
     }
 
-    let l1 = l1_parts.join(&sep);
+    #[cfg(feature = "plugins")]
+    let plugin_parts = crate::plugins::segments(&vals);
+    #[cfg(not(feature = "plugins"))]
+    let plugin_parts: Vec<String> = Vec::new();
+    if !cfg.line2 {
+        l1_parts.extend(plugin_parts.iter().cloned());
+    }
 
     // ======== ASSEMBLE LINE 2 ========
-    let l2 = if cfg.line2 {
-        let mut l2_parts: Vec<String> = Vec::new();
+    let mut l2_parts: Vec<String> = Vec::new();
+    if cfg.line2 {
+        // Anything besides zeros, for STATUSLINE_LINE2=auto
+        let mut has_data = !plugin_parts.is_empty();
 
         if cfg.show_tokens {
             let mut tok_parts: Vec<String> = Vec::new();
@@ -258,8 +383,8 @@ pub fn render(sess: &Session, cfg: &Config) -> String {
                         "{}O{}:{}/{}",
                         MAGENTA,
                         RST,
-                        format::fmt_k(ms.opus_in),
-                        format::fmt_k(ms.opus_out)
+                        cfg.precision.tokens(ms.opus_in),
+                        cfg.precision.tokens(ms.opus_out)
                     ));
                 }
                 if ms.sonnet_out > 0 || ms.sonnet_in > 0 {
@@ -267,8 +392,8 @@ pub fn render(sess: &Session, cfg: &Config) -> String {
                         "{}S{}:{}/{}",
                         CYAN,
                         RST,
-                        format::fmt_k(ms.sonnet_in),
-                        format::fmt_k(ms.sonnet_out)
+                        cfg.precision.tokens(ms.sonnet_in),
+                        cfg.precision.tokens(ms.sonnet_out)
                     ));
                 }
                 if ms.haiku_out > 0 || ms.haiku_in > 0 {
@@ -276,35 +401,78 @@ pub fn render(sess: &Session, cfg: &Config) -> String {
                         "{}H{}:{}/{}",
                         GREEN,
                         RST,
-                        format::fmt_k(ms.haiku_in),
-                        format::fmt_k(ms.haiku_out)
+                        cfg.precision.tokens(ms.haiku_in),
+                        cfg.precision.tokens(ms.haiku_out)
                     ));
                 }
             }
+            has_data |= !tok_parts.is_empty() || in_tok != 0 || out_tok != 0;
             if !tok_parts.is_empty() {
-                l2_parts.push(tok_parts.join(" "));
+                let toks = tok_parts.join(" ");
+                let toks = if models_stale {
+                    mark_stale(&toks)
+                } else {
+                    toks
+                };
+                l2_parts.push(style("tokens", toks));
             } else {
-                l2_parts.push(format!(
-                    "{}in:{}{} {}out:{}{}",
-                    DIM, RST, in_fmt, DIM, RST, out_fmt
-                ));
+                let part = style(
+                    "tokens",
+                    format!(
+                        "{}{}:{}{} {}{}:{}{}",
+                        DIM,
+                        label(cfg, "in"),
+                        RST,
+                        in_fmt,
+                        DIM,
+                        label(cfg, "out"),
+                        RST,
+                        out_fmt
+                    ),
+                );
+                l2_parts.extend(when_zero(cfg, "tokens", in_tok == 0 && out_tok == 0, part));
             }
         }
 
         if !speed_fmt.is_empty() {
-            l2_parts.push(speed_fmt);
+            has_data = true;
+            l2_parts.push(style("speed", speed_fmt));
         }
+        has_data |= !cum_proj.is_empty() && !cum_proj_zero;
+        has_data |= !cum_all.is_empty() && !cum_all_zero;
         if !cum_proj.is_empty() {
-            l2_parts.push(cum_proj);
+            let part = style("cumulative", cum_proj);
+            l2_parts.extend(when_zero(cfg, "cumulative", cum_proj_zero, part));
         }
         if !cum_all.is_empty() {
-            l2_parts.push(cum_all);
+            let part = style("cumulative", cum_all);
+            l2_parts.extend(when_zero(cfg, "cumulative", cum_all_zero, part));
+        }
+        l2_parts.extend(plugin_parts);
+        if cfg.line2_auto && !has_data {
+            l2_parts.clear();
         }
+    }
 
-        l2_parts.join(&sep)
-    } else {
-        String::new()
+    // --- Built-in layouts: heavy info first, or all on one line ---
+    match cfg.layout.as_str() {
+        "swapped" if !l2_parts.is_empty() => std::mem::swap(&mut l1_parts, &mut l2_parts),
+        "dense" => l1_parts.append(&mut l2_parts),
+        _ => {}
+    }
+
+    let width = line_width(sess, cfg);
+    let finish = |parts: &mut Vec<String>| {
+        if width > 0 {
+            fit_width(parts, &sep, &symbol(cfg, "more"), width);
+        }
+        if cfg.rtl {
+            mirror(parts);
+        }
+        parts.join(&sep)
     };
+    let l1 = finish(&mut l1_parts);
+    let l2 = finish(&mut l2_parts);
 
     // --- NO_COLOR ---
     let (l1, l2) = if cfg.no_color {
@@ -314,31 +482,1372 @@ pub fn render(sess: &Session, cfg: &Config) -> String {
     };
 
     if !l2.is_empty() {
+        (format!("{}\n{}\n", l1, l2), vals)
+    } else {
+        (format!("{}\n\n", l1), vals)
+    }
+}
+
+fn values(sess: &Session, model: String, branch: String) -> Values {
+    Values {
+        model,
+        context: sess.context_window.used_percentage,
+        cost: sess.cost.total_cost_usd,
+        duration: sess.cost.total_duration_ms / 60_000.0,
+        branch,
+        added: sess.cost.total_lines_added,
+        removed: sess.cost.total_lines_removed,
+    }
+}
+
+/// Context history as block characters, or as an inline image when
+/// STATUSLINE_GRAPHICS selects a protocol the terminal supports.
+fn context_sparkline(history: &[u8], clr: &str, cfg: &Config) -> String {
+    #[cfg(feature = "graphics")]
+    if !cfg.no_color {
+        if let Some(proto) = crate::graphics::protocol(&cfg.graphics) {
+            let rgb = match clr {
+                RED => (243, 139, 168),
+                YELLOW => (249, 226, 175),
+                _ => (166, 227, 161),
+            };
+            return crate::graphics::sparkline(history, rgb, proto);
+        }
+    }
+    #[cfg(not(feature = "graphics"))]
+    let _ = cfg;
+    format!("{}{}{}", clr, format::sparkline(history), RST)
+}
+
+/// One-glyph session health, worst condition first: budget spent, context
+/// nearly full, recent tool errors, context filling up, all fine.
+fn mood(sess: &Session, cfg: &Config, models: Option<&cache::ModelStats>) -> String {
+    let pct = sess.context_window.used_percentage;
+    let key = if cfg.budget > 0.0 && sess.cost.total_cost_usd >= cfg.budget {
+        "mood_broke"
+    } else if pct >= 90.0 {
+        "mood_full"
+    } else if models.is_some_and(|m| m.recent_errors > 0) {
+        "mood_errors"
+    } else if pct >= 70.0 {
+        "mood_tight"
+    } else {
+        "mood_ok"
+    };
+    symbol(cfg, key)
+}
+
+/// Account tag (STATUSLINE_ACCOUNT), or a red warning when the account does
+/// not match STATUSLINE_ACCOUNT_EXPECT for this project, whether or not the
+/// tag is enabled.
+fn account_part(sess: &Session, cfg: &Config, src: &Sources) -> Option<String> {
+    if !cfg.account && cfg.account_expect.is_empty() {
+        return None;
+    }
+    let account = src.cache.account()?;
+    let dir = if sess.workspace.project_dir.is_empty() {
+        std::env::current_dir()
+            .map(|d| d.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    } else {
+        sess.workspace.project_dir.clone()
+    };
+    let home = std::env::var("HOME").unwrap_or_default();
+    if crate::account::mismatch(&account, &cfg.account_expect, &dir, &home) {
+        Some(format!(
+            "{}{} {}{}",
+            RED,
+            symbol(cfg, "warn"),
+            account.tag,
+            RST
+        ))
+    } else if cfg.account {
+        Some(format!("{}{}{}", DIM, account.tag, RST))
+    } else {
+        None
+    }
+}
+
+/// `minimal` layout: one short line, `S 62% $4.2 main*`.
+fn render_minimal(sess: &Session, cfg: &Config, src: &Sources) -> (String, Values) {
+    let mut parts: Vec<String> = Vec::new();
+    let name = sess
+        .model
+        .display_name
+        .strip_prefix("Claude ")
+        .unwrap_or(&sess.model.display_name);
+    let mut branch = String::new();
+    if cfg.mood {
+        let ms = src.cache.models(&sess.id());
+        parts.push(mood(sess, cfg, ms.as_ref()));
+    }
+    if cfg.show_model {
+        let initial = name.chars().next().unwrap_or('?');
+        parts.push(format!("{}{}{}", CYAN, initial, RST));
+    }
+    if let Some(account) = account_part(sess, cfg, src) {
+        parts.push(account);
+    }
+    if cfg.show_context {
+        let pct = sess.context_window.used_percentage as i32;
+        let clr = if pct >= 90 {
+            RED
+        } else if pct >= 70 {
+            YELLOW
+        } else {
+            GREEN
+        };
+        parts.push(format!(
+            "{}{}%{}",
+            clr,
+            cfg.precision.pct(sess.context_window.used_percentage),
+            RST
+        ));
+    }
+    if cfg.show_cost {
+        parts.push(cost_text(sess, &cfg.precision));
+    }
+    if cfg.show_git {
+        let gs = std::env::current_dir().ok().and_then(|cwd| {
+            let _span = crate::trace::span("git");
+            src.vcs
+                .state(&cwd.to_string_lossy(), &sess.workspace.project_dir, cfg)
+        });
+        if let Some(gs) = gs.filter(|g| !g.branch.is_empty()) {
+            let short =
+                format::branch_label(&gs.branch, &cfg.branch_icons, 20, &cfg.branch_truncate);
+            let dirty = if gs.dirty { "*" } else { "" };
+            // The red background must end before the dirty marker
+            let name = match branch_color(cfg, &gs.branch) {
+                ALERT => format!("{}{}{}", ALERT, short, RST),
+                clr => format!("{}{}", clr, short),
+            };
+            parts.push(format!("{}{}{}{}", name, YELLOW, dirty, RST));
+            branch = gs.branch;
+        }
+    }
+    let width = line_width(sess, cfg);
+    if width > 0 {
+        fit_width(&mut parts, " ", &symbol(cfg, "more"), width);
+    }
+    if cfg.rtl {
+        mirror(&mut parts);
+    }
+    let line = parts.join(" ");
+    let line = if cfg.no_color {
+        strip_ansi(&line)
+    } else {
+        line
+    };
+    (
+        format!("{}\n\n", line),
+        values(sess, name.to_string(), branch),
+    )
+}
+
+/// Screen-reader output (`--output plain-verbose`): one wordy line without
+/// colors, glyphs or abbreviations.
+fn render_verbose(sess: &Session, cfg: &Config, src: &Sources) -> (String, Values) {
+    let name = sess
+        .model
+        .display_name
+        .strip_prefix("Claude ")
+        .unwrap_or(&sess.model.display_name);
+    let mut parts: Vec<String> = Vec::new();
+    if cfg.show_model && !name.is_empty() {
+        parts.push(format!("Model {}", name));
+    }
+    if cfg.show_context {
+        let pct = sess.context_window.used_percentage as i32;
+        let level = if pct >= 90 && !crate::quiet::active(&cfg.quiet_hours) {
+            ", critical"
+        } else {
+            ""
+        };
+        parts.push(format!("context {} percent{}", pct, level));
+    }
+    if cfg.show_cost {
+        let estimated = if sess.cost.estimated { "about " } else { "" };
+        parts.push(format!(
+            "cost {}{}",
+            estimated,
+            spoken_cost(sess.cost.total_cost_usd)
+        ));
+    }
+    if cfg.show_duration {
+        parts.push(format!(
+            "duration {}",
+            spoken_duration(sess.cost.total_duration_ms as i64)
+        ));
+    }
+    let (_, branch) = git_segment(cfg, &sess.workspace.project_dir, src);
+    if !branch.is_empty() {
+        parts.push(format!("branch {}", branch));
+    }
+    if cfg.show_diff {
+        let added = sess.cost.total_lines_added as i64;
+        let removed = sess.cost.total_lines_removed as i64;
+        if added > 0 || removed > 0 {
+            parts.push(format!("{} lines added, {} removed", added, removed));
+        }
+    }
+    (
+        format!("{}\n\n", parts.join(", ")),
+        values(sess, name.to_string(), branch),
+    )
+}
+
+fn plural(n: i64, unit: &str) -> String {
+    format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" })
+}
+
+/// `4 dollars 20 cents`
+fn spoken_cost(usd: f64) -> String {
+    let cents = (usd * 100.0).round() as i64;
+    match (cents / 100, cents % 100) {
+        (0, c) => plural(c, "cent"),
+        (d, 0) => plural(d, "dollar"),
+        (d, c) => format!("{} {}", plural(d, "dollar"), plural(c, "cent")),
+    }
+}
+
+/// `1 hour 5 minutes`
+fn spoken_duration(ms: i64) -> String {
+    let min = ms / 60_000;
+    match (min / 60, min % 60) {
+        (0, m) => plural(m, "minute"),
+        (h, 0) => plural(h, "hour"),
+        (h, m) => format!("{} {}", plural(h, "hour"), plural(m, "minute")),
+    }
+}
+
+/// The d1/d7/d30 figures of a cumulative segment: costs, token totals, or
+/// costs followed by dimmed tokens (`STATUSLINE_CUMULATIVE_UNIT`), laid out
+/// as `$4.2/$31/$187`, `day $4.2 wk $31 mo $187` or `$4.2 today`
+/// (`STATUSLINE_CUMULATIVE_FORMAT`).
+fn cumulative_periods(stats: &cache::CumulativeStats, cfg: &Config) -> String {
+    let p = &cfg.precision;
+    let costs = [stats.d1, stats.d7, stats.d30].map(|c| p.cost(c));
+    let tokens = stats.tokens.map(|t| p.tokens(t));
+    let figure = |i: usize| match cfg.cumulative_unit.as_str() {
+        "tokens" => tokens[i].clone(),
+        "both" => format!("{} {}{}{}", costs[i], DIM, tokens[i], RST),
+        _ => costs[i].clone(),
+    };
+    match cfg.cumulative_format.as_str() {
+        "labeled" => {
+            let names = label(cfg, "periods");
+            let names: Vec<&str> = names.split('/').collect();
+            (0..3)
+                .map(|i| {
+                    format!(
+                        "{}{}{} {}",
+                        DIM,
+                        names.get(i).unwrap_or(&""),
+                        RST,
+                        figure(i)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+        "today" => format!("{} {}{}{}", figure(0), DIM, label(cfg, "today"), RST),
+        _ => {
+            let figures = match cfg.cumulative_unit.as_str() {
+                "tokens" => tokens.join("/"),
+                "both" => format!("{} {}{}{}", costs.join("/"), DIM, tokens.join("/"), RST),
+                _ => costs.join("/"),
+            };
+            // Calendar periods read differently from the default rolling ones
+            if cfg.day_boundary == "calendar" {
+                format!("{} {}{}{}", figures, DIM, label(cfg, "calendar"), RST)
+            } else {
+                figures
+            }
+        }
+    }
+}
+
+/// 30-day cost inside/outside STATUSLINE_WORK_HOURS, ` ◷$20/$6`.
+fn work_split(on: f64, off: f64, precision: &format::Precision, icon: &str) -> String {
+    format!(
+        " {}{}{}{}/{}",
+        DIM,
+        icon,
+        RST,
+        precision.cost(on),
+        precision.cost(off)
+    )
+}
+
+/// Session cost projected to the end of the workday at the average burn
+/// rate so far, `→$31 by 18:00`. Nothing once the workday is over or while
+/// the session is too young for a meaningful rate.
+fn projection(cfg: &Config, sess: &Session, left_secs: i64, end_min: u32) -> Option<String> {
+    let dur_secs = sess.cost.total_duration_ms as i64 / 1000;
+    if left_secs <= 0 || dur_secs < 60 {
+        return None;
+    }
+    let cost = sess.cost.total_cost_usd;
+    let projected = cost + cost / dur_secs as f64 * left_secs as f64;
+    Some(format!(
+        "{}{}{} by {:02}:{:02}{}",
+        DIM,
+        symbol(cfg, "projection"),
+        cfg.precision.cost(projected),
+        end_min / 60,
+        end_min % 60,
+        RST
+    ))
+}
+
+/// Weekly token use against STATUSLINE_PLAN_TOKENS, with the distance from
+/// an even burn through the week: `⚖ 38% ▲6%` when ahead of schedule (yellow,
+/// red beyond 10 points), green `⚖ 31% ▼3%` when behind.
+fn plan_pace(cfg: &Config, used: u64, elapsed: f64) -> String {
+    let used_pct = used as f64 * 100.0 / cfg.plan_tokens as f64;
+    let diff = (used_pct - elapsed * 100.0).round() as i64;
+    let (clr, arrow) = if diff > 10 {
+        (RED, "pace_ahead")
+    } else if diff > 0 {
+        (YELLOW, "pace_ahead")
+    } else {
+        (GREEN, "pace_behind")
+    };
+    format!(
+        "{}{} {}% {}{}%{}",
+        clr,
+        symbol(cfg, "plan"),
+        used_pct.round() as i64,
+        symbol(cfg, arrow),
+        diff.abs(),
+        RST
+    )
+}
+
+/// When the session started, by STATUSLINE_SESSION_START: `since 09:14`
+/// (`clock`) or `started 2h5m ago` (`ago`).
+fn session_start(cfg: &Config, started: i64, now: i64) -> String {
+    let text = if cfg.session_start == "ago" {
+        let ago = format::fmt_duration((now - started).max(0) * 1000);
+        label(cfg, "started").replace("{}", &ago)
+    } else {
+        let t = crate::clock::local(started);
+        let at = format!("{:02}:{:02}", t.hour, t.minute);
+        label(cfg, "since").replace("{}", &at)
+    };
+    format!("{}{}{}", DIM, text, RST)
+}
+
+/// `☕ 1h32m` once activity has been continuous for STATUSLINE_BREAK_AFTER
+/// minutes.
+fn break_reminder(cfg: &Config, src: &Sources) -> Option<String> {
+    let now = crate::clock::now_unix();
+    let active = now - src.cache.touch_activity(now);
+    (active >= cfg.break_after as i64 * 60).then(|| {
+        format!(
+            "{}{} {}{}",
+            YELLOW,
+            symbol(cfg, "break"),
+            format::fmt_duration(active * 1000),
+            RST
+        )
+    })
+}
+
+/// Time left until the project deadline: `⏳ 2d4h`, yellow within a day,
+/// red within an hour and once it has passed (`⏳ -15m`).
+fn countdown(cfg: &Config, secs: i64) -> String {
+    let left = secs.abs();
+    let text = if left >= 86_400 {
+        format!("{}d{}h", left / 86_400, left % 86_400 / 3600)
+    } else {
+        format::fmt_duration(left * 1000)
+    };
+    let clr = if secs < 3600 {
+        RED
+    } else if secs < 86_400 {
+        YELLOW
+    } else {
+        DIM
+    };
+    let sign = if secs < 0 { "-" } else { "" };
+    format!("{}{} {}{}{}", clr, symbol(cfg, "deadline"), sign, text, RST)
+}
+
+/// Pomodoro time left: `🍅 17:32` while working, green `🍅 break 3:12`.
+fn pomodoro(cfg: &Config, phase: crate::pomodoro::Phase, left: i64) -> String {
+    let (clr, text) = match phase {
+        crate::pomodoro::Phase::Work => (RED, String::new()),
+        crate::pomodoro::Phase::Break => (GREEN, format!("{} ", label(cfg, "break"))),
+    };
+    format!(
+        "{}{} {}{}:{:02}{}",
+        clr,
+        symbol(cfg, "pomodoro"),
+        text,
+        left / 60,
+        left % 60,
+        RST
+    )
+}
+
+/// Session cost, with a `~` when it was estimated from token usage.
+fn cost_text(sess: &Session, precision: &format::Precision) -> String {
+    let cost = precision.cost(sess.cost.total_cost_usd);
+    if sess.cost.estimated {
+        format!("~{}", cost)
+    } else {
+        cost
+    }
+}
+
+/// Horizontal glyphs and their mirror images, for `STATUSLINE_RTL`.
+const MIRRORED: [(char, char); 6] = [
+    ('\u{2192}', '\u{2190}'),
+    ('\u{25b8}', '\u{25c2}'),
+    ('\u{25b6}', '\u{25c0}'),
+    ('\u{276f}', '\u{276e}'),
+    ('\u{bb}', '\u{ab}'),
+    ('\u{2197}', '\u{2196}'),
+];
+
+/// Right-to-left mode: reverse the segment order, mirror horizontal glyphs,
+/// fill the context bar from the right and move `↑`/`↓` behind their
+/// counts, so that a terminal which
+/// bidi-reorders the line shows it the way it reads.
+fn mirror(parts: &mut [String]) {
+    parts.reverse();
+    for part in parts.iter_mut() {
+        *part = mirror_glyphs(part);
+    }
+}
+
+fn mirror_glyphs(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\u{2191}' || c == '\u{2193}' {
+            while let Some(d) = chars.next_if(char::is_ascii_digit) {
+                out.push(d);
+            }
+            out.push(c);
+            continue;
+        }
+        if c == '\u{2593}' || c == '\u{2591}' {
+            let mut bar = vec![c];
+            while let Some(b) = chars.next_if(|b| *b == '\u{2593}' || *b == '\u{2591}') {
+                bar.push(b);
+            }
+            out.extend(bar.iter().rev());
+            continue;
+        }
+        let m = MIRRORED.iter().find_map(|&(a, b)| match c {
+            _ if c == a => Some(b),
+            _ if c == b => Some(a),
+            _ => None,
+        });
+        out.push(m.unwrap_or(c));
+    }
+    out
+}
+
+/// Dim a value and prefix it with `~` to flag data from an outdated cache.
+fn mark_stale(s: &str) -> String {
+    format!("{}~{}{}", DIM, strip_ansi(s), RST)
+}
+
+/// A segment showing zero under STATUSLINE_HIDE_WHEN_ZERO (`cost,context=dim`):
+/// hidden, dimmed, or as it was when not listed.
+fn when_zero(cfg: &Config, segment: &str, zero: bool, text: String) -> Option<String> {
+    if !zero {
+        return Some(text);
+    }
+    let mode = cfg.hide_when_zero.split(',').find_map(|entry| {
+        let (name, mode) = entry.split_once('=').unwrap_or((entry, "hide"));
+        (name.trim() == segment).then(|| mode.trim())
+    });
+    match mode {
+        Some("hide") => None,
+        Some("dim") => Some(format!("{}{}{}", DIM, strip_ansi(&text), RST)),
+        _ => Some(text),
+    }
+}
+
+/// Local-only status for interactive use without a session payload on stdin:
+/// git, working directory and time, plus an optional usage hint.
+pub fn render_local(cfg: &Config, hint: bool) -> String {
+    let sep = format!(" {}{}{} ", DIM, symbol(cfg, "sep"), RST);
+    let mut parts: Vec<String> = Vec::new();
+
+    let (git_part, _) = git_segment(cfg, "", &Sources::LIVE);
+    if !git_part.is_empty() {
+        parts.push(git_part);
+    }
+    if let Ok(cwd) = std::env::current_dir() {
+        let cwd = cwd.to_string_lossy().to_string();
+        let home = std::env::var("HOME").unwrap_or_default();
+        let short = match cwd.strip_prefix(&home) {
+            Some(rest) if !home.is_empty() => format!("~{}", rest),
+            _ => cwd,
+        };
+        parts.push(format!("{}{}{}", CYAN, short, RST));
+    }
+    let now = crate::clock::now();
+    parts.push(format!("{:02}:{:02}", now.hour, now.minute));
+
+    if cfg.rtl {
+        mirror(&mut parts);
+    }
+    let l1 = parts.join(&sep);
+    if !hint {
+        let l1 = if cfg.no_color { strip_ansi(&l1) } else { l1 };
+        return format!("{}\n", l1);
+    }
+    let l2 = format!(
+        "{}no session JSON on stdin \u{2013} Claude Code pipes it in (see --help){}",
+        DIM, RST
+    );
+    if cfg.no_color {
+        format!("{}\n{}\n", strip_ansi(&l1), strip_ansi(&l2))
+    } else {
         format!("{}\n{}\n", l1, l2)
+    }
+}
+
+/// A textual label in the configured language (see `i18n`).
+fn label(cfg: &Config, key: &str) -> String {
+    crate::i18n::label(&cfg.lang, &cfg.labels, key)
+}
+
+fn symbol(cfg: &Config, key: &str) -> String {
+    crate::symbols::symbol(&cfg.symbols, key)
+}
+
+/// Branch name color: magenta, or white on red for a protected branch.
+fn branch_color(cfg: &Config, branch: &str) -> &'static str {
+    if crate::git::is_protected(branch, &cfg.protected_branches) {
+        ALERT
     } else {
-        format!("{}\n\n", l1)
+        MAGENTA
+    }
+}
+
+/// Git branch segment: branch (or worktree), dirty marker, ahead/behind, stash.
+/// Also returns the full branch name. `project_dir` scopes the dirty check
+/// with STATUSLINE_DIRTY_SCOPE=project.
+fn git_segment(cfg: &Config, project_dir: &str, src: &Sources) -> (String, String) {
+    let mut branch = String::new();
+    let mut git_display = String::new();
+    let mut dirty = String::new();
+    let mut git_extra = String::new();
+    let mut signed = String::new();
+    let mut files_warn = String::new();
+    let mut ci = String::new();
+    if cfg.show_git {
+        if let Ok(cwd) = std::env::current_dir() {
+            let gs = {
+                let _span = crate::trace::span("git");
+                src.vcs.state(&cwd.to_string_lossy(), project_dir, cfg)
+            };
+            if let Some(gs) = gs {
+                if !gs.branch.is_empty() {
+                    branch = gs.branch.clone();
+                    let sb = format::branch_label(
+                        &gs.branch,
+                        &cfg.branch_icons,
+                        20,
+                        &cfg.branch_truncate,
+                    );
+                    if gs.in_worktree {
+                        let sw = format::branch_label(
+                            &gs.worktree_name,
+                            &cfg.branch_icons,
+                            20,
+                            &cfg.branch_truncate,
+                        );
+                        if sw == sb {
+                            git_display = format!("{} {}", symbol(cfg, "worktree"), sb);
+                        } else {
+                            git_display = format!("{}{} {}", symbol(cfg, "worktree"), sw, sb);
+                        }
+                    } else {
+                        git_display = sb;
+                    }
+                    if gs.dirty {
+                        dirty = symbol(cfg, "dirty");
+                    }
+                    let mut parts = Vec::new();
+                    if gs.ahead > 0 {
+                        let n = format::fmt_commits(gs.ahead, gs.ahead_capped);
+                        parts.push(format!("{}{}", symbol(cfg, "ahead"), n));
+                    }
+                    if gs.behind > 0 {
+                        let n = format::fmt_commits(gs.behind, gs.behind_capped);
+                        parts.push(format!("{}{}", symbol(cfg, "behind"), n));
+                    }
+                    if gs.stash > 0 {
+                        let detail = match cfg.stash_detail.as_str() {
+                            "age" if gs.stash_time > 0 => {
+                                let age = crate::clock::now_unix() - gs.stash_time;
+                                format!(" ({})", format::fmt_age(age))
+                            }
+                            "message" if !gs.stash_message.is_empty() => {
+                                format!(" ({})", format::truncate(&gs.stash_message, 20))
+                            }
+                            _ => String::new(),
+                        };
+                        let stash = label(cfg, "stash");
+                        parts.push(format!("{}:{}{}", stash, gs.stash, detail));
+                    }
+                    if gs.commits_today > 0 {
+                        parts.push(format!("{}{}", symbol(cfg, "commits"), gs.commits_today));
+                    }
+                    git_extra = parts.join(" ");
+                    let mut warn = Vec::new();
+                    if gs.large_staged > 0 {
+                        warn.push(format!("big:{}", gs.large_staged));
+                    }
+                    if gs.lfs_pointers > 0 {
+                        warn.push(format!("lfs:{}", gs.lfs_pointers));
+                    }
+                    if !warn.is_empty() {
+                        files_warn = format!(
+                            "{}{} {}{}",
+                            YELLOW,
+                            symbol(cfg, "warn"),
+                            warn.join(" "),
+                            RST
+                        );
+                    }
+                    if cfg.ci && !gs.head.is_empty() {
+                        ci = match crate::ci::status(cfg, &gs.head).as_deref() {
+                            Some("pass") => format!("{}CI {}{}", GREEN, symbol(cfg, "pass"), RST),
+                            Some("fail") => format!("{}CI {}{}", RED, symbol(cfg, "fail"), RST),
+                            Some(_) => format!("{}CI {}{}", YELLOW, symbol(cfg, "pending"), RST),
+                            None => String::new(),
+                        };
+                    }
+                    if cfg.signed {
+                        signed = match gs.signature.as_str() {
+                            "" => format!("{}unsigned{}", RED, RST),
+                            // Present, not verified: no green
+                            kind => format!("{}signed:{}{}", DIM, kind, RST),
+                        };
+                    }
+                }
+            }
+        }
+    }
+
+    if git_display.is_empty() {
+        return (String::new(), branch);
+    }
+    let clr = branch_color(cfg, &branch);
+    let mut git_part = format!("{}{}{}", clr, git_display, RST);
+    if !dirty.is_empty() {
+        git_part = format!("{} {}{}{}", git_part, YELLOW, dirty, RST);
+    }
+    if !ci.is_empty() {
+        git_part = format!("{} {}", git_part, ci);
+    }
+    if !git_extra.is_empty() {
+        git_part = format!("{} {}{}{}", git_part, CYAN, git_extra, RST);
+    }
+    if !signed.is_empty() {
+        git_part = format!("{} {}", git_part, signed);
+    }
+    if !files_warn.is_empty() {
+        git_part = format!("{} {}", git_part, files_warn);
+    }
+    (git_part, branch)
+}
+
+/// A tool call that has been waiting for its result: usually a permission
+/// prompt in another pane.
+fn awaiting(cfg: &Config, tool: &str, waited: i64) -> String {
+    format!(
+        "{}{}awaiting approval{} {}{} {}{}",
+        ALERT,
+        symbol(cfg, "awaiting"),
+        RST,
+        DIM,
+        tool,
+        cfg.precision.duration(waited * 1000),
+        RST
+    )
+}
+
+/// Ticket id from the branch name, linked to STATUSLINE_TICKET_URL if set.
+fn ticket_part(cfg: &Config, branch: &str) -> String {
+    let Some((text, id)) = format::ticket(branch, &cfg.ticket_patterns) else {
+        return String::new();
+    };
+    let text = format!("{}{}{}", CYAN, text, RST);
+    if cfg.ticket_url.is_empty() {
+        text
+    } else {
+        hyperlink(&cfg.ticket_url.replace("{id}", &id), &text)
+    }
+}
+
+/// OSC 8 hyperlink; terminals without support show the text only.
+fn hyperlink(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+/// Columns available per line: STATUSLINE_MAX_WIDTH, narrowed to the
+/// terminal width from the session JSON with STATUSLINE_FIT_TERMINAL. 0 means
+/// no limit.
+fn line_width(sess: &Session, cfg: &Config) -> usize {
+    let hint = if cfg.fit_terminal {
+        sess.terminal.columns
+    } else {
+        0
+    };
+    match (cfg.max_width, hint) {
+        (0, h) => h,
+        (m, 0) => m,
+        (m, h) => m.min(h),
+    }
+}
+
+/// Visible width in terminal columns (ANSI escapes excluded, wide
+/// characters such as CJK and emoji count two).
+fn visible_width(s: &str) -> usize {
+    UnicodeWidthStr::width(strip_ansi(s).as_str())
+}
+
+/// Drop trailing (lowest-priority) segments until the joined line fits in
+/// `max` columns, marking the cut with `more`. A single remaining segment
+/// that is still too wide is truncated.
+fn fit_width(parts: &mut Vec<String>, sep: &str, more: &str, max: usize) {
+    let sep_w = visible_width(sep);
+    let total = |parts: &[String]| {
+        parts.iter().map(|p| visible_width(p)).sum::<usize>()
+            + sep_w * parts.len().saturating_sub(1)
+    };
+    if total(parts) <= max {
+        return;
+    }
+    let ellipsis = format!("{}{}{}", DIM, more, RST);
+    while parts.len() > 1 {
+        parts.pop();
+        parts.push(ellipsis.clone());
+        if total(parts) <= max {
+            return;
+        }
+        parts.pop();
+    }
+    if let Some(first) = parts.first_mut() {
+        *first = truncate_ansi(first, max.saturating_sub(visible_width(more))) + more;
+    }
+}
+
+/// Keep the first `cols` visible columns, preserving escapes, then reset.
+/// A wide character that would straddle the limit is dropped.
+fn truncate_ansi(s: &str, cols: usize) -> String {
+    let mut out = String::new();
+    let mut shown = 0;
+    let mut i = 0;
+    while let Some(c) = s[i..].chars().next() {
+        if let Some(n) = escape_len(&s.as_bytes()[i..]) {
+            out.push_str(&s[i..i + n]);
+            i += n;
+            continue;
+        }
+        let w = UnicodeWidthChar::width(c).unwrap_or(0);
+        if shown + w <= cols {
+            out.push(c);
+            shown += w;
+        } else {
+            shown = cols;
+        }
+        i += c.len_utf8();
+    }
+    out + RST
+}
+
+/// Byte length of the escape sequence at the start of `b`, if there is one.
+/// CSI runs to its final byte; OSC (hyperlinks), DCS (sixel) and APC (kitty
+/// images) run to the ESC \ or BEL terminator.
+fn escape_len(b: &[u8]) -> Option<usize> {
+    if b.first() != Some(&0x1b) {
+        return None;
+    }
+    match b.get(1)? {
+        b'[' => Some(
+            b[2..]
+                .iter()
+                .position(|c| (0x40..=0x7e).contains(c))
+                .map_or(b.len(), |p| p + 3),
+        ),
+        b']' | b'P' | b'_' => Some(
+            (2..b.len())
+                .find_map(|i| match b[i] {
+                    0x07 => Some(i + 1),
+                    0x1b if b.get(i + 1) == Some(&b'\\') => Some(i + 2),
+                    _ => None,
+                })
+                .unwrap_or(b.len()),
+        ),
+        _ => None,
     }
 }
 
 /// Strip ANSI escape sequences manually (no regex dep).
-fn strip_ansi(s: &str) -> String {
+pub fn strip_ansi(s: &str) -> String {
     let bytes = s.as_bytes();
     let mut result = Vec::with_capacity(bytes.len());
     let mut i = 0;
     while i < bytes.len() {
-        if bytes[i] == 0x1b && i + 1 < bytes.len() && bytes[i + 1] == b'[' {
-            // Skip until 'm'
-            i += 2;
-            while i < bytes.len() && bytes[i] != b'm' {
+        match escape_len(&bytes[i..]) {
+            Some(n) => i += n,
+            None => {
+                result.push(bytes[i]);
                 i += 1;
             }
-            if i < bytes.len() {
-                i += 1; // skip 'm'
-            }
-        } else {
-            result.push(bytes[i]);
-            i += 1;
         }
     }
     String::from_utf8(result).unwrap_or_default()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_width_drops_trailing_segments() {
+        let sep = " | ";
+        let mut parts = vec![
+            "Opus".to_string(),
+            format!("{}42%{}", GREEN, RST),
+            "main".into(),
+        ];
+        fit_width(&mut parts, sep, "\u{2026}", 14);
+        assert_eq!(strip_ansi(&parts.join(sep)), "Opus | 42% | \u{2026}");
+        fit_width(&mut parts, sep, "\u{2026}", 10);
+        assert_eq!(strip_ansi(&parts.join(sep)), "Opus | \u{2026}");
+        let mut parts = vec!["Opus 4.6".to_string()];
+        fit_width(&mut parts, sep, "\u{2026}", 5);
+        assert_eq!(strip_ansi(&parts[0]), "Opus\u{2026}");
+    }
+
+    #[test]
+    fn fit_width_counts_wide_characters_twice() {
+        let sep = " | ";
+        let mut parts = vec!["Opus".to_string(), "\u{65e5}\u{672c}\u{8a9e}".to_string()];
+        assert_eq!(visible_width(&parts[1]), 6);
+        fit_width(&mut parts, sep, "\u{2026}", 12);
+        assert_eq!(strip_ansi(&parts.join(sep)), "Opus | \u{2026}");
+        let mut parts = vec!["\u{65e5}\u{672c}\u{8a9e}".to_string()];
+        fit_width(&mut parts, sep, "\u{2026}", 4);
+        assert_eq!(strip_ansi(&parts[0]), "\u{65e5}\u{2026}");
+    }
+
+    #[test]
+    fn hyperlinks_take_no_columns() {
+        let link = hyperlink("https://jira.example/browse/ABC-12", "ABC-12");
+        assert_eq!(strip_ansi(&format!("{} | main", link)), "ABC-12 | main");
+        assert_eq!(visible_width(&link), 6);
+        assert_eq!(strip_ansi(&truncate_ansi(&link, 3)), "ABC");
+    }
+
+    #[test]
+    fn image_escapes_take_no_columns() {
+        // Kitty graphics (APC) and sixel (DCS) payloads around a label
+        let kitty = "\x1b_Gf=100,a=T;iVBORw0KGgo=\x1b\\";
+        let sixel = "\x1bPq#0;2;0;0;0~-\x1b\\";
+        let line = format!("{}ctx{} 62%", kitty, sixel);
+        assert_eq!(strip_ansi(&line), "ctx 62%");
+        assert_eq!(visible_width(&line), 7);
+        let cut = truncate_ansi(&line, 3);
+        assert!(cut.starts_with(kitty) && cut.contains(sixel), "{:?}", cut);
+        assert_eq!(strip_ansi(&cut), "ctx");
+    }
+
+    #[test]
+    fn countdown_shows_days_then_hours() {
+        let cfg = crate::config::from_options(&Default::default());
+        assert_eq!(
+            strip_ansi(&countdown(&cfg, 2 * 86_400 + 4 * 3600)),
+            "\u{23f3} 2d4h"
+        );
+        assert_eq!(strip_ansi(&countdown(&cfg, 3 * 3600 + 60)), "\u{23f3} 3h1m");
+        assert_eq!(strip_ansi(&countdown(&cfg, -15 * 60)), "\u{23f3} -15m");
+    }
+
+    #[test]
+    fn mirror_reverses_segments_and_glyphs() {
+        let mut parts = vec![
+            "Opus \u{2192} Sonnet".to_string(),
+            "main \u{2191}2 \u{2193}13".to_string(),
+            "\u{2593}\u{2593}\u{2591} 62%".to_string(),
+        ];
+        mirror(&mut parts);
+        assert_eq!(
+            parts,
+            [
+                "\u{2591}\u{2593}\u{2593} 62%",
+                "main 2\u{2191} 13\u{2193}",
+                "Opus \u{2190} Sonnet"
+            ]
+        );
+    }
+
+    #[test]
+    fn projection_extends_burn_rate_to_workday_end() {
+        let mut sess = Session::default();
+        sess.cost.total_cost_usd = 10.0;
+        sess.cost.total_duration_ms = 3_600_000.0;
+        let cfg = crate::config::from_options(&Default::default());
+        assert_eq!(
+            strip_ansi(&projection(&cfg, &sess, 2 * 3600, 18 * 60).unwrap()),
+            "\u{2192}$30 by 18:00"
+        );
+        assert!(projection(&cfg, &sess, 0, 18 * 60).is_none());
+        sess.cost.total_duration_ms = 30_000.0;
+        assert!(projection(&cfg, &sess, 3600, 18 * 60).is_none());
+    }
+
+    #[test]
+    fn line_width_uses_terminal_hint_when_enabled() {
+        let mut cfg = crate::config::load(&[]);
+        let mut sess = Session::default();
+        sess.terminal.columns = 100;
+        cfg.max_width = 120;
+        cfg.fit_terminal = false;
+        assert_eq!(line_width(&sess, &cfg), 120);
+        cfg.fit_terminal = true;
+        assert_eq!(line_width(&sess, &cfg), 100);
+        cfg.max_width = 0;
+        assert_eq!(line_width(&sess, &cfg), 100);
+    }
+
+    #[test]
+    fn renders_from_mock_sources() {
+        use crate::cache::{CumulativeStats, ModelStats};
+        use crate::git::GitState;
+        use crate::sources::{MemCache, MockVcs};
+
+        let mut cfg = crate::config::load(&[]);
+        cfg.layout = String::new();
+        cfg.output = String::new();
+        cfg.mood = false;
+        cfg.ci = false;
+        let mut sess = Session {
+            transcript_path: "/p/abc.jsonl".into(),
+            ..Default::default()
+        };
+        sess.model.display_name = "Claude Opus 4.6".into();
+        sess.context_window.used_percentage = 42.0;
+        sess.cost.total_cost_usd = 8.42;
+        sess.workspace.project_dir = "/p".into();
+        let vcs = MockVcs(Some(GitState {
+            branch: "feature/auth".into(),
+            dirty: true,
+            ahead: 2,
+            ..Default::default()
+        }));
+        let cache = MemCache {
+            models: Some(ModelStats {
+                opus_in: 1_000,
+                opus_out: 52_000,
+                sonnet_out: 26_000,
+                ..Default::default()
+            }),
+            project: Some(CumulativeStats {
+                d1: 1.0,
+                d7: 12.0,
+                d30: 40.0,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let src = Sources {
+            vcs: &vcs,
+            cache: &cache,
+        };
+        assert_eq!(
+            strip_ansi(&render_with(&sess, &cfg, &src).0),
+            "Opus 4.6 \u{2588}\u{2584}\u{b7} \u{2502} \u{2593}\u{2593}\u{2593}\u{2593}\u{2591}\u{2591}\u{2591}\u{2591}\u{2591}\u{2591} 42% \u{2502} $8.4 \u{2502} 0m \u{2502} \u{2605}auth \u{25cf} \u{2191}2\n\
+             O:1.0k/52k S:0/26k \u{2502} \u{2302} $1.0/$12/$40\n"
+        );
+
+        let empty = MemCache::default();
+        let src = Sources {
+            vcs: &MockVcs(None),
+            cache: &empty,
+        };
+        assert_eq!(
+            strip_ansi(&render_with(&sess, &cfg, &src).0),
+            "Opus 4.6 \u{2502} \u{2593}\u{2593}\u{2593}\u{2593}\u{2591}\u{2591}\u{2591}\u{2591}\u{2591}\u{2591} 42% \u{2502} $8.4 \u{2502} 0m\nin:0 out:0\n"
+        );
+
+        cfg.line2_auto = true;
+        assert!(render_with(&sess, &cfg, &src).0.ends_with(" 0m\n\n"));
+    }
+
+    #[test]
+    fn local_files_are_read_through_sources() {
+        use crate::sources::{MemCache, MockVcs};
+
+        let mut cfg = crate::config::load(&[]);
+        cfg.output = String::new();
+        cfg.quiet_hours = String::new();
+        cfg.awaiting_secs = 60;
+        cfg.plan_tokens = 1000;
+        cfg.break_after = 30;
+        cfg.account = true;
+        let mut sess = Session::default();
+        sess.model.display_name = "Claude Opus 4.6".into();
+        sess.transcript_path = "/nonexistent/projects/-p/abc.jsonl".into();
+        let now = crate::clock::now_unix();
+        let cache = MemCache {
+            deadline: Some(now + 3 * 86_400),
+            pending: Some(("Bash".into(), now - 120)),
+            tokens: 500,
+            active_since: Some(now - 3600),
+            account: Some("bedrock"),
+            ..Default::default()
+        };
+        let src = Sources {
+            vcs: &MockVcs(None),
+            cache: &cache,
+        };
+        let out = strip_ansi(&render_with(&sess, &cfg, &src).0);
+        for part in [
+            "\u{23f3}awaiting approval Bash 2m",
+            "Opus 4.6 \u{2502} bedrock",
+            "\u{2615} 1h",
+            "\u{23f3} 3d",
+            "\u{2696} 50%",
+        ] {
+            assert!(out.contains(part), "{} in {}", part, out);
+        }
+
+        let out = strip_ansi(&render_with(&sess, &cfg, &Sources::NONE).0);
+        for part in ["awaiting", "bedrock", "\u{2615}", "\u{23f3}"] {
+            assert!(!out.contains(part), "{} in {}", part, out);
+        }
+        assert!(out.contains("\u{2696} 0%"), "{}", out);
+    }
+
+    #[test]
+    fn break_reminder_waits_for_the_threshold() {
+        use crate::sources::MemCache;
+        let mut cfg = crate::config::from_options(&Default::default());
+        cfg.break_after = 30;
+        let now = crate::clock::now_unix();
+        let remind = |active_min: i64| {
+            let cache = MemCache {
+                active_since: Some(now - active_min * 60),
+                ..Default::default()
+            };
+            let src = Sources {
+                vcs: &crate::sources::NoVcs,
+                cache: &cache,
+            };
+            break_reminder(&cfg, &src).map(|s| strip_ansi(&s))
+        };
+        assert_eq!(remind(29), None);
+        assert_eq!(remind(45).as_deref(), Some("\u{2615} 45m"));
+        assert_eq!(remind(92).as_deref(), Some("\u{2615} 1h32m"));
+    }
+
+    #[test]
+    fn signature_badge_does_not_claim_verification() {
+        use crate::git::GitState;
+        use crate::sources::{MemCache, MockVcs};
+
+        let mut cfg = crate::config::from_options(&Default::default());
+        cfg.signed = true;
+        let cache = MemCache::default();
+        let badge = |signature: &str| {
+            let vcs = MockVcs(Some(GitState {
+                branch: "feat".into(),
+                signature: signature.into(),
+                ..Default::default()
+            }));
+            let src = Sources {
+                vcs: &vcs,
+                cache: &cache,
+            };
+            git_segment(&cfg, "", &src).0
+        };
+        assert_eq!(
+            badge("ssh"),
+            format!("{}feat{} {}signed:ssh{}", MAGENTA, RST, DIM, RST)
+        );
+        assert!(badge("").ends_with(&format!("{}unsigned{}", RED, RST)));
+    }
+
+    #[test]
+    fn commits_today_follow_the_branch() {
+        use crate::git::GitState;
+        use crate::sources::{MemCache, MockVcs};
+
+        let cfg = crate::config::from_options(&Default::default());
+        let cache = MemCache::default();
+        let segment = |commits_today: i32| {
+            let vcs = MockVcs(Some(GitState {
+                branch: "feat".into(),
+                commits_today,
+                ..Default::default()
+            }));
+            let src = Sources {
+                vcs: &vcs,
+                cache: &cache,
+            };
+            strip_ansi(&git_segment(&cfg, "", &src).0)
+        };
+        assert_eq!(segment(3), "feat \u{2713}3");
+        assert_eq!(segment(0), "feat");
+    }
+
+    #[test]
+    fn swapped_and_dense_layouts() {
+        use crate::sources::{MemCache, MockVcs};
+
+        let mut cfg = crate::config::load(&[]);
+        cfg.output = String::new();
+        cfg.mood = false;
+        let mut sess = Session::default();
+        sess.model.display_name = "Claude Opus 4.6".into();
+        sess.context_window.total_input_tokens = 1_000.0;
+        let cache = MemCache::default();
+        let src = Sources {
+            vcs: &MockVcs(None),
+            cache: &cache,
+        };
+        cfg.layout = "swapped".into();
+        let out = strip_ansi(&render_with(&sess, &cfg, &src).0);
+        assert!(out.starts_with("in:1.0k out:0\nOpus 4.6 "), "{}", out);
+        cfg.layout = "dense".into();
+        let out = strip_ansi(&render_with(&sess, &cfg, &src).0);
+        assert!(out.ends_with(" \u{2502} in:1.0k out:0\n\n"), "{}", out);
+    }
+
+    #[test]
+    fn disabled_segments_read_nothing() {
+        use crate::sources::Recorder;
+
+        let reads = |sess: &Session, cfg: &Config| {
+            let rec = Recorder::default();
+            let src = Sources {
+                vcs: &rec,
+                cache: &rec,
+            };
+            render_with(sess, cfg, &src);
+            rec.calls.take()
+        };
+        let mut cfg = crate::config::from_options(&Default::default());
+        cfg.show_git = false;
+        cfg.show_model_bars = false;
+        cfg.show_tokens = false;
+        cfg.show_cumulative = false;
+        let mut sess = Session {
+            transcript_path: "/nonexistent/projects/-p/abc.jsonl".into(),
+            ..Default::default()
+        };
+        sess.workspace.project_dir = "/nonexistent/p".into();
+        // Deadline and pomodoro have no setting: the file `statusline
+        // deadline` or `pomo` writes is their switch, so they always look
+        assert_eq!(reads(&sess, &cfg), ["deadline", "pomodoro"]);
+        cfg.show_model_bars = true;
+        assert!(reads(&sess, &cfg).contains(&"models"));
+        cfg.show_model_bars = false;
+        cfg.show_cumulative = true;
+        assert!(reads(&sess, &cfg).contains(&"cumulative"));
+        cfg.show_cumulative = false;
+        cfg.show_git = true;
+        assert!(reads(&sess, &cfg).contains(&"vcs"));
+    }
+
+    #[test]
+    fn when_zero_hides_or_dims_listed_segments() {
+        let mut cfg = crate::config::load(&[]);
+        cfg.hide_when_zero = "cost, context=dim".into();
+        let cost = format!("{}$0.00{}", GREEN, RST);
+        assert_eq!(when_zero(&cfg, "cost", true, cost.clone()), None);
+        assert_eq!(when_zero(&cfg, "cost", false, cost.clone()), Some(cost));
+        assert_eq!(
+            when_zero(&cfg, "context", true, format!("{}0%{}", GREEN, RST)),
+            Some(format!("{}0%{}", DIM, RST))
+        );
+        assert_eq!(
+            when_zero(&cfg, "tokens", true, "in:0".into()),
+            Some("in:0".into())
+        );
+    }
+
+    #[test]
+    fn plan_pace_compares_with_even_burn() {
+        let mut cfg = crate::config::from_options(&Default::default());
+        cfg.plan_tokens = 1000;
+        assert_eq!(
+            strip_ansi(&plan_pace(&cfg, 380, 0.32)),
+            "\u{2696} 38% \u{25b2}6%"
+        );
+        assert_eq!(
+            strip_ansi(&plan_pace(&cfg, 310, 0.34)),
+            "\u{2696} 31% \u{25bc}3%"
+        );
+    }
+
+    #[test]
+    fn ascii_symbols_replace_timer_and_pace_glyphs() {
+        let mut cfg = crate::config::from_options(&Default::default());
+        cfg.symbols = "ascii".into();
+        cfg.plan_tokens = 1000;
+        assert_eq!(strip_ansi(&plan_pace(&cfg, 380, 0.32)), "plan 38% +6%");
+        assert_eq!(strip_ansi(&countdown(&cfg, 3 * 3600 + 60)), "due 3h1m");
+        let mut parts = vec!["Opus".to_string(), "main".into()];
+        fit_width(&mut parts, " ", &symbol(&cfg, "more"), 8);
+        assert_eq!(strip_ansi(&parts.join(" ")), "Opus ...");
+    }
+
+    #[test]
+    fn session_start_shows_clock_or_age() {
+        let mut cfg = crate::config::from_options(&Default::default());
+        cfg.session_start = "ago".into();
+        assert_eq!(
+            strip_ansi(&session_start(&cfg, 1000, 1000 + 7500)),
+            "started 2h5m ago"
+        );
+        cfg.lang = "de".into();
+        assert_eq!(
+            strip_ansi(&session_start(&cfg, 1000, 1000 + 60)),
+            "gestartet vor 1m"
+        );
+    }
+
+    #[test]
+    fn pomodoro_shows_minutes_and_seconds() {
+        use crate::pomodoro::Phase;
+        let cfg = crate::config::from_options(&Default::default());
+        assert_eq!(
+            strip_ansi(&pomodoro(&cfg, Phase::Work, 1052)),
+            "\u{1f345} 17:32"
+        );
+        assert_eq!(
+            strip_ansi(&pomodoro(&cfg, Phase::Break, 192)),
+            "\u{1f345} break 3:12"
+        );
+    }
+
+    #[test]
+    fn spoken_values_use_words() {
+        assert_eq!(spoken_cost(4.2), "4 dollars 20 cents");
+        assert_eq!(spoken_cost(1.0), "1 dollar");
+        assert_eq!(spoken_cost(0.05), "5 cents");
+        assert_eq!(spoken_duration(65 * 60_000), "1 hour 5 minutes");
+        assert_eq!(spoken_duration(60_000), "1 minute");
+    }
+
+    #[test]
+    fn work_split_shows_on_and_off_cost() {
+        assert_eq!(
+            strip_ansi(&work_split(
+                20.0,
+                6.5,
+                &format::Precision::default(),
+                "\u{25f7}"
+            )),
+            " \u{25f7}$20/$6.5"
+        );
+    }
+
+    #[test]
+    fn uncached_cost_follows_session_cost() {
+        use crate::cache::ModelStats;
+        use crate::sources::{MemCache, MockVcs};
+
+        let mut cfg = crate::config::load(&[]);
+        cfg.layout = String::new();
+        cfg.output = String::new();
+        cfg.line2 = false;
+        cfg.uncached_cost = true;
+        let mut sess = Session {
+            transcript_path: "/p/abc.jsonl".into(),
+            ..Default::default()
+        };
+        sess.cost.total_cost_usd = 8.42;
+        let cache = MemCache {
+            models: Some(ModelStats {
+                uncached_cost: 31.0,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let src = Sources {
+            vcs: &MockVcs(None),
+            cache: &cache,
+        };
+        let out = strip_ansi(&render_with(&sess, &cfg, &src).0);
+        assert!(out.contains("$8.4 ($31 uncached)"), "{}", out);
+
+        cfg.uncached_cost = false;
+        let out = strip_ansi(&render_with(&sess, &cfg, &src).0);
+        assert!(!out.contains("uncached"), "{}", out);
+    }
+
+    #[test]
+    fn cumulative_format_and_scope() {
+        use crate::cache::CumulativeStats;
+        use crate::sources::{MemCache, MockVcs};
+
+        let stats = CumulativeStats {
+            d1: 4.2,
+            d7: 31.0,
+            d30: 187.0,
+            d30_split: Some((150.0, 37.0)),
+            ..Default::default()
+        };
+        let mut cfg = crate::config::load(&[]);
+        cfg.cumulative_format = "labeled".into();
+        assert_eq!(
+            strip_ansi(&cumulative_periods(&stats, &cfg)),
+            "day $4.2 wk $31 mo $187"
+        );
+        cfg.cumulative_format = "today".into();
+        assert_eq!(strip_ansi(&cumulative_periods(&stats, &cfg)), "$4.2 today");
+
+        cfg.layout = String::new();
+        cfg.output = String::new();
+        cfg.cumulative_scope = "global".into();
+        let cache = MemCache {
+            project: Some(stats.clone()),
+            all: Some(stats),
+            ..Default::default()
+        };
+        let src = Sources {
+            vcs: &MockVcs(None),
+            cache: &cache,
+        };
+        let out = strip_ansi(&render_with(&Session::default(), &cfg, &src).0);
+        let line2 = out.lines().nth(1).unwrap();
+        assert!(line2.ends_with("\u{03a3} $4.2 today"), "{}", line2);
+        assert!(!line2.contains('\u{2302}'), "{}", line2);
+    }
+
+    #[test]
+    fn cumulative_unit_picks_cost_tokens_or_both() {
+        let stats = cache::CumulativeStats {
+            d1: 4.2,
+            d7: 31.0,
+            d30: 187.0,
+            tokens: [1_200_000, 8_000_000, 40_000_000],
+            ..Default::default()
+        };
+        let mut cfg = crate::config::load(&[]);
+        assert_eq!(cumulative_periods(&stats, &cfg), "$4.2/$31/$187");
+        cfg.cumulative_unit = "tokens".into();
+        assert_eq!(cumulative_periods(&stats, &cfg), "1.2M/8.0M/40.0M");
+        cfg.cumulative_unit = "both".into();
+        assert_eq!(
+            strip_ansi(&cumulative_periods(&stats, &cfg)),
+            "$4.2/$31/$187 1.2M/8.0M/40.0M"
+        );
+    }
+}
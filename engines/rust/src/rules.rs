@@ -0,0 +1,198 @@
+//! Conditional styling rules (STATUSLINE_RULES).
+//!
+//! Rules are separated by `;`, each of the form
+//! `when <value> <op> <literal> then <segment>.<color|style> = <name>`, e.g.
+//! `when cost > 20 then cost.color = red; when branch == "main" then git.style = bold`.
+//! Values: model, context, cost, duration (minutes), branch, added, removed.
+//! Invalid rules are ignored; the render path never fails on config.
+
+const RST: &str = "\x1b[0m";
+
+#[derive(Debug, PartialEq)]
+enum Lit {
+    Num(f64),
+    Str(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Rule {
+    var: String,
+    op: String,
+    lit: Lit,
+    segment: String,
+    prop: String,
+    code: &'static str,
+}
+
+/// Segment values the rules can test.
+#[derive(Default, serde::Serialize)]
+pub struct Values {
+    pub model: String,
+    pub context: f64,
+    pub cost: f64,
+    pub duration: f64,
+    pub branch: String,
+    pub added: f64,
+    pub removed: f64,
+}
+
+impl Values {
+    fn get(&self, var: &str) -> Option<Lit> {
+        Some(match var {
+            "model" => Lit::Str(self.model.clone()),
+            "branch" => Lit::Str(self.branch.clone()),
+            "context" => Lit::Num(self.context),
+            "cost" => Lit::Num(self.cost),
+            "duration" => Lit::Num(self.duration),
+            "added" => Lit::Num(self.added),
+            "removed" => Lit::Num(self.removed),
+            _ => return None,
+        })
+    }
+}
+
+pub fn parse(spec: &str) -> Vec<Rule> {
+    spec.split(';')
+        .filter_map(|r| parse_rule(r.trim()))
+        .collect()
+}
+
+fn parse_rule(rule: &str) -> Option<Rule> {
+    let rest = rule.strip_prefix("when ")?;
+    let (cond, action) = rest.split_once(" then ")?;
+    let mut cond_parts = cond.trim().splitn(3, ' ');
+    let var = cond_parts.next()?.to_string();
+    let op = cond_parts.next()?.to_string();
+    if !matches!(op.as_str(), ">" | "<" | ">=" | "<=" | "==" | "!=") {
+        return None;
+    }
+    let raw = cond_parts.next()?.trim();
+    let lit = match raw.strip_prefix('"').and_then(|r| r.strip_suffix('"')) {
+        Some(s) => Lit::Str(s.to_string()),
+        None => Lit::Num(raw.parse().ok()?),
+    };
+    let (target, value) = action.split_once('=')?;
+    let (segment, prop) = target.trim().split_once('.')?;
+    let value = value.trim().trim_matches('"');
+    let code = match prop {
+        "color" => color_code(value)?,
+        "style" => style_code(value)?,
+        _ => return None,
+    };
+    Some(Rule {
+        var,
+        op,
+        lit,
+        segment: segment.to_string(),
+        prop: prop.to_string(),
+        code,
+    })
+}
+
+fn color_code(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "red" => "\x1b[31m",
+        "green" => "\x1b[32m",
+        "yellow" => "\x1b[33m",
+        "blue" => "\x1b[34m",
+        "magenta" => "\x1b[35m",
+        "cyan" => "\x1b[36m",
+        "white" => "\x1b[37m",
+        "dim" => "\x1b[2m",
+        _ => return None,
+    })
+}
+
+fn style_code(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "bold" => "\x1b[1m",
+        "dim" => "\x1b[2m",
+        "italic" => "\x1b[3m",
+        "underline" => "\x1b[4m",
+        "reverse" => "\x1b[7m",
+        _ => return None,
+    })
+}
+
+fn matches(rule: &Rule, values: &Values) -> bool {
+    let ord = match (values.get(&rule.var), &rule.lit) {
+        (Some(Lit::Num(a)), Lit::Num(b)) => a.partial_cmp(b),
+        (Some(Lit::Str(a)), Lit::Str(b)) => Some(a.as_str().cmp(b.as_str())),
+        _ => None,
+    };
+    let Some(ord) = ord else {
+        return false;
+    };
+    match rule.op.as_str() {
+        ">" => ord.is_gt(),
+        "<" => ord.is_lt(),
+        ">=" => ord.is_ge(),
+        "<=" => ord.is_le(),
+        "==" => ord.is_eq(),
+        _ => ord.is_ne(),
+    }
+}
+
+/// Restyle a rendered segment according to the matching rules. A color
+/// replaces the segment's own colors; a style is layered on top of them.
+pub fn apply(rules: &[Rule], values: &Values, segment: &str, text: String) -> String {
+    let mut text = text;
+    for rule in rules.iter().filter(|r| r.segment == segment) {
+        if text.is_empty() || !matches(rule, values) {
+            continue;
+        }
+        text = if rule.prop == "color" {
+            format!("{}{}{}", rule.code, crate::render::strip_ansi(&text), RST)
+        } else {
+            format!(
+                "{}{}{}",
+                rule.code,
+                text.replace(RST, &format!("{}{}", RST, rule.code)),
+                RST
+            )
+        };
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rules() {
+        let rules = parse(
+            r#"when cost > 20 then cost.color = red; when branch == "main" then git.style = bold; bogus"#,
+        );
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].lit, Lit::Num(20.0));
+        assert_eq!(rules[1].lit, Lit::Str("main".into()));
+        assert!(parse("when cost ~ 1 then cost.color = red").is_empty());
+        assert!(parse("when cost > 1 then cost.color = mauve").is_empty());
+    }
+
+    #[test]
+    fn apply_color_and_style() {
+        let rules = parse(
+            r#"when cost >= 20 then cost.color = red; when branch == "main" then git.style = bold"#,
+        );
+        let vals = Values {
+            cost: 25.0,
+            branch: "main".into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            apply(&rules, &vals, "cost", "$25".into()),
+            "\x1b[31m$25\x1b[0m"
+        );
+        assert_eq!(
+            apply(&rules, &vals, "git", "\x1b[35mmain\x1b[0m".into()),
+            "\x1b[1m\x1b[35mmain\x1b[0m\x1b[1m\x1b[0m"
+        );
+        let cheap = Values {
+            cost: 5.0,
+            ..Default::default()
+        };
+        assert_eq!(apply(&rules, &cheap, "cost", "$5".into()), "$5");
+    }
+}
@@ -0,0 +1,285 @@
+//! `statusline config schema`: JSON Schema for ~/.claude/statusline.env.
+//!
+//! The env file is flat `KEY=value` text, so the schema describes an object
+//! of string values. Tools that edit the config (the init wizard, editors,
+//! web playgrounds) can use it for completion and validation.
+//!
+//! `statusline config explain KEY` shows how a key's value was resolved.
+
+use crate::config;
+use crate::segments::SEGMENTS;
+use serde_json::{json, Map, Value};
+use std::env;
+
+enum Kind {
+    /// On by default, disabled with "false"
+    Toggle,
+    /// Off by default, enabled with "true" or "1"
+    OptIn,
+    Uint(u64),
+    Float,
+    /// The first value is the default
+    Choice(&'static [&'static str]),
+    /// Free text with its default
+    Text(&'static str),
+}
+
+/// Options that are not segment toggles (those come from SEGMENTS).
+const OPTIONS: &[(&str, Kind, &str)] = &[
+    ("STATUSLINE_LINE2", Kind::Choice(&["true", "false", "auto"]), "Show the second line; auto leaves it out until it has data (per-model tokens, session tokens, speed or cumulative costs)"),
+    ("STATUSLINE_OFFLINE", Kind::OptIn, "Never touch the network"),
+    ("STATUSLINE_NO_SPAWN", Kind::OptIn, "No background processes; refresh caches inline"),
+    ("STATUSLINE_STALE_SECS", Kind::Uint(0), "Dim cache values older than this many seconds (0 = off)"),
+    ("STATUSLINE_TRACE", Kind::OptIn, "Write spans to <cache dir>/trace.json"),
+    ("STATUSLINE_SPARKLINE", Kind::OptIn, "Context-history sparkline after the context bar"),
+    ("STATUSLINE_GRAPHICS", Kind::Choice(&["off", "auto", "kitty", "sixel"]), "Draw the sparkline as an inline image (feature `graphics`)"),
+    ("STATUSLINE_OUTPUT", Kind::Choice(&["ansi", "zsh-prompt", "bash-prompt", "zellij", "wezterm", "lualine", "plain-verbose"]), "Output adapter"),
+    ("STATUSLINE_WRITE_TO", Kind::Text(""), "Also write each render to this file or FIFO"),
+    ("STATUSLINE_RENDER_CACHE", Kind::OptIn, "Replay the last output when all inputs are unchanged"),
+    ("STATUSLINE_MAX_WIDTH", Kind::Uint(0), "Cap each line at this many columns (0 = off)"),
+    ("STATUSLINE_FIT_TERMINAL", Kind::OptIn, "Cap each line at the terminal width sent in the session JSON (terminal.columns)"),
+    ("STATUSLINE_LAYOUT", Kind::Choice(&["default", "classic", "swapped", "dense", "minimal"]), "Built-in layout: default (classic), swapped (tokens and cumulative on line 1), dense (one line) or minimal"),
+    ("STATUSLINE_MOOD", Kind::OptIn, "Leading session health glyph"),
+    ("STATUSLINE_BUDGET", Kind::Float, "Session budget in USD for the mood glyph (0 = off)"),
+    ("STATUSLINE_RULES", Kind::Text(""), "Conditional styling rules: when <value> <op> <literal> then <segment>.<color|style> = <name>; ..."),
+    ("STATUSLINE_POST_HOOK", Kind::Text(""), "Script whose stdout replaces the output"),
+    ("STATUSLINE_TRANSCRIPT_ROOTS", Kind::Text(""), "Colon-separated Claude config dirs whose projects/ transcripts are scanned (default: $CLAUDE_CONFIG_DIR or ~/.claude)"),
+    ("STATUSLINE_MODEL_ALIASES", Kind::Text(""), "Map gateway model names to Claude ids: from=to, comma-separated; a trailing * in from matches by prefix"),
+    ("STATUSLINE_ACCOUNT", Kind::OptIn, "Show the Claude account (organization, API key or cloud provider)"),
+    ("STATUSLINE_ACCOUNT_EXPECT", Kind::Text(""), "Warn in red when the account does not match: dir=expected, comma-separated, e.g. ~/work=acme.com"),
+    ("STATUSLINE_WORK_HOURS", Kind::Text(""), "Split cumulative costs into on/off hours: cron hour and weekday fields, e.g. \"9-17 1-5\""),
+    ("STATUSLINE_HOLIDAYS", Kind::Text(""), "Comma-separated YYYY-MM-DD dates counted as off-hours"),
+    ("STATUSLINE_HISTORY", Kind::OptIn, "Record cost snapshots with the git branch for `statusline stats`"),
+    ("STATUSLINE_DIGEST", Kind::OptIn, "Write a markdown digest of each day's sessions and costs (records history)"),
+    ("STATUSLINE_BREAK_AFTER", Kind::Uint(0), "Break reminder after this many minutes of continuous activity (0 = off)"),
+    ("STATUSLINE_BELL", Kind::Text(""), "Ring (bell) or flash the terminal on events: context (turns critical), error (background job error), e.g. context=bell,error=flash"),
+    ("STATUSLINE_QUIET_HOURS", Kind::Text(""), "Local-time windows without attention cues (warnings, break reminder, bells, webhooks), e.g. 22:00-07:00,12-13"),
+    ("STATUSLINE_LANG", Kind::Text("en"), "Language for textual labels (in/out, stash, tok/s, break): en, de, fr, es, ru or ja, also as a locale such as de_DE.UTF-8 or de-AT; others fall back to en"),
+    ("STATUSLINE_LABELS", Kind::Text(""), "Per-label overrides, e.g. in=\u{2193},out=\u{2191},stash=s"),
+    ("STATUSLINE_RTL", Kind::OptIn, "Right-to-left mode: reverse segment order and mirror directional glyphs"),
+    ("STATUSLINE_COST_CENTS", Kind::OptIn, "Always show costs with cents ($14.20) instead of adaptive precision"),
+    ("STATUSLINE_PCT_DECIMALS", Kind::Uint(0), "Decimals for the context percentage"),
+    ("STATUSLINE_TOKEN_WIDTH", Kind::Uint(3), "Token counts with up to this many digits are shown exactly"),
+    ("STATUSLINE_TOKENS_EXACT", Kind::OptIn, "Full comma-grouped token counts (1,234,567) instead of 1.2M"),
+    ("STATUSLINE_DURATION_SECONDS", Kind::Uint(0), "Show seconds (42s, 1m20s) for sessions shorter than this many minutes (0 = off)"),
+    ("STATUSLINE_SESSION_START", Kind::Choice(&["off", "clock", "ago"]), "Show when the session started: since 09:14 (clock) or started 2h ago (ago)"),
+    ("STATUSLINE_WORKDAY_END", Kind::Text(""), "Project the session cost to this local time at the current burn rate, e.g. 18:00"),
+    ("STATUSLINE_PLAN_TOKENS", Kind::Uint(0), "Weekly token allowance; shows usage and pace against an even burn (0 = off)"),
+    ("STATUSLINE_WORKTREE_PATTERN", Kind::Text("/.worktrees/"), "Comma-separated path markers; a linked worktree is named by the path after the first match (default /.worktrees/), else by its git dir"),
+    ("STATUSLINE_DEFAULT_BRANCH", Kind::Text(""), "Ahead/behind against this branch when there is no upstream, e.g. origin/main; auto = origin/HEAD"),
+    ("STATUSLINE_GIT_WALK_LIMIT", Kind::Uint(1000), "Stop counting ahead/behind commits here and show e.g. 1k+ (0 = no limit)"),
+    ("STATUSLINE_DIRTY_SCOPE", Kind::Choice(&["repo", "project"]), "Where the dirty marker looks for changes: the whole repository or only the session's project dir"),
+    ("STATUSLINE_DIRTY_IGNORE", Kind::Text(""), "Comma-separated gitignore-style patterns whose changes do not count as dirty, e.g. node_modules/,.claude/,*.lock"),
+    ("STATUSLINE_UNTRACKED", Kind::Choice(&["git", "auto", "no"]), "Untracked files in the dirty check: as status.showUntrackedFiles says (git), skipped in repos with over 100k files (auto), or never (no)"),
+    ("STATUSLINE_PROTECTED_BRANCHES", Kind::Text("main,master"), "Highlight the branch in red when it matches one of these comma-separated names or globs, e.g. main,master,release/*; empty turns it off"),
+    ("STATUSLINE_COMMITS_TODAY", Kind::OptIn, "Your commits on this branch since local midnight in the git segment (\u{2713}3)"),
+    ("STATUSLINE_STASH_DETAIL", Kind::Choice(&["off", "age", "message"]), "After the stash count, the age (stash:2 (3d)) or message of the most recent stash"),
+    ("STATUSLINE_SIGNED", Kind::OptIn, "Badge for whether HEAD carries a signature (not verified): signed:gpg, signed:ssh, signed:x509 or a red unsigned"),
+    ("STATUSLINE_LARGE_FILE_MB", Kind::Uint(0), "Warn about staged files larger than this many MB that are not in HEAD yet (0 = off)"),
+    ("STATUSLINE_LFS_POINTERS", Kind::OptIn, "Warn about Git LFS files that are still pointers (not pulled)"),
+    ("STATUSLINE_CI", Kind::OptIn, "CI status of HEAD next to the branch (CI \u{2713}/\u{2717}/\u{25cf}), fetched in the background from GitHub or GitLab"),
+    ("STATUSLINE_CI_TTL", Kind::Uint(300), "Seconds before the CI status is fetched again"),
+    ("STATUSLINE_TICKET", Kind::OptIn, "Ticket id from the branch name (ABC-123, #456) as its own segment"),
+    ("STATUSLINE_TICKET_PATTERNS", Kind::Text("[A-Z][A-Z0-9]+-[0-9]+ #([0-9]+)"), "Space-separated regexes for ticket ids, first match wins; group 1, if any, is the id in the URL (default: [A-Z][A-Z0-9]+-[0-9]+ #([0-9]+))"),
+    ("STATUSLINE_TICKET_URL", Kind::Text(""), "Link the ticket id to this URL, {id} replaced (OSC 8 hyperlink)"),
+    ("STATUSLINE_BRANCH_ICONS", Kind::Text(""), "Branch prefix icons: prefix=icon, comma-separated, added to the built-ins (* matches one path component, an empty icon keeps the prefix); off disables"),
+    ("STATUSLINE_BRANCH_TRUNCATE", Kind::Choice(&["tail", "head", "middle", "smart"]), "How long branch names are cut: tail (feat/lon\u{2026}), head (\u{2026}ng-name), middle (feat\u{2026}name) or smart (prefix icon and last path component)"),
+    ("STATUSLINE_FILES", Kind::OptIn, "Count of files edited in the session (\u{270e}12 files); list them with `statusline files`"),
+    ("STATUSLINE_AWAITING_SECS", Kind::Uint(0), "Flag a tool call without a result after this many seconds (\u{23f3}awaiting approval: a permission prompt or a long-running tool; 0 = off)"),
+    ("STATUSLINE_HIDE_WHEN_ZERO", Kind::Text(""), "Segments to hide while they show zero, comma-separated; name=dim dims instead (context, cost, duration, tokens, cumulative)"),
+    ("STATUSLINE_WARM_START_KB", Kind::Uint(256), "First render of a large transcript: parse this many KB from its end inline so line 2 has token data (0 = off)"),
+    ("STATUSLINE_DAY_BOUNDARY", Kind::Choice(config::DAY_BOUNDARIES), "Cumulative periods: rolling 24h/7d/30d windows, the last 1/7/30 local days from midnight (DST-aware), or calendar today/this week/this month"),
+    ("STATUSLINE_CUMULATIVE_UNIT", Kind::Choice(config::CUMULATIVE_UNITS), "Cumulative \u{2302} \u{03a3} figures: dollars, token totals, or dollars followed by tokens"),
+    ("STATUSLINE_UNCACHED_COST", Kind::OptIn, "After the session cost, what it would have cost without prompt caching ($8.4 ($31 uncached))"),
+    ("STATUSLINE_CUMULATIVE_FORMAT", Kind::Choice(config::CUMULATIVE_FORMATS), "Cumulative layout: $4.2/$31/$187 (compact), day $4.2 wk $31 mo $187 (labeled), or only $4.2 today"),
+    ("STATUSLINE_CUMULATIVE_SCOPE", Kind::Choice(config::CUMULATIVE_SCOPES), "Which cumulative totals to show: project (\u{2302}), all projects (\u{03a3}) or both"),
+    ("STATUSLINE_SYMBOLS", Kind::Text(""), "Replace fixed glyphs: ascii for plain-text stand-ins, and/or key=text pairs for project (\u{2302}), global (\u{03a3}), sep (\u{2502}) and the other keys in the README"),
+    ("STATUSLINE_PRIVACY", Kind::OptIn, "Keyed hashes instead of session ids and project paths in cache file names; paths redacted in debug bundles and digests"),
+    ("STATUSLINE_DIGEST_WEBHOOK", Kind::Text(""), "Also POST the daily digest as {\"text\": ...} to this URL"),
+    ("STATUSLINE_TELEMETRY", Kind::OptIn, "Send a daily report of the engine version and a cost bucket to STATUSLINE_TELEMETRY_URL (records history); see `statusline telemetry preview`"),
+    ("STATUSLINE_TELEMETRY_URL", Kind::Text(""), "Endpoint for STATUSLINE_TELEMETRY; nothing is sent without it"),
+];
+
+/// Command-line flags for keys that are not segment toggles: the flag and
+/// the value it sets, or None when the value is the flag's argument.
+const FLAGS: &[(&str, &str, Option<&str>)] = &[
+    ("STATUSLINE_LINE2", "--no-line2", Some("false")),
+    ("STATUSLINE_OFFLINE", "--offline", Some("true")),
+    ("STATUSLINE_NO_SPAWN", "--no-spawn", Some("true")),
+    ("STATUSLINE_OUTPUT", "--output", None),
+    ("STATUSLINE_LAYOUT", "--layout", None),
+    ("STATUSLINE_WRITE_TO", "--write-to", None),
+];
+
+fn property(kind: &Kind, description: &str) -> Value {
+    match kind {
+        Kind::Toggle => {
+            json!({"type": "string", "enum": ["true", "false"], "default": "true", "description": description})
+        }
+        Kind::OptIn => {
+            json!({"type": "string", "enum": ["true", "false", "1", "0"], "default": "false", "description": description})
+        }
+        Kind::Uint(default) => {
+            json!({"type": "string", "pattern": "^[0-9]+$", "default": default.to_string(), "description": description})
+        }
+        Kind::Float => {
+            json!({"type": "string", "pattern": "^[0-9]+(\\.[0-9]+)?$", "default": "0", "description": description})
+        }
+        Kind::Choice(values) => {
+            json!({"type": "string", "enum": values, "default": values[0], "description": description})
+        }
+        Kind::Text(default) => {
+            json!({"type": "string", "default": default, "description": description})
+        }
+    }
+}
+
+pub fn schema() -> Value {
+    let mut props = Map::new();
+    for seg in SEGMENTS {
+        props.insert(
+            seg.env_key.to_string(),
+            property(&Kind::Toggle, seg.description),
+        );
+    }
+    for (key, kind, description) in OPTIONS {
+        props.insert(key.to_string(), property(kind, description));
+    }
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "claude-code-statusline config (~/.claude/statusline.env)",
+        "description": format!("KEY=value settings for statusline {}; environment variables of the same name override the file", crate::VERSION),
+        "type": "object",
+        "properties": props,
+        "additionalProperties": false,
+    })
+}
+
+/// `statusline config explain KEY`: each layer's value, lowest precedence
+/// first (default, file, env, command line), and the one that wins.
+fn explain(key: &str, args: &[String]) -> Result<String, String> {
+    let (kind, flag) = match SEGMENTS.iter().find(|s| s.env_key == key) {
+        Some(seg) => (&Kind::Toggle, Some((seg.flag, Some("false")))),
+        None => {
+            let (_, kind, _) = OPTIONS
+                .iter()
+                .find(|(k, _, _)| *k == key)
+                .ok_or_else(|| format!("unknown key: {}", key))?;
+            let flag = FLAGS
+                .iter()
+                .find(|(k, _, _)| *k == key)
+                .map(|(_, f, v)| (*f, *v));
+            (kind, flag)
+        }
+    };
+    let default = property(kind, "")["default"]
+        .as_str()
+        .expect("every kind has a default")
+        .to_string();
+    let cli = flag.and_then(|(flag, value)| match value {
+        Some(v) => args.iter().any(|a| a == flag).then(|| v.to_string()),
+        None => config::arg_value(args, flag),
+    });
+    let layers = [
+        ("default", Some(default), String::new()),
+        (
+            "file",
+            config::file_value(key),
+            config::config_path().display().to_string(),
+        ),
+        ("env", env::var(key).ok(), String::new()),
+        (
+            "command line",
+            cli,
+            flag.map(|(f, _)| f.to_string()).unwrap_or_default(),
+        ),
+    ];
+    let (winner, value) = layers
+        .iter()
+        .rev()
+        .find_map(|(layer, v, _)| v.as_ref().map(|v| (*layer, v.clone())))
+        .unwrap_or(("default", String::new()));
+    let mut out = format!("{}={}  (from {})\n", key, value, winner);
+    let numeric = matches!(kind, Kind::Uint(_) | Kind::Float);
+    if numeric && winner != "default" && value.parse::<f64>().is_err() {
+        out.push_str("  not a number: ignored, the built-in default applies\n");
+    }
+    out.push('\n');
+    for (layer, v, note) in &layers {
+        let shown = v.as_deref().map(|v| format!("{:?}", v));
+        let line = format!(
+            "  {:<13} {:<12} {}",
+            layer,
+            shown.as_deref().unwrap_or("-"),
+            note
+        );
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    Ok(out.trim_end().to_string())
+}
+
+/// `statusline config <schema | explain KEY>`
+pub fn run(args: &[String]) -> Result<String, String> {
+    match args.first().map(String::as_str) {
+        Some("schema") => serde_json::to_string_pretty(&schema()).map_err(|e| e.to_string()),
+        Some("explain") => match args.get(1) {
+            Some(key) => explain(key, &args[2..]),
+            None => Err("usage: statusline config explain KEY".into()),
+        },
+        Some(other) => Err(format!(
+            "unknown config command: {} (schema, explain)",
+            other
+        )),
+        None => Err("usage: statusline config schema | explain KEY".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ENV_KEYS;
+
+    #[test]
+    fn schema_covers_every_env_key() {
+        let schema = schema();
+        let props = schema["properties"].as_object().unwrap();
+        for key in ENV_KEYS {
+            assert!(props.contains_key(*key), "{} missing from schema", key);
+        }
+        assert_eq!(props.len(), ENV_KEYS.len());
+    }
+
+    #[test]
+    fn lang_accepts_locales() {
+        // i18n::label reads `de_DE.UTF-8` as `de`, so no enum to reject it
+        let lang = &schema()["properties"]["STATUSLINE_LANG"];
+        assert!(lang.get("enum").is_none());
+        assert_eq!(lang["default"], "en");
+        assert_eq!(crate::i18n::label("de_DE.UTF-8", "", "stash"), "Stash");
+    }
+
+    #[test]
+    fn explain_prefers_command_line() {
+        let args = vec!["--layout".to_string(), "minimal".to_string()];
+        let out = explain("STATUSLINE_LAYOUT", &args).unwrap();
+        assert!(out.starts_with("STATUSLINE_LAYOUT=minimal  (from command line)"));
+        assert!(explain("STATUSLINE_BOGUS", &[]).is_err());
+    }
+
+    #[test]
+    fn explain_shows_real_defaults() {
+        let default = |key: &str| {
+            let out = explain(key, &[]).unwrap();
+            out.lines()
+                .find(|l| l.trim_start().starts_with("default"))
+                .unwrap()
+                .to_string()
+        };
+        assert!(default("STATUSLINE_DAY_BOUNDARY").contains("\"rolling\""));
+        assert!(default("STATUSLINE_LINE2").contains("\"true\""));
+        assert!(default("STATUSLINE_GRAPHICS").contains("\"off\""));
+        assert!(default("STATUSLINE_WORKTREE_PATTERN").contains("\"/.worktrees/\""));
+        assert!(default("STATUSLINE_TOKEN_WIDTH").contains("\"3\""));
+    }
+}
@@ -0,0 +1,194 @@
+use crate::config::Config;
+use serde::Serialize;
+
+/// Static description of a renderable segment.
+pub struct Segment {
+    pub name: &'static str,
+    pub line: u8,
+    pub env_key: &'static str,
+    pub flag: &'static str,
+    pub description: &'static str,
+    pub get: fn(&Config) -> bool,
+    pub set: fn(&mut Config, bool),
+}
+
+impl Segment {
+    /// Whether the segment renders under the given config (line 2 segments need line2).
+    pub fn enabled(&self, cfg: &Config) -> bool {
+        (self.get)(cfg) && (self.line == 1 || cfg.line2)
+    }
+}
+
+pub const SEGMENTS: &[Segment] = &[
+    Segment {
+        name: "model",
+        line: 1,
+        env_key: "STATUSLINE_SHOW_MODEL",
+        flag: "--no-model",
+        description: "Model name (Opus 4.6)",
+        get: |c| c.show_model,
+        set: |c, v| c.show_model = v,
+    },
+    Segment {
+        name: "model_bars",
+        line: 1,
+        env_key: "STATUSLINE_SHOW_MODEL_BARS",
+        flag: "--no-model-bars",
+        description: "Per-model output mix bars",
+        get: |c| c.show_model_bars,
+        set: |c, v| c.show_model_bars = v,
+    },
+    Segment {
+        name: "context",
+        line: 1,
+        env_key: "STATUSLINE_SHOW_CONTEXT",
+        flag: "--no-context",
+        description: "Context window usage bar",
+        get: |c| c.show_context,
+        set: |c, v| c.show_context = v,
+    },
+    Segment {
+        name: "cost",
+        line: 1,
+        env_key: "STATUSLINE_SHOW_COST",
+        flag: "--no-cost",
+        description: "Session cost",
+        get: |c| c.show_cost,
+        set: |c, v| c.show_cost = v,
+    },
+    Segment {
+        name: "duration",
+        line: 1,
+        env_key: "STATUSLINE_SHOW_DURATION",
+        flag: "--no-duration",
+        description: "Wall clock duration",
+        get: |c| c.show_duration,
+        set: |c, v| c.show_duration = v,
+    },
+    Segment {
+        name: "git",
+        line: 1,
+        env_key: "STATUSLINE_SHOW_GIT",
+        flag: "--no-git",
+        description: "Branch, dirty, ahead/behind, stash",
+        get: |c| c.show_git,
+        set: |c, v| c.show_git = v,
+    },
+    Segment {
+        name: "diff",
+        line: 1,
+        env_key: "STATUSLINE_SHOW_DIFF",
+        flag: "--no-diff",
+        description: "Lines added/removed",
+        get: |c| c.show_diff,
+        set: |c, v| c.show_diff = v,
+    },
+    Segment {
+        name: "tokens",
+        line: 2,
+        env_key: "STATUSLINE_SHOW_TOKENS",
+        flag: "--no-tokens",
+        description: "Per-model token counts",
+        get: |c| c.show_tokens,
+        set: |c, v| c.show_tokens = v,
+    },
+    Segment {
+        name: "speed",
+        line: 2,
+        env_key: "STATUSLINE_SHOW_SPEED",
+        flag: "--no-speed",
+        description: "Output throughput (tok/s)",
+        get: |c| c.show_speed,
+        set: |c, v| c.show_speed = v,
+    },
+    Segment {
+        name: "cumulative",
+        line: 2,
+        env_key: "STATUSLINE_SHOW_CUMULATIVE",
+        flag: "--no-cumulative",
+        description: "Project and global cumulative cost",
+        get: |c| c.show_cumulative,
+        set: |c, v| c.show_cumulative = v,
+    },
+];
+
+#[derive(Serialize)]
+struct SegmentInfo {
+    name: &'static str,
+    line: u8,
+    env_key: &'static str,
+    flag: &'static str,
+    description: &'static str,
+    enabled: bool,
+}
+
+#[derive(Serialize)]
+struct Description {
+    version: &'static str,
+    line2_key: &'static str,
+    line2_flag: &'static str,
+    segments: Vec<SegmentInfo>,
+}
+
+/// Describe all segments with their config keys and current enabled state as JSON.
+pub fn describe(cfg: &Config) -> String {
+    let desc = Description {
+        version: crate::VERSION,
+        line2_key: "STATUSLINE_LINE2",
+        line2_flag: "--no-line2",
+        segments: SEGMENTS
+            .iter()
+            .map(|s| SegmentInfo {
+                name: s.name,
+                line: s.line,
+                env_key: s.env_key,
+                flag: s.flag,
+                description: s.description,
+                enabled: s.enabled(cfg),
+            })
+            .collect(),
+    };
+    let mut out = serde_json::to_string_pretty(&desc).unwrap_or_default();
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{self, ENV_KEYS};
+    use std::collections::HashSet;
+
+    #[test]
+    fn registry_is_consistent() {
+        let mut cfg = config::from_options(&Default::default());
+        let (mut names, mut flags) = (HashSet::new(), HashSet::new());
+        for seg in SEGMENTS {
+            assert!(names.insert(seg.name), "{} listed twice", seg.name);
+            assert!(flags.insert(seg.flag), "{} listed twice", seg.flag);
+            assert!(ENV_KEYS.contains(&seg.env_key), "{}", seg.env_key);
+            assert!(seg.flag.starts_with("--no-"), "{}", seg.flag);
+            (seg.set)(&mut cfg, false);
+            assert!(!(seg.get)(&cfg), "{} does not switch off", seg.name);
+            (seg.set)(&mut cfg, true);
+            assert!((seg.get)(&cfg), "{} does not switch on", seg.name);
+        }
+    }
+
+    #[test]
+    fn describe_lists_every_segment() {
+        let mut cfg = config::from_options(&Default::default());
+        cfg.line2 = false;
+        cfg.show_cost = false;
+        let desc: serde_json::Value = serde_json::from_str(&describe(&cfg)).unwrap();
+        assert_eq!(desc["version"], crate::VERSION);
+        let segments = desc["segments"].as_array().unwrap();
+        assert_eq!(segments.len(), SEGMENTS.len());
+        for (info, seg) in segments.iter().zip(SEGMENTS) {
+            assert_eq!(info["name"], seg.name);
+            assert_eq!(info["env_key"], seg.env_key);
+            let enabled = seg.name != "cost" && seg.line == 1;
+            assert_eq!(info["enabled"], enabled, "{}", seg.name);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! `statusline serve --http 127.0.0.1:PORT`: a local JSON endpoint with the
+//! segment values of the most recent session, the one `watch` shows, for
+//! editor extensions (a VS Code status bar item) to poll. This engine has no
+//! daemon holding state: each request renders the last-session snapshot
+//! from the caches.
+
+use crate::config::{self, Config};
+use crate::render;
+use crate::watch;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+const DEFAULT_ADDR: &str = "127.0.0.1:7878";
+
+pub fn run(cfg: &Config) -> Result<String, String> {
+    let addr =
+        config::arg_value(&cfg.command_args, "--http").unwrap_or_else(|| DEFAULT_ADDR.to_string());
+    let addr: SocketAddr = addr
+        .parse()
+        .map_err(|_| format!("invalid address: {} (expected 127.0.0.1:PORT)", addr))?;
+    // Session data stays on this machine
+    if !addr.ip().is_loopback() {
+        return Err(format!("{} is not a loopback address", addr.ip()));
+    }
+    let listener = TcpListener::bind(addr).map_err(|e| format!("cannot bind {}: {}", addr, e))?;
+    // Announced on stdout like `report`, before the loop that never returns
+    let mut out = io::stdout();
+    let _ = writeln!(out, "[ok] Serving http://{}/status", addr);
+    let _ = out.flush();
+    // One thread per connection, so a slow client does not hold up the others
+    thread::scope(|scope| {
+        for stream in listener.incoming().flatten() {
+            scope.spawn(move || handle(stream, cfg, addr.port()));
+        }
+    });
+    Ok(String::new())
+}
+
+fn handle(mut stream: TcpStream, cfg: &Config, port: u16) {
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(2)));
+    let _ = stream.set_write_timeout(Some(Duration::from_secs(2)));
+    let mut reader = BufReader::new(&stream);
+    let mut line = String::new();
+    if reader.read_line(&mut line).is_err() {
+        return;
+    }
+    let mut host = None;
+    for _ in 0..100 {
+        let mut header = String::new();
+        match reader.read_line(&mut header) {
+            Ok(n) if n > 0 && !header.trim().is_empty() => {
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.trim().eq_ignore_ascii_case("host") {
+                        host = Some(value.trim().to_string());
+                    }
+                }
+            }
+            _ => break,
+        }
+    }
+    let reply = respond(&line, host.as_deref(), port, || status(cfg));
+    let _ = stream.write_all(reply.as_bytes());
+}
+
+/// Whether the Host header names this server by a loopback name. Anything
+/// else is refused, so a web page cannot read the session through DNS
+/// rebinding (a name of its own that resolves to 127.0.0.1).
+fn host_allowed(host: Option<&str>, port: u16) -> bool {
+    let Some(host) = host else {
+        return false;
+    };
+    ["127.0.0.1", "localhost", "[::1]"]
+        .iter()
+        .any(|name| host.eq_ignore_ascii_case(&format!("{}:{}", name, port)))
+}
+
+/// The HTTP response to `request_line` with the request's Host header;
+/// `status` is only read for a GET of `/` or `/status`.
+fn respond(
+    request_line: &str,
+    host: Option<&str>,
+    port: u16,
+    status: impl FnOnce() -> Option<Value>,
+) -> String {
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let path = path.split('?').next().unwrap_or("");
+    let (code, body) = match (method, path) {
+        _ if !host_allowed(host, port) => (
+            "403 Forbidden",
+            json!({"error": "Host must be 127.0.0.1 or localhost with this port"}),
+        ),
+        ("GET", "/" | "/status") => match status() {
+            Some(v) => ("200 OK", v),
+            None => (
+                "503 Service Unavailable",
+                json!({"error": "no Claude Code session yet"}),
+            ),
+        },
+        ("GET", _) => ("404 Not Found", json!({"error": "not found"})),
+        _ => ("405 Method Not Allowed", json!({"error": "GET only"})),
+    };
+    let body = body.to_string();
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        code,
+        body.len(),
+        body
+    )
+}
+
+/// Segment values and both plain lines of the most recent session.
+fn status(cfg: &Config) -> Option<Value> {
+    let sess = watch::latest()?;
+    let (rendered, values) = render::render_values(&sess, cfg);
+    let lines: Vec<String> = render::strip_ansi(&rendered)
+        .lines()
+        .map(str::to_string)
+        .collect();
+    Some(json!({
+        "session_id": sess.session_id,
+        "values": values,
+        "lines": lines,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOST: Option<&str> = Some("127.0.0.1:7878");
+
+    #[test]
+    fn routes_and_status_codes() {
+        let ok = respond("GET /status HTTP/1.1\r\n", HOST, 7878, || {
+            Some(json!({"values": {"cost": 1.5}}))
+        });
+        assert!(ok.starts_with("HTTP/1.1 200 OK\r\n"), "{}", ok);
+        assert!(
+            ok.ends_with("\r\n\r\n{\"values\":{\"cost\":1.5}}"),
+            "{}",
+            ok
+        );
+        assert!(ok.contains("Content-Length: 23\r\n"));
+
+        let waiting = respond("GET /?t=1 HTTP/1.1\r\n", HOST, 7878, || None);
+        assert!(waiting.starts_with("HTTP/1.1 503 "), "{}", waiting);
+
+        let missing = respond(
+            "GET /favicon.ico HTTP/1.1\r\n",
+            HOST,
+            7878,
+            || unreachable!(),
+        );
+        assert!(missing.starts_with("HTTP/1.1 404 "));
+        let post = respond("POST /status HTTP/1.1\r\n", HOST, 7878, || unreachable!());
+        assert!(post.starts_with("HTTP/1.1 405 "));
+    }
+
+    #[test]
+    fn foreign_hosts_are_refused() {
+        let get = |host| respond("GET /status HTTP/1.1\r\n", host, 7878, || Some(json!({})));
+        assert!(get(Some("localhost:7878")).starts_with("HTTP/1.1 200 "));
+        assert!(get(Some("LOCALHOST:7878")).starts_with("HTTP/1.1 200 "));
+        for host in [
+            Some("evil.example:7878"),
+            Some("127.0.0.1:80"),
+            Some("127.0.0.1"),
+            None,
+        ] {
+            assert!(get(host).starts_with("HTTP/1.1 403 "), "{:?}", host);
+        }
+    }
+}
@@ -1,5 +1,7 @@
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
 use std::io::Read;
+use std::path::Path;
 
 #[allow(dead_code)]
 #[derive(Deserialize, Default)]
@@ -13,28 +15,38 @@ pub struct Model {
 #[allow(dead_code)]
 #[derive(Deserialize, Default)]
 pub struct ContextWindow {
-    #[serde(default)]
+    #[serde(default, deserialize_with = "number")]
     pub used_percentage: f64,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "number")]
     pub context_window_size: f64,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "number")]
     pub total_input_tokens: f64,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "number")]
     pub total_output_tokens: f64,
 }
 
 #[derive(Deserialize, Default)]
 pub struct Cost {
-    #[serde(default)]
+    #[serde(default, deserialize_with = "number")]
     pub total_cost_usd: f64,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "number")]
     pub total_duration_ms: f64,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "number")]
     pub total_api_duration_ms: f64,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "number")]
     pub total_lines_added: f64,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "number")]
     pub total_lines_removed: f64,
+    /// `total_cost_usd` was estimated from the transcript (Bedrock/Vertex).
+    #[serde(skip)]
+    pub estimated: bool,
+}
+
+/// Display hints from the caller, when it knows the real terminal size.
+#[derive(Deserialize, Default)]
+pub struct Terminal {
+    #[serde(default, alias = "width", deserialize_with = "columns")]
+    pub columns: usize,
 }
 
 #[allow(dead_code)]
@@ -65,14 +77,174 @@ pub struct Session {
     pub workspace: Workspace,
     #[serde(default)]
     pub transcript_path: String,
+    #[serde(default)]
+    pub terminal: Terminal,
+    /// Per-invocation config from the caller, `{"layout": "minimal"}`
+    #[serde(default)]
+    pub statusline_options: HashMap<String, serde_json::Value>,
+}
+
+impl Session {
+    /// Session id derived from the transcript file name (`<id>.jsonl`).
+    pub fn id(&self) -> String {
+        if self.transcript_path.is_empty() {
+            return String::new();
+        }
+        let base = Path::new(&self.transcript_path)
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default();
+        base.strip_suffix(".jsonl").unwrap_or(&base).to_string()
+    }
+}
+
+/// A null, string or otherwise non-numeric value reads as 0 instead of
+/// failing the whole payload; `sanitize` bounds the rest.
+fn number<'de, D: Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+    Ok(serde_json::Value::deserialize(d)?.as_f64().unwrap_or(0.0))
+}
+
+fn columns<'de, D: Deserializer<'de>>(d: D) -> Result<usize, D::Error> {
+    Ok(clean(number(d)?, MAX_COLUMNS) as usize)
+}
+
+/// Finite and within 0..=max; NaN and infinities become 0.
+fn clean(x: f64, max: f64) -> f64 {
+    if x.is_finite() {
+        x.clamp(0.0, max)
+    } else {
+        0.0
+    }
+}
+
+const MAX_COLUMNS: f64 = 10_000.0;
+const MAX_TOKENS: f64 = 1e15;
+const MAX_COST_USD: f64 = 1e9;
+/// A thousand years
+const MAX_DURATION_MS: f64 = 3.2e13;
+const MAX_LINES: f64 = 1e12;
+
+impl Session {
+    /// Bound every numeric field, so formatting and percentage math never
+    /// see negative, non-finite or absurd values.
+    pub fn sanitize(&mut self) {
+        let cw = &mut self.context_window;
+        cw.used_percentage = clean(cw.used_percentage, 100.0);
+        cw.context_window_size = clean(cw.context_window_size, MAX_TOKENS);
+        cw.total_input_tokens = clean(cw.total_input_tokens, MAX_TOKENS);
+        cw.total_output_tokens = clean(cw.total_output_tokens, MAX_TOKENS);
+        let cost = &mut self.cost;
+        cost.total_cost_usd = clean(cost.total_cost_usd, MAX_COST_USD);
+        cost.total_duration_ms = clean(cost.total_duration_ms, MAX_DURATION_MS);
+        cost.total_api_duration_ms = clean(cost.total_api_duration_ms, MAX_DURATION_MS);
+        cost.total_lines_added = clean(cost.total_lines_added, MAX_LINES);
+        cost.total_lines_removed = clean(cost.total_lines_removed, MAX_LINES);
+    }
 }
 
 const MAX_STDIN_SIZE: u64 = 1 << 20; // 1 MB
 
 pub fn parse(reader: impl Read) -> Session {
+    let buf = read_raw(reader);
+    let mut sess: Session = serde_json::from_slice(&buf)
+        .or_else(|_| serde_json::from_slice(&clamp_literals(&buf)))
+        .unwrap_or_default();
+    sess.sanitize();
+    sess
+}
+
+/// serde_json rejects number literals beyond f64 (`1e309`) outright, which
+/// would drop the whole payload; rewrite them to the largest finite value.
+fn clamp_literals(buf: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(buf.len());
+    let (mut i, mut in_string) = (0, false);
+    while i < buf.len() {
+        let b = buf[i];
+        if in_string {
+            out.push(b);
+            if b == b'\\' {
+                out.extend(buf.get(i + 1));
+                i += 1;
+            } else if b == b'"' {
+                in_string = false;
+            }
+            i += 1;
+        } else if b == b'-' || b.is_ascii_digit() {
+            let end = buf[i..]
+                .iter()
+                .position(|c| !matches!(c, b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E'))
+                .map_or(buf.len(), |n| i + n);
+            let literal = &buf[i..end];
+            match std::str::from_utf8(literal)
+                .ok()
+                .and_then(|t| t.parse::<f64>().ok())
+            {
+                Some(x) if x.is_infinite() => {
+                    out.extend_from_slice(if x > 0.0 { b"1e308" } else { b"-1e308" })
+                }
+                _ => out.extend_from_slice(literal),
+            }
+            i = end;
+        } else {
+            in_string = b == b'"';
+            out.push(b);
+            i += 1;
+        }
+    }
+    out
+}
+
+/// Read the raw payload (capped at MAX_STDIN_SIZE); empty on error.
+pub fn read_raw(reader: impl Read) -> Vec<u8> {
     let mut buf = Vec::new();
     if reader.take(MAX_STDIN_SIZE).read_to_end(&mut buf).is_err() {
-        return Session::default();
+        return Vec::new();
+    }
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitizes_numerics() {
+        let sess = parse(
+            &br#"{
+                "context_window": {"used_percentage": 250, "total_input_tokens": -5,
+                                   "total_output_tokens": null},
+                "cost": {"total_cost_usd": 1e309, "total_duration_ms": "soon",
+                         "total_lines_added": -1e400},
+                "terminal": {"columns": -80}
+            }"#[..],
+        );
+        assert_eq!(sess.context_window.used_percentage, 100.0);
+        assert_eq!(sess.context_window.total_input_tokens, 0.0);
+        assert_eq!(sess.context_window.total_output_tokens, 0.0);
+        assert_eq!(sess.cost.total_cost_usd, MAX_COST_USD);
+        assert_eq!(sess.cost.total_duration_ms, 0.0);
+        assert_eq!(sess.cost.total_lines_added, 0.0);
+        assert_eq!(sess.terminal.columns, 0);
+    }
+
+    #[test]
+    fn sane_values_pass_through() {
+        let sess = parse(
+            &br#"{"model": {"display_name": "Opus 1e999"},
+                  "context_window": {"used_percentage": 42.5},
+                  "cost": {"total_cost_usd": 8.42}, "terminal": {"width": 120}}"#[..],
+        );
+        assert_eq!(sess.model.display_name, "Opus 1e999");
+        assert_eq!(sess.context_window.used_percentage, 42.5);
+        assert_eq!(sess.cost.total_cost_usd, 8.42);
+        assert_eq!(sess.terminal.columns, 120);
+    }
+
+    #[test]
+    fn clamp_literals_skips_strings() {
+        assert_eq!(
+            clamp_literals(br#"{"a":"1e999\"1e999","b":[1e999,-2e400,3.5]}"#),
+            br#"{"a":"1e999\"1e999","b":[1e308,-1e308,3.5]}"#
+        );
     }
-    serde_json::from_slice(&buf).unwrap_or_default()
 }
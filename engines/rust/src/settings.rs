@@ -0,0 +1,157 @@
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_COMMAND: &str = "~/.claude/statusline.sh";
+
+/// Path of Claude Code's settings.json.
+pub fn settings_path() -> PathBuf {
+    crate::cache::claude_dir().join("settings.json")
+}
+
+fn status_line(command: &str) -> Value {
+    let mut m = Map::new();
+    m.insert("type".into(), Value::from("command"));
+    m.insert("command".into(), Value::from(command));
+    m.insert("padding".into(), Value::from(0));
+    Value::Object(m)
+}
+
+/// Insert or update the statusLine entry, keeping every other key and its order.
+pub fn install(path: &Path, command: &str) -> Result<String, String> {
+    let mut root = read(path)?;
+    let want = status_line(command);
+    let obj = root
+        .as_object_mut()
+        .ok_or("settings.json is not a JSON object")?;
+    if obj.get("statusLine") == Some(&want) {
+        return Ok(format!(
+            "[ok] statusLine already configured in {}",
+            path.display()
+        ));
+    }
+    let action = if obj.contains_key("statusLine") {
+        "Updated"
+    } else {
+        "Added"
+    };
+    obj.insert("statusLine".into(), want);
+    write(path, &root)?;
+    Ok(format!("[ok] {} statusLine in {}", action, path.display()))
+}
+
+/// Remove the statusLine entry.
+pub fn uninstall(path: &Path) -> Result<String, String> {
+    if !path.exists() {
+        return Ok(format!("[--] {} not found", path.display()));
+    }
+    let mut root = read(path)?;
+    let obj = root
+        .as_object_mut()
+        .ok_or("settings.json is not a JSON object")?;
+    if obj.remove("statusLine").is_none() {
+        return Ok("[--] settings.json: no statusLine key found".into());
+    }
+    write(path, &root)?;
+    Ok(format!("[ok] Removed statusLine from {}", path.display()))
+}
+
+fn read(path: &Path) -> Result<Value, String> {
+    match fs::read_to_string(path) {
+        Ok(data) if data.trim().is_empty() => Ok(Value::Object(Map::new())),
+        Ok(data) => serde_json::from_str(&data)
+            .map_err(|e| format!("cannot parse {}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Value::Object(Map::new())),
+        Err(e) => Err(format!("cannot read {}: {}", path.display(), e)),
+    }
+}
+
+/// Back up the existing file, then replace it atomically.
+fn write(path: &Path, root: &Value) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+    }
+    if path.exists() {
+        let bak = path.with_extension("json.bak");
+        fs::copy(path, &bak).map_err(|e| format!("cannot back up to {}: {}", bak.display(), e))?;
+    }
+    let mut data = serde_json::to_string_pretty(root).map_err(|e| e.to_string())?;
+    data.push('\n');
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data).map_err(|e| format!("cannot write {}: {}", tmp.display(), e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("cannot replace {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn temp_settings(name: &str, data: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!(
+            "statusline-settings-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.json");
+        fs::write(&path, data).unwrap();
+        path
+    }
+
+    fn keys(path: &Path) -> Vec<String> {
+        let root: Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        root.as_object().unwrap().keys().cloned().collect()
+    }
+
+    #[test]
+    fn install_keeps_other_keys_in_order() {
+        let original = r#"{"model": "opus", "hooks": {"Stop": []}, "theme": "dark"}"#;
+        let path = temp_settings("keep", original);
+        assert!(install(&path, DEFAULT_COMMAND).unwrap().contains("Added"));
+        assert_eq!(keys(&path), ["model", "hooks", "theme", "statusLine"]);
+        let bak = path.with_extension("json.bak");
+        assert_eq!(fs::read_to_string(bak).unwrap(), original);
+        assert!(install(&path, DEFAULT_COMMAND).unwrap().contains("already"));
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn install_replaces_an_existing_status_line() {
+        let path = temp_settings(
+            "replace",
+            r#"{"statusLine": {"type": "command", "command": "old.sh"}, "model": "opus"}"#,
+        );
+        assert!(install(&path, "new.sh").unwrap().contains("Updated"));
+        assert_eq!(keys(&path), ["statusLine", "model"]);
+        let root: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(root["statusLine"], status_line("new.sh"));
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn invalid_settings_are_left_alone() {
+        for (name, data) in [("array", "[1, 2]"), ("broken", "{\"model\": ")] {
+            let path = temp_settings(name, data);
+            assert!(install(&path, DEFAULT_COMMAND).is_err());
+            assert!(uninstall(&path).is_err());
+            assert_eq!(fs::read_to_string(&path).unwrap(), data);
+            assert!(!path.with_extension("json.bak").exists());
+            let _ = fs::remove_dir_all(path.parent().unwrap());
+        }
+    }
+
+    #[test]
+    fn uninstall_removes_only_the_status_line() {
+        let path = temp_settings(
+            "remove",
+            r#"{"model": "opus", "statusLine": {"type": "command"}, "theme": "dark"}"#,
+        );
+        assert!(uninstall(&path).unwrap().starts_with("[ok]"));
+        assert_eq!(keys(&path), ["model", "theme"]);
+        assert!(path.with_extension("json.bak").exists());
+        assert!(uninstall(&path).unwrap().starts_with("[--]"));
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
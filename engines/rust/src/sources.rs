@@ -0,0 +1,302 @@
+//! Where a render gets its data besides the session JSON: the repository
+//! state, the caches and the other local files it reads (transcript tail,
+//! history, account). The binary reads them through gix and the cache
+//! directory (`Sources::LIVE`); tests pass in-memory mocks, so the full
+//! render path runs without a repository or a filesystem, and `embed` uses
+//! `Sources::NONE`.
+
+use crate::account::{self, Account};
+use crate::cache::{self, CumulativeStats, ModelStats};
+use crate::config::Config;
+use crate::deadline;
+use crate::git::{self, GitState};
+use crate::history;
+use crate::pending;
+use crate::pomodoro::{self, Phase};
+
+pub trait VcsProvider {
+    /// State of the repository containing `cwd`; None outside a repository.
+    fn state(&self, cwd: &str, project_dir: &str, cfg: &Config) -> Option<GitState>;
+}
+
+pub trait CacheStore {
+    /// Per-model stats for the session, from the transcript parse.
+    fn models(&self, session_id: &str) -> Option<ModelStats>;
+    /// Cumulative costs for the project and for all projects.
+    fn cumulative(&self, project_dir: &str) -> (Option<CumulativeStats>, Option<CumulativeStats>);
+    /// Record the context % and return the session's recent values.
+    fn push_context(&self, session_id: &str, pct: u8) -> Vec<u8>;
+    /// The project's `statusline deadline` (unix seconds).
+    fn deadline(&self, project_dir: &str) -> Option<i64>;
+    /// The running `statusline pomo` phase and seconds left in it.
+    fn pomodoro(&self, now: i64) -> Option<(Phase, i64)>;
+    /// The oldest tool call in the transcript still without a result: tool
+    /// name and unix start time.
+    fn pending(&self, transcript_path: &str) -> Option<(String, i64)>;
+    /// Tokens recorded in history by all sessions since `since`, the start
+    /// of the current week.
+    fn tokens_since(&self, since: u64) -> u64;
+    /// Record a render at `now` and return when the current stretch of
+    /// continuous activity began.
+    fn touch_activity(&self, now: i64) -> i64;
+    /// The Claude account in use.
+    fn account(&self) -> Option<Account>;
+}
+
+/// Repository state via gix (empty without the `git` feature).
+pub struct Gix;
+
+impl VcsProvider for Gix {
+    fn state(&self, cwd: &str, project_dir: &str, cfg: &Config) -> Option<GitState> {
+        git::get(cwd, project_dir, cfg)
+    }
+}
+
+/// The JSON caches in `cache::cache_dir()`.
+pub struct Disk;
+
+impl CacheStore for Disk {
+    fn models(&self, session_id: &str) -> Option<ModelStats> {
+        cache::read_models(session_id)
+    }
+
+    fn cumulative(&self, project_dir: &str) -> (Option<CumulativeStats>, Option<CumulativeStats>) {
+        cache::read_cumulative(project_dir)
+    }
+
+    fn push_context(&self, session_id: &str, pct: u8) -> Vec<u8> {
+        cache::push_context_history(session_id, pct)
+    }
+
+    fn deadline(&self, project_dir: &str) -> Option<i64> {
+        deadline::read(project_dir)
+    }
+
+    fn pomodoro(&self, now: i64) -> Option<(Phase, i64)> {
+        pomodoro::status(now)
+    }
+
+    fn pending(&self, transcript_path: &str) -> Option<(String, i64)> {
+        pending::oldest(transcript_path)
+    }
+
+    fn tokens_since(&self, since: u64) -> u64 {
+        history::week_tokens(since)
+    }
+
+    fn touch_activity(&self, now: i64) -> i64 {
+        cache::touch_activity(now)
+    }
+
+    fn account(&self) -> Option<Account> {
+        account::detect()
+    }
+}
+
+/// No repository, as outside one.
+pub struct NoVcs;
+
+impl VcsProvider for NoVcs {
+    fn state(&self, _cwd: &str, _project_dir: &str, _cfg: &Config) -> Option<GitState> {
+        None
+    }
+}
+
+/// Empty caches that keep nothing, so a render touches no files.
+pub struct NoCache;
+
+impl CacheStore for NoCache {
+    fn models(&self, _session_id: &str) -> Option<ModelStats> {
+        None
+    }
+
+    fn cumulative(&self, _project_dir: &str) -> (Option<CumulativeStats>, Option<CumulativeStats>) {
+        (None, None)
+    }
+
+    fn push_context(&self, _session_id: &str, pct: u8) -> Vec<u8> {
+        vec![pct]
+    }
+
+    fn deadline(&self, _project_dir: &str) -> Option<i64> {
+        None
+    }
+
+    fn pomodoro(&self, _now: i64) -> Option<(Phase, i64)> {
+        None
+    }
+
+    fn pending(&self, _transcript_path: &str) -> Option<(String, i64)> {
+        None
+    }
+
+    fn tokens_since(&self, _since: u64) -> u64 {
+        0
+    }
+
+    fn touch_activity(&self, now: i64) -> i64 {
+        now
+    }
+
+    fn account(&self) -> Option<Account> {
+        None
+    }
+}
+
+pub struct Sources<'a> {
+    pub vcs: &'a dyn VcsProvider,
+    pub cache: &'a dyn CacheStore,
+}
+
+impl Sources<'static> {
+    pub const LIVE: Sources<'static> = Sources {
+        vcs: &Gix,
+        cache: &Disk,
+    };
+
+    /// Session JSON only (`embed`, the WASM build).
+    pub const NONE: Sources<'static> = Sources {
+        vcs: &NoVcs,
+        cache: &NoCache,
+    };
+}
+
+/// Records which sources a render reads, in order, and finds nothing.
+#[cfg(test)]
+#[derive(Default)]
+pub struct Recorder {
+    pub calls: std::cell::RefCell<Vec<&'static str>>,
+}
+
+#[cfg(test)]
+impl Recorder {
+    fn record(&self, name: &'static str) {
+        self.calls.borrow_mut().push(name);
+    }
+}
+
+#[cfg(test)]
+impl VcsProvider for Recorder {
+    fn state(&self, _cwd: &str, _project_dir: &str, _cfg: &Config) -> Option<GitState> {
+        self.record("vcs");
+        None
+    }
+}
+
+#[cfg(test)]
+impl CacheStore for Recorder {
+    fn models(&self, _session_id: &str) -> Option<ModelStats> {
+        self.record("models");
+        None
+    }
+
+    fn cumulative(&self, _project_dir: &str) -> (Option<CumulativeStats>, Option<CumulativeStats>) {
+        self.record("cumulative");
+        (None, None)
+    }
+
+    fn push_context(&self, _session_id: &str, pct: u8) -> Vec<u8> {
+        self.record("push_context");
+        vec![pct]
+    }
+
+    fn deadline(&self, _project_dir: &str) -> Option<i64> {
+        self.record("deadline");
+        None
+    }
+
+    fn pomodoro(&self, _now: i64) -> Option<(Phase, i64)> {
+        self.record("pomodoro");
+        None
+    }
+
+    fn pending(&self, _transcript_path: &str) -> Option<(String, i64)> {
+        self.record("pending");
+        None
+    }
+
+    fn tokens_since(&self, _since: u64) -> u64 {
+        self.record("tokens_since");
+        0
+    }
+
+    fn touch_activity(&self, now: i64) -> i64 {
+        self.record("touch_activity");
+        now
+    }
+
+    fn account(&self) -> Option<Account> {
+        self.record("account");
+        None
+    }
+}
+
+/// A repository that always has this state (or none).
+#[cfg(test)]
+pub struct MockVcs(pub Option<GitState>);
+
+#[cfg(test)]
+impl VcsProvider for MockVcs {
+    fn state(&self, _cwd: &str, _project_dir: &str, _cfg: &Config) -> Option<GitState> {
+        self.0.clone()
+    }
+}
+
+/// Caches held in memory; `push_context` appends like the file-backed one.
+/// `active_since` is when activity began (None: at this render).
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemCache {
+    pub models: Option<ModelStats>,
+    pub project: Option<CumulativeStats>,
+    pub all: Option<CumulativeStats>,
+    pub context: std::cell::RefCell<Vec<u8>>,
+    pub deadline: Option<i64>,
+    pub pending: Option<(String, i64)>,
+    pub tokens: u64,
+    pub active_since: Option<i64>,
+    pub account: Option<&'static str>,
+}
+
+#[cfg(test)]
+impl CacheStore for MemCache {
+    fn models(&self, _session_id: &str) -> Option<ModelStats> {
+        self.models.clone()
+    }
+
+    fn cumulative(&self, _project_dir: &str) -> (Option<CumulativeStats>, Option<CumulativeStats>) {
+        (self.project.clone(), self.all.clone())
+    }
+
+    fn push_context(&self, _session_id: &str, pct: u8) -> Vec<u8> {
+        let mut history = self.context.borrow_mut();
+        if history.last() != Some(&pct) {
+            history.push(pct);
+        }
+        history.clone()
+    }
+
+    fn deadline(&self, _project_dir: &str) -> Option<i64> {
+        self.deadline
+    }
+
+    fn pomodoro(&self, _now: i64) -> Option<(Phase, i64)> {
+        None
+    }
+
+    fn pending(&self, _transcript_path: &str) -> Option<(String, i64)> {
+        self.pending.clone()
+    }
+
+    fn tokens_since(&self, _since: u64) -> u64 {
+        self.tokens
+    }
+
+    fn touch_activity(&self, now: i64) -> i64 {
+        self.active_since.unwrap_or(now)
+    }
+
+    fn account(&self) -> Option<Account> {
+        self.account.map(account::plain)
+    }
+}
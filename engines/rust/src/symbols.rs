@@ -0,0 +1,76 @@
+//! Fixed glyphs that some fonts draw poorly. STATUSLINE_SYMBOLS swaps them:
+//! `ascii` selects plain-text stand-ins, and `key=text` pairs override
+//! single glyphs on top (`ascii,sep=/` or `project=proj,global=all`).
+
+/// Symbol keys with their default glyph and the `ascii` stand-in.
+const SYMBOLS: &[(&str, &str, &str)] = &[
+    ("project", "\u{2302}", "proj"),
+    ("global", "\u{03a3}", "all"),
+    ("work", "\u{25f7}", "work "),
+    ("files", "\u{270e}", "#"),
+    ("sep", "\u{2502}", "|"),
+    ("bar_full", "\u{2593}", "#"),
+    ("bar_empty", "\u{2591}", "-"),
+    ("none", "\u{00b7}", "."),
+    ("warn", "\u{26a0}", "!"),
+    ("more", "\u{2026}", "..."),
+    ("mood_broke", "\u{1f4b8}", "$"),
+    ("mood_full", "\u{1f635}", "x("),
+    ("mood_errors", "\u{1f915}", ":("),
+    ("mood_tight", "\u{1f62c}", ":/"),
+    ("mood_ok", "\u{1f642}", ":)"),
+    ("awaiting", "\u{23f3}", ""),
+    ("deadline", "\u{23f3}", "due"),
+    ("projection", "\u{2192}", "->"),
+    ("plan", "\u{2696}", "plan"),
+    ("pace_ahead", "\u{25b2}", "+"),
+    ("pace_behind", "\u{25bc}", "-"),
+    ("break", "\u{2615}", "rest"),
+    ("pomodoro", "\u{1f345}", "pomo"),
+    ("worktree", "\u{2295}", "+"),
+    ("dirty", "\u{25cf}", "*"),
+    ("ahead", "\u{2191}", "^"),
+    ("behind", "\u{2193}", "v"),
+    ("commits", "\u{2713}", "c"),
+    ("pass", "\u{2713}", "ok"),
+    ("fail", "\u{2717}", "x"),
+    ("pending", "\u{25cf}", "..."),
+];
+
+/// The glyph for `key` under `setting`. Unknown keys come back as given.
+pub fn symbol(setting: &str, key: &str) -> String {
+    let mut ascii = false;
+    let mut found = None;
+    for item in setting.split(',') {
+        match item.split_once('=') {
+            Some((k, v)) if k.trim() == key => found = Some(v.trim()),
+            None if item.trim() == "ascii" => ascii = true,
+            _ => {}
+        }
+    }
+    if let Some(v) = found {
+        return v.to_string();
+    }
+    match SYMBOLS.iter().find(|(k, _, _)| *k == key) {
+        Some((_, _, text)) if ascii => text.to_string(),
+        Some((_, glyph, _)) => glyph.to_string(),
+        None => key.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_presets_and_overrides() {
+        assert_eq!(symbol("", "project"), "\u{2302}");
+        assert_eq!(symbol("ascii", "global"), "all");
+        assert_eq!(symbol("ascii, sep=/", "sep"), "/");
+        assert_eq!(symbol("ascii, sep=/", "project"), "proj");
+        assert_eq!(symbol("project=P", "project"), "P");
+        assert_eq!(symbol("project=P", "global"), "\u{03a3}");
+        assert_eq!(symbol("ascii", "more"), "...");
+        assert_eq!(symbol("", "other"), "other");
+    }
+}
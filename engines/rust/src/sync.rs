@@ -0,0 +1,304 @@
+//! `statusline sync push|pull --remote host:path`: share transcripts between
+//! machines so the cumulative (⌂ Σ) stats cover all of them.
+//!
+//! `push` copies this machine's transcripts to `<remote>/<host>/projects`.
+//! `pull` fetches the other hosts' folders into `<cache dir>/sync/hosts` and
+//! merges them into `<cache dir>/sync/projects`, which cumulative-stats.sh
+//! scans as one more transcript root. A `host:path` remote goes through
+//! rsync over ssh; anything else is a directory (a mount or a synced folder)
+//! and is copied directly.
+
+use crate::cache;
+use crate::config::{self, Config};
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub fn run(cfg: &Config) -> Result<String, String> {
+    let args = &cfg.command_args;
+    let action = args.first().map(String::as_str);
+    if !matches!(action, Some("push" | "pull")) {
+        return Err("usage: statusline sync push|pull --remote host:path [--host NAME]".into());
+    }
+    let remote = config::arg_value(args, "--remote").ok_or("--remote host:path is required")?;
+    let host = match config::arg_value(args, "--host") {
+        Some(h) => h,
+        None => hostname().ok_or("cannot determine the host name, pass --host NAME")?,
+    };
+    check_remote(&remote, &host, cfg.offline)?;
+    let roots = cache::transcript_roots(&cfg.transcript_roots);
+    if action == Some("push") {
+        push(&remote, &host, &roots)?;
+        return Ok(format!("[ok] Pushed transcripts to {}/{}", remote, host));
+    }
+    let staged = cache::sync_dir().join("hosts");
+    pull(&remote, &host, &staged)?;
+    let (merged, skipped) = merge(
+        &staged,
+        &cache::sync_dir().join("projects"),
+        &local_sessions(&roots),
+    )
+    .map_err(|e| format!("cannot merge transcripts: {}", e))?;
+    Ok(format!(
+        "[ok] Merged {} sessions from other hosts ({} duplicates skipped)",
+        merged, skipped
+    ))
+}
+
+/// `host:path` (rsync over ssh) rather than a local directory.
+fn is_remote(remote: &str) -> bool {
+    remote
+        .split_once(':')
+        .is_some_and(|(host, _)| !host.is_empty() && !host.contains('/'))
+}
+
+/// Refuse ssh remotes in offline mode, and quotes that would break the
+/// remote `mkdir` in `push`.
+fn check_remote(remote: &str, host: &str, offline: bool) -> Result<(), String> {
+    if !is_remote(remote) {
+        return Ok(());
+    }
+    if offline {
+        return Err(format!(
+            "{} needs ssh, which offline mode (STATUSLINE_OFFLINE) forbids; use a mounted directory",
+            remote
+        ));
+    }
+    if remote.contains('\'') || host.contains('\'') {
+        return Err("remote paths and host names must not contain a single quote".into());
+    }
+    Ok(())
+}
+
+fn push(remote: &str, host: &str, roots: &[PathBuf]) -> Result<(), String> {
+    for projects in roots.iter().filter(|r| r.is_dir()) {
+        if !is_remote(remote) {
+            copy_transcripts(projects, &Path::new(remote).join(host).join("projects"))
+                .map_err(|e| format!("cannot copy to {}: {}", remote, e))?;
+            continue;
+        }
+        let (_, path) = remote.split_once(':').unwrap_or_default();
+        let dest = format!("{}/{}/projects", path.trim_end_matches('/'), host);
+        // rsync creates only the last directory of the destination
+        rsync(&[
+            format!("--rsync-path=mkdir -p '{}' && rsync", dest),
+            "--include=/*/".into(),
+            "--include=/*/*.jsonl".into(),
+            "--exclude=*".into(),
+            format!("{}/", projects.display()),
+            format!("{}/", remote_join(remote, &format!("{}/projects", host))),
+        ])?;
+    }
+    Ok(())
+}
+
+/// Fetch every other host's transcripts into `staged/<host>/projects`.
+fn pull(remote: &str, host: &str, staged: &Path) -> Result<(), String> {
+    if is_remote(remote) {
+        fs::create_dir_all(staged).map_err(|e| e.to_string())?;
+        return rsync(&[
+            format!("--exclude=/{}/", host),
+            "--include=/*/".into(),
+            "--include=/*/projects/".into(),
+            "--include=/*/projects/*/".into(),
+            "--include=/*/projects/*/*.jsonl".into(),
+            "--exclude=*".into(),
+            format!("{}/", remote.trim_end_matches('/')),
+            format!("{}/", staged.display()),
+        ]);
+    }
+    let hosts = fs::read_dir(remote).map_err(|e| format!("cannot read {}: {}", remote, e))?;
+    for entry in hosts.flatten() {
+        if entry.file_name() == host {
+            continue;
+        }
+        let from = entry.path().join("projects");
+        if from.is_dir() {
+            copy_transcripts(&from, &staged.join(entry.file_name()).join("projects"))
+                .map_err(|e| format!("cannot copy from {}: {}", remote, e))?;
+        }
+    }
+    Ok(())
+}
+
+fn remote_join(remote: &str, rel: &str) -> String {
+    format!("{}/{}", remote.trim_end_matches('/'), rel)
+}
+
+fn rsync(args: &[String]) -> Result<(), String> {
+    // -t keeps modification times, which cumulative-stats.sh filters on
+    let status = Command::new("rsync")
+        .arg("-rt")
+        .args(args)
+        .status()
+        .map_err(|e| format!("cannot run rsync ({}): use a mounted directory instead", e))?;
+    if !status.success() {
+        return Err(format!("rsync failed ({})", status));
+    }
+    Ok(())
+}
+
+/// Copy the transcripts (`<project>/<session>.jsonl`) under `from` to `to`,
+/// keeping modification times. Files already there with the same size and
+/// time are skipped.
+fn copy_transcripts(from: &Path, to: &Path) -> io::Result<()> {
+    for (project, file) in transcripts(from) {
+        let dest = to.join(&project).join(file.file_name().unwrap_or_default());
+        let meta = fs::metadata(&file)?;
+        let unchanged = fs::metadata(&dest)
+            .is_ok_and(|d| d.len() == meta.len() && d.modified().ok() == meta.modified().ok());
+        if !unchanged {
+            fs::create_dir_all(to.join(&project))?;
+            copy_with_mtime(&file, &dest)?;
+        }
+    }
+    Ok(())
+}
+
+fn copy_with_mtime(from: &Path, to: &Path) -> io::Result<()> {
+    fs::copy(from, to)?;
+    File::options()
+        .write(true)
+        .open(to)?
+        .set_modified(fs::metadata(from)?.modified()?)
+}
+
+/// `(project folder, transcript path)` for each `*.jsonl` one level below
+/// `projects`, in name order.
+fn transcripts(projects: &Path) -> Vec<(OsString, PathBuf)> {
+    let mut found = Vec::new();
+    for project in sorted_dir(projects).into_iter().filter(|p| p.is_dir()) {
+        let name = project.file_name().unwrap_or_default().to_os_string();
+        for file in sorted_dir(&project) {
+            if file.extension().is_some_and(|e| e == "jsonl") && file.is_file() {
+                found.push((name.clone(), file));
+            }
+        }
+    }
+    found
+}
+
+fn sorted_dir(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|rd| rd.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default();
+    entries.sort();
+    entries
+}
+
+/// Transcript file names (session ids) under this machine's own roots.
+fn local_sessions(roots: &[PathBuf]) -> HashSet<OsString> {
+    roots
+        .iter()
+        .flat_map(|r| transcripts(r))
+        .filter_map(|(_, f)| f.file_name().map(|n| n.to_os_string()))
+        .collect()
+}
+
+/// Rebuild `out` from the host folders under `staged`, one transcript per
+/// session: none when this machine has the session itself, else the largest
+/// copy, since a transcript only grows. Returns the sessions merged and the
+/// copies skipped.
+fn merge(staged: &Path, out: &Path, local: &HashSet<OsString>) -> io::Result<(usize, usize)> {
+    let mut chosen: HashMap<OsString, (OsString, PathBuf, u64)> = HashMap::new();
+    let mut skipped = 0;
+    for host in sorted_dir(staged) {
+        for (project, file) in transcripts(&host.join("projects")) {
+            let name = file.file_name().unwrap_or_default().to_os_string();
+            let len = fs::metadata(&file).map(|m| m.len()).unwrap_or(0);
+            if local.contains(&name) {
+                skipped += 1;
+                continue;
+            }
+            match chosen.get(&name) {
+                Some((_, _, prev)) if *prev >= len => skipped += 1,
+                Some(_) => {
+                    skipped += 1;
+                    chosen.insert(name, (project, file, len));
+                }
+                None => {
+                    chosen.insert(name, (project, file, len));
+                }
+            }
+        }
+    }
+    if out.exists() {
+        fs::remove_dir_all(out)?;
+    }
+    for (name, (project, file, _)) in &chosen {
+        let dir = out.join(project);
+        fs::create_dir_all(&dir)?;
+        // A hard link shares the modification time; copy across filesystems
+        if fs::hard_link(file, dir.join(name)).is_err() {
+            copy_with_mtime(file, &dir.join(name))?;
+        }
+    }
+    Ok((chosen.len(), skipped))
+}
+
+fn hostname() -> Option<String> {
+    let out = Command::new("hostname").output().ok()?;
+    let name = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    // The short name: `laptop`, not `laptop.local`
+    let name = name.split('.').next().unwrap_or_default().to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn remotes_and_directories() {
+        assert!(is_remote("laptop:.claude-sync"));
+        assert!(is_remote("me@build-box:/srv/sync"));
+        assert!(!is_remote("/mnt/sync"));
+        assert!(!is_remote("./a:b"));
+        assert!(!is_remote(":path"));
+    }
+
+    #[test]
+    fn offline_refuses_ssh_remotes() {
+        assert!(check_remote("nas:claude", "laptop", true).is_err());
+        assert!(check_remote("nas:claude", "laptop", false).is_ok());
+        assert!(check_remote("/mnt/sync", "laptop", true).is_ok());
+        assert!(check_remote("nas:it's", "laptop", false).is_err());
+    }
+
+    #[test]
+    fn merge_counts_each_session_once() {
+        let dir = env::temp_dir().join(format!("statusline-sync-{}", std::process::id()));
+        let write = |rel: &str, data: &str| {
+            let path = dir.join(rel);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, data).unwrap();
+        };
+        write("remote/laptop/projects/-src-app/s1.jsonl", "{}\n");
+        write("remote/laptop/projects/-src-app/s2.jsonl", "{}\n");
+        write("remote/desktop/projects/-home-app/s2.jsonl", "{}\n{}\n");
+        write("remote/desktop/projects/-home-app/notes.txt", "");
+        write("remote/laptop/projects/-src-app/s9.jsonl", "{}\n");
+        write("remote/work/projects/-src-app/s9.jsonl", "{}\n");
+        write("local/projects/-src-app/s9.jsonl", "{}\n");
+
+        let staged = dir.join("staged");
+        pull(dir.join("remote").to_str().unwrap(), "work", &staged).unwrap();
+        assert!(!staged.join("work").exists());
+
+        let local = local_sessions(&[dir.join("local/projects")]);
+        let out = dir.join("merged");
+        assert_eq!(merge(&staged, &out, &local).unwrap(), (2, 2));
+        assert!(out.join("-src-app/s1.jsonl").is_file());
+        let s2 = fs::read_to_string(out.join("-home-app/s2.jsonl")).unwrap();
+        assert_eq!(s2.lines().count(), 2);
+        assert!(!out.join("-src-app/s2.jsonl").exists());
+
+        // A rerun rebuilds rather than adding up
+        assert_eq!(merge(&staged, &out, &local).unwrap(), (2, 2));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,140 @@
+//! Opt-in usage telemetry (STATUSLINE_TELEMETRY with
+//! STATUSLINE_TELEMETRY_URL): once a day, a background job POSTs coarse
+//! aggregates of the previous day, the engine version and a cost bucket,
+//! and nothing else. `statusline telemetry preview` prints exactly what
+//! would be sent.
+
+use crate::cache;
+use crate::clock;
+use crate::config::Config;
+use crate::debuglog;
+use crate::digest;
+use crate::history;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// A claimed report that has not been sent by then is tried again.
+const RETRY_AFTER: Duration = Duration::from_secs(15 * 60);
+
+/// Upper bounds of the daily cost buckets, in dollars.
+const COST_BUCKETS: &[(f64, &str)] = &[
+    (1.0, "<1"),
+    (5.0, "1-5"),
+    (10.0, "5-10"),
+    (25.0, "10-25"),
+    (50.0, "25-50"),
+    (100.0, "50-100"),
+];
+
+/// `statusline telemetry [preview]`
+pub fn run(cfg: &Config) -> Result<String, String> {
+    match cfg.command_args.first().map(String::as_str) {
+        Some("preview") | None => Ok(preview(cfg)),
+        Some(other) => Err(format!("unknown telemetry command: {} (preview)", other)),
+    }
+}
+
+fn preview(cfg: &Config) -> String {
+    let state = match (cfg.telemetry, cfg.telemetry_url.is_empty()) {
+        (false, _) => "off (STATUSLINE_TELEMETRY=true to opt in)".to_string(),
+        (true, true) => "on, but nothing is sent without STATUSLINE_TELEMETRY_URL".to_string(),
+        (true, false) if cfg.offline => "on, but offline mode sends nothing".to_string(),
+        (true, false) => format!("on, once a day to {}", cfg.telemetry_url),
+    };
+    let payload = match payload() {
+        Some(p) => serde_json::to_string_pretty(&p).unwrap_or_default(),
+        None => "nothing: no usage recorded yesterday".to_string(),
+    };
+    // The bucket is summed from history, which keeps project paths locally
+    let history = if cfg.telemetry && !cfg.history {
+        format!(
+            "\nHistory: recorded for the daily sum, with project paths, in {} (local only)\n",
+            cache::cache_dir().join("history").display()
+        )
+    } else {
+        String::new()
+    };
+    format!(
+        "Telemetry: {}\n{}\nYesterday's report:\n{}",
+        state, history, payload
+    )
+}
+
+/// The report for the last day that has ended; None without usage.
+fn payload() -> Option<Value> {
+    let to = clock::day_start(clock::now_unix());
+    let from = clock::day_start(to - 1);
+    let usage = history::usage_between(from as u64, to as u64);
+    if usage.is_empty() {
+        return None;
+    }
+    Some(report(usage.iter().map(|u| u.cost).sum()))
+}
+
+fn report(cost: f64) -> Value {
+    json!({
+        "engine": "rust",
+        "version": crate::VERSION,
+        "daily_cost": bucket(cost),
+    })
+}
+
+fn bucket(cost: f64) -> &'static str {
+    COST_BUCKETS
+        .iter()
+        .find(|(max, _)| cost < *max)
+        .map(|(_, name)| *name)
+        .unwrap_or("100+")
+}
+
+fn marker() -> PathBuf {
+    cache::cache_dir().join("telemetry-last")
+}
+
+/// Claim today's report, so only one render spawns the sender. The day
+/// counts as done once `send` succeeds.
+pub fn claim() -> bool {
+    let today = clock::local_date(clock::now_unix());
+    cache::claim_daily(&marker(), &today, RETRY_AFTER)
+}
+
+/// Send yesterday's report (internal mode).
+pub fn send(cfg: &Config) {
+    if !cfg.telemetry || cfg.telemetry_url.is_empty() || cfg.offline {
+        return;
+    }
+    let today = clock::local_date(clock::now_unix());
+    if let Some(body) = payload() {
+        if !digest::post_json(&cfg.telemetry_url, &body.to_string()) {
+            debuglog::write("telemetry: POST failed");
+            return;
+        }
+    }
+    cache::finish_daily(&marker(), &today);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_is_coarse() {
+        assert_eq!(bucket(0.4), "<1");
+        assert_eq!(bucket(5.0), "5-10");
+        assert_eq!(bucket(99.99), "50-100");
+        assert_eq!(bucket(420.0), "100+");
+        let r = report(12.34);
+        assert_eq!(r["daily_cost"], "10-25");
+        assert_eq!(r.as_object().unwrap().len(), 3);
+        assert_eq!(r["version"], crate::VERSION);
+    }
+
+    #[test]
+    fn session_options_cannot_opt_in() {
+        let json = r#"{"telemetry": true, "STATUSLINE_TELEMETRY_URL": "https://example.com"}"#;
+        let options = crate::config::stdin_options(&serde_json::from_str(json).unwrap());
+        let cfg = crate::config::from_options(&options);
+        assert!(!cfg.telemetry && cfg.telemetry_url.is_empty());
+    }
+}
@@ -0,0 +1,145 @@
+//! Lightweight span tracing (feature `trace`, enabled by STATUSLINE_TRACE=1).
+//!
+//! Spans are appended to `<cache dir>/trace.json` in Chrome trace-event
+//! array format (open with chrome://tracing or ui.perfetto.dev). The array is
+//! left unterminated, which the format allows, so background processes can
+//! append to the same file.
+
+#[cfg(feature = "trace")]
+mod imp {
+    use crate::cache;
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    static ENABLED: AtomicBool = AtomicBool::new(false);
+    static WRITE: AtomicBool = AtomicBool::new(false);
+    static EVENTS: Mutex<Vec<(&'static str, u64, u64)>> = Mutex::new(Vec::new());
+
+    fn now_us() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_micros() as u64)
+            .unwrap_or(0)
+    }
+
+    pub struct Span {
+        name: &'static str,
+        start: Option<u64>,
+    }
+
+    impl Drop for Span {
+        fn drop(&mut self) {
+            if let Some(start) = self.start {
+                let dur = now_us().saturating_sub(start);
+                if let Ok(mut ev) = EVENTS.lock() {
+                    ev.push((self.name, start, dur));
+                }
+            }
+        }
+    }
+
+    pub fn init(enabled: bool) {
+        ENABLED.store(enabled, Ordering::Relaxed);
+        WRITE.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Collect spans in memory only, for `--self-profile`.
+    pub fn profile() {
+        ENABLED.store(true, Ordering::Relaxed);
+    }
+
+    /// Spans recorded so far as (name, duration in microseconds).
+    pub fn spans() -> Vec<(&'static str, u64)> {
+        EVENTS
+            .lock()
+            .map(|ev| ev.iter().map(|&(name, _, dur)| (name, dur)).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn span(name: &'static str) -> Span {
+        let start = ENABLED.load(Ordering::Relaxed).then(now_us);
+        Span { name, start }
+    }
+
+    pub fn finish() {
+        if !WRITE.load(Ordering::Relaxed) {
+            return;
+        }
+        let events = EVENTS.lock().map(|ev| ev.clone()).unwrap_or_default();
+        if events.is_empty() {
+            return;
+        }
+        let dir = cache::cache_dir();
+        let _ = fs::create_dir_all(&dir);
+        let path = dir.join("trace.json");
+        let fresh = fs::metadata(&path).map(|m| m.len() == 0).unwrap_or(true);
+        let mut out = String::new();
+        if fresh {
+            out.push_str("[\n");
+        }
+        let pid = std::process::id();
+        for (name, ts, dur) in events {
+            out.push_str(&format!(
+                "{{\"name\":\"{}\",\"cat\":\"statusline\",\"ph\":\"X\",\"ts\":{},\"dur\":{},\"pid\":{},\"tid\":1}},\n",
+                name, ts, dur, pid
+            ));
+        }
+        if let Ok(mut f) = OpenOptions::new().create(true).append(true).open(&path) {
+            let _ = f.write_all(out.as_bytes());
+        }
+    }
+}
+
+#[cfg(not(feature = "trace"))]
+mod imp {
+    pub struct Span;
+
+    pub fn init(_enabled: bool) {}
+
+    pub fn span(_name: &'static str) -> Span {
+        Span
+    }
+
+    pub fn profile() {}
+
+    pub fn spans() -> Vec<(&'static str, u64)> {
+        Vec::new()
+    }
+
+    pub fn finish() {}
+}
+
+pub use imp::{finish, init, profile, span, spans};
+
+/// `--self-profile` table: total time and call count per span, slowest first.
+pub fn profile_report() -> String {
+    let spans = spans();
+    if spans.is_empty() {
+        return "no spans recorded (built without the `trace` feature?)\n".to_string();
+    }
+    let mut totals: Vec<(&str, u64, usize)> = Vec::new();
+    for (name, dur) in spans {
+        match totals.iter_mut().find(|(n, _, _)| *n == name) {
+            Some(t) => {
+                t.1 += dur;
+                t.2 += 1;
+            }
+            None => totals.push((name, dur, 1)),
+        }
+    }
+    totals.sort_by_key(|t| std::cmp::Reverse(t.1));
+    let mut out = format!("{:<18} {:>9} {:>6}\n", "span", "ms", "calls");
+    for (name, us, calls) in totals {
+        out.push_str(&format!(
+            "{:<18} {:>9.3} {:>6}\n",
+            name,
+            us as f64 / 1000.0,
+            calls
+        ));
+    }
+    out.push_str("(render includes the spans nested in it)\n");
+    out
+}
@@ -0,0 +1,396 @@
+//! `statusline tui`: full-screen dashboard for the active session (feature `tui`).
+//!
+//! Reads the same data as `statusline watch` (last-session snapshot, model and
+//! cumulative caches) plus the session transcript for todos and tool calls.
+
+use crate::cache;
+use crate::format;
+use crate::session::{self, Session};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Bar, BarChart, BarGroup, Block, Gauge, List, ListItem, Paragraph};
+use ratatui::Frame;
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::time::Duration;
+
+const TICK: Duration = Duration::from_secs(1);
+/// Only the tail of the transcript is scanned for todos and tool calls.
+const TRANSCRIPT_TAIL: u64 = 2 << 20;
+const MAX_TOOL_CALLS: usize = 12;
+
+struct Todo {
+    content: String,
+    status: String,
+}
+
+struct Snapshot {
+    sess: Session,
+    models: Option<cache::ModelStats>,
+    proj: Option<cache::CumulativeStats>,
+    all: Option<cache::CumulativeStats>,
+    todos: Vec<Todo>,
+    tools: Vec<String>,
+}
+
+pub fn run() -> Result<(), String> {
+    let mut terminal = ratatui::try_init().map_err(|e| format!("cannot start TUI: {}", e))?;
+    let result = (|| -> std::io::Result<()> {
+        loop {
+            let snap = load();
+            terminal.draw(|f| draw(f, &snap))?;
+            if event::poll(TICK)? {
+                if let Event::Key(k) = event::read()? {
+                    if quits(&k) {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    })();
+    ratatui::restore();
+    result.map_err(|e| e.to_string())
+}
+
+/// q, Esc and Ctrl-C leave the dashboard.
+fn quits(k: &KeyEvent) -> bool {
+    let ctrl_c = k.code == KeyCode::Char('c') && k.modifiers.contains(KeyModifiers::CONTROL);
+    k.kind == KeyEventKind::Press && (matches!(k.code, KeyCode::Char('q') | KeyCode::Esc) || ctrl_c)
+}
+
+fn load() -> Snapshot {
+    let sess = fs::read(cache::last_session_path())
+        .map(|raw| session::parse(&raw[..]))
+        .unwrap_or_default();
+    let models = cache::read_models(&sess.id());
+    let (proj, all) = cache::read_cumulative(&sess.workspace.project_dir);
+    let (todos, tools) = scan_transcript(&sess.transcript_path);
+    Snapshot {
+        sess,
+        models,
+        proj,
+        all,
+        todos,
+        tools,
+    }
+}
+
+fn draw(f: &mut Frame, s: &Snapshot) {
+    let [header, gauge, middle, bottom] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(3),
+        Constraint::Length(9),
+        Constraint::Min(5),
+    ])
+    .areas(f.area());
+    let [costs, tokens] =
+        Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(middle);
+    let [todos, tools] =
+        Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(bottom);
+
+    let model = s.sess.model.display_name.clone();
+    let model = model.strip_prefix("Claude ").unwrap_or(&model).to_string();
+    let title = format!(
+        "{}  {}  {}  +{} -{}",
+        if model.is_empty() { "?" } else { &model },
+        format::fmt_cost(s.sess.cost.total_cost_usd),
+        format::fmt_duration(s.sess.cost.total_duration_ms as i64),
+        s.sess.cost.total_lines_added as i64,
+        s.sess.cost.total_lines_removed as i64,
+    );
+    f.render_widget(
+        Paragraph::new(title).block(Block::bordered().title(" statusline (q to quit) ")),
+        header,
+    );
+
+    let pct = s.sess.context_window.used_percentage.clamp(0.0, 100.0) as u16;
+    let color = if pct >= 90 {
+        Color::Red
+    } else if pct >= 70 {
+        Color::Yellow
+    } else {
+        Color::Green
+    };
+    f.render_widget(
+        Gauge::default()
+            .block(Block::bordered().title(" Context "))
+            .gauge_style(Style::default().fg(color))
+            .percent(pct),
+        gauge,
+    );
+
+    // Cumulative cost in cents, project next to global
+    let cents = |v: f64| (v * 100.0).max(0.0) as u64;
+    let mut chart = BarChart::default()
+        .block(Block::bordered().title(" Cost (project / all) "))
+        .bar_width(6)
+        .bar_gap(1)
+        .group_gap(3);
+    for (label, p, a) in [
+        (
+            "1d",
+            s.proj.as_ref().map(|c| c.d1),
+            s.all.as_ref().map(|c| c.d1),
+        ),
+        (
+            "7d",
+            s.proj.as_ref().map(|c| c.d7),
+            s.all.as_ref().map(|c| c.d7),
+        ),
+        (
+            "30d",
+            s.proj.as_ref().map(|c| c.d30),
+            s.all.as_ref().map(|c| c.d30),
+        ),
+    ] {
+        let p = p.unwrap_or(0.0);
+        let a = a.unwrap_or(0.0);
+        chart = chart.data(
+            BarGroup::default().label(Line::from(label)).bars(&[
+                Bar::default()
+                    .value(cents(p))
+                    .text_value(format::fmt_cost(p))
+                    .style(Style::default().fg(Color::Magenta)),
+                Bar::default()
+                    .value(cents(a))
+                    .text_value(format::fmt_cost(a))
+                    .style(Style::default().fg(Color::Cyan)),
+            ]),
+        );
+    }
+    f.render_widget(chart, costs);
+
+    let mut rows: Vec<ListItem> = Vec::new();
+    if let Some(ms) = &s.models {
+        for (name, i, o) in [
+            ("Opus", ms.opus_in, ms.opus_out),
+            ("Sonnet", ms.sonnet_in, ms.sonnet_out),
+            ("Haiku", ms.haiku_in, ms.haiku_out),
+        ] {
+            if i > 0 || o > 0 {
+                rows.push(ListItem::new(format!(
+                    "{:<7} in {:>6}  out {:>6}",
+                    name,
+                    format::fmt_k(i),
+                    format::fmt_k(o)
+                )));
+            }
+        }
+    }
+    if rows.is_empty() {
+        rows.push(ListItem::new(format!(
+            "total   in {:>6}  out {:>6}",
+            format::fmt_k(s.sess.context_window.total_input_tokens as i64),
+            format::fmt_k(s.sess.context_window.total_output_tokens as i64)
+        )));
+    }
+    f.render_widget(
+        List::new(rows).block(Block::bordered().title(" Tokens ")),
+        tokens,
+    );
+
+    let todo_items: Vec<ListItem> = s
+        .todos
+        .iter()
+        .map(|t| {
+            let (mark, color) = match t.status.as_str() {
+                "completed" => ("[x]", Color::DarkGray),
+                "in_progress" => ("[~]", Color::Yellow),
+                _ => ("[ ]", Color::White),
+            };
+            ListItem::new(format!("{} {}", mark, t.content)).style(Style::default().fg(color))
+        })
+        .collect();
+    f.render_widget(
+        List::new(todo_items).block(Block::bordered().title(" Todos ")),
+        todos,
+    );
+
+    let tool_items: Vec<ListItem> = s.tools.iter().map(|t| ListItem::new(t.as_str())).collect();
+    f.render_widget(
+        List::new(tool_items).block(Block::bordered().title(" Recent tool calls ")),
+        tools,
+    );
+}
+
+fn scan_transcript(path: &str) -> (Vec<Todo>, Vec<String>) {
+    read_tail(path).map(|data| scan(&data)).unwrap_or_default()
+}
+
+/// Latest TodoWrite list and the most recent tool calls (newest first).
+fn scan(data: &str) -> (Vec<Todo>, Vec<String>) {
+    let mut todos = Vec::new();
+    let mut tools = Vec::new();
+    for line in data.lines() {
+        let entry: Value = match serde_json::from_str(line) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        let content = match entry.pointer("/message/content").and_then(|c| c.as_array()) {
+            Some(c) => c,
+            None => continue,
+        };
+        for item in content {
+            if item.get("type").and_then(|t| t.as_str()) != Some("tool_use") {
+                continue;
+            }
+            let name = item.get("name").and_then(|n| n.as_str()).unwrap_or("?");
+            let input = item.get("input").cloned().unwrap_or(Value::Null);
+            if name == "TodoWrite" {
+                if let Some(list) = input.get("todos").and_then(|t| t.as_array()) {
+                    todos = list
+                        .iter()
+                        .map(|t| Todo {
+                            content: str_field(t, "content"),
+                            status: str_field(t, "status"),
+                        })
+                        .collect();
+                }
+            }
+            let detail = [
+                "command",
+                "file_path",
+                "pattern",
+                "path",
+                "url",
+                "description",
+            ]
+            .iter()
+            .map(|k| str_field(&input, k))
+            .find(|v| !v.is_empty())
+            .unwrap_or_default();
+            let detail = format::truncate(detail.lines().next().unwrap_or(""), 60);
+            tools.push(format!("{} {}", name, detail));
+        }
+    }
+    tools.reverse();
+    tools.truncate(MAX_TOOL_CALLS);
+    (todos, tools)
+}
+
+fn str_field(v: &Value, key: &str) -> String {
+    v.get(key)
+        .and_then(|s| s.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn read_tail(path: &str) -> Option<String> {
+    if path.is_empty() {
+        return None;
+    }
+    let mut f = File::open(path).ok()?;
+    let len = f.metadata().ok()?.len();
+    if len > TRANSCRIPT_TAIL {
+        f.seek(SeekFrom::Start(len - TRANSCRIPT_TAIL)).ok()?;
+    }
+    let mut buf = Vec::new();
+    f.read_to_end(&mut buf).ok()?;
+    Some(String::from_utf8_lossy(&buf).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn quit_keys() {
+        assert!(quits(&key(KeyCode::Char('q'), KeyModifiers::NONE)));
+        assert!(quits(&key(KeyCode::Esc, KeyModifiers::NONE)));
+        assert!(quits(&key(KeyCode::Char('c'), KeyModifiers::CONTROL)));
+        assert!(!quits(&key(KeyCode::Char('c'), KeyModifiers::NONE)));
+        assert!(!quits(&key(KeyCode::Enter, KeyModifiers::NONE)));
+        let mut release = key(KeyCode::Char('q'), KeyModifiers::NONE);
+        release.kind = KeyEventKind::Release;
+        assert!(!quits(&release));
+    }
+
+    fn tool_use(name: &str, input: Value) -> String {
+        serde_json::json!({
+            "type": "assistant",
+            "message": {"content": [{"type": "tool_use", "name": name, "input": input}]}
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn scan_keeps_latest_todos_and_newest_tools_first() {
+        let lines = [
+            tool_use(
+                "TodoWrite",
+                serde_json::json!({"todos": [{"content": "old", "status": "pending"}]}),
+            ),
+            "not json".to_string(),
+            tool_use(
+                "Bash",
+                serde_json::json!({"command": "cargo test\ncargo fmt"}),
+            ),
+            tool_use(
+                "TodoWrite",
+                serde_json::json!({"todos": [
+                    {"content": "write tests", "status": "completed"},
+                    {"content": "ship", "status": "in_progress"}
+                ]}),
+            ),
+            tool_use("Read", serde_json::json!({"file_path": "/src/tui.rs"})),
+        ];
+        let (todos, tools) = scan(&lines.join("\n"));
+        let todos: Vec<_> = todos
+            .iter()
+            .map(|t| (t.content.as_str(), t.status.as_str()))
+            .collect();
+        assert_eq!(
+            todos,
+            [("write tests", "completed"), ("ship", "in_progress")]
+        );
+        assert_eq!(tools[0], "Read /src/tui.rs");
+        assert_eq!(tools[2], "Bash cargo test");
+        assert_eq!(tools.len(), 4);
+
+        let many: Vec<_> = (0..20)
+            .map(|i| tool_use("Grep", serde_json::json!({"pattern": format!("p{}", i)})))
+            .collect();
+        let (_, tools) = scan(&many.join("\n"));
+        assert_eq!(tools.len(), MAX_TOOL_CALLS);
+        assert_eq!(tools[0], "Grep p19");
+    }
+
+    #[test]
+    fn draws_the_snapshot() {
+        let mut sess = Session::default();
+        sess.model.display_name = "Claude Opus 4.6".into();
+        sess.context_window.used_percentage = 42.0;
+        let snap = Snapshot {
+            sess,
+            models: None,
+            proj: None,
+            all: None,
+            todos: vec![Todo {
+                content: "ship".into(),
+                status: "in_progress".into(),
+            }],
+            tools: vec!["Bash ls".into()],
+        };
+        let mut terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+        terminal.draw(|f| draw(f, &snap)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|c| c.symbol())
+            .collect();
+        for part in ["Opus 4.6", "42%", "[~] ship", "Bash ls"] {
+            assert!(screen.contains(part), "{} missing", part);
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use crate::config::Config;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+const REPO: &str = "ridjex/claude-code-statusline";
+
+/// `statusline self-update [--check] [--force] [--version vX.Y.Z]`
+pub fn run(cfg: &Config) -> Result<String, String> {
+    if cfg.offline {
+        return Err("self-update is disabled in offline mode (STATUSLINE_OFFLINE)".into());
+    }
+    let args = &cfg.command_args;
+    let check_only = args.iter().any(|a| a == "--check");
+    let force = args.iter().any(|a| a == "--force");
+    let platform = platform().ok_or("unsupported platform")?;
+
+    let requested = crate::config::arg_value(args, "--version");
+    let explicit = requested.is_some();
+    let tag = match requested {
+        Some(v) => v,
+        None => latest_tag()?,
+    };
+    // The tag goes into the download URL and file names
+    if !valid_tag(&tag) {
+        return Err(format!("not a release version: {}", tag));
+    }
+    let current = crate::VERSION;
+    // A requested version is installed unless it is the running one; the
+    // latest release only when it is newer, so local builds stay put
+    let wanted = if explicit {
+        parse_version(&tag) != parse_version(current)
+    } else {
+        is_newer(&tag, current)
+    };
+    if !force && !wanted {
+        return Ok(format!("[ok] statusline {} is up to date", current));
+    }
+    if check_only {
+        return Ok(format!("[i ] update available: {} -> {}", current, tag));
+    }
+
+    let exe = env::current_exe().map_err(|e| format!("cannot locate binary: {}", e))?;
+    let exe = fs::canonicalize(&exe).unwrap_or(exe);
+    let work = env::temp_dir().join(format!(
+        "claude-code-statusline-update-{}",
+        std::process::id()
+    ));
+    fs::create_dir_all(&work).map_err(|e| format!("cannot create {}: {}", work.display(), e))?;
+    let result = install(&tag, &platform, &work, &exe);
+    let _ = fs::remove_dir_all(&work);
+    result
+}
+
+fn install(tag: &str, platform: &str, work: &Path, exe: &Path) -> Result<String, String> {
+    let name = format!("claude-code-statusline-{}-{}", tag, platform);
+    let tarball = format!("{}.tar.gz", name);
+    let base = format!("https://github.com/{}/releases/download/{}", REPO, tag);
+
+    let tar_path = work.join(&tarball);
+    download(&format!("{}/{}", base, tarball), &tar_path)?;
+    let sums_path = work.join("checksums.txt");
+    download(&format!("{}/checksums.txt", base), &sums_path)?;
+
+    let sums = fs::read_to_string(&sums_path).map_err(|e| e.to_string())?;
+    let expected = expected_sum(&sums, &tarball)
+        .ok_or_else(|| format!("checksum entry not found for {}", tarball))?;
+    let data = fs::read(&tar_path).map_err(|e| e.to_string())?;
+    let actual = format!("{:x}", Sha256::digest(&data));
+    if actual != expected {
+        return Err(format!(
+            "checksum verification FAILED for {} (expected {}, got {})",
+            tarball, expected, actual
+        ));
+    }
+
+    let status = Command::new("tar")
+        .arg("xzf")
+        .arg(&tar_path)
+        .arg("-C")
+        .arg(work)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| format!("cannot run tar: {}", e))?;
+    if !status.success() {
+        return Err(format!("cannot extract {}", tarball));
+    }
+    let new_bin = work
+        .join(&name)
+        .join("engines")
+        .join("rust")
+        .join("statusline");
+    if !new_bin.is_file() {
+        return Err(format!("{} has no rust engine binary", tarball));
+    }
+
+    // Stage next to the target so the final rename stays on one filesystem
+    let staged = exe.with_extension("new");
+    fs::copy(&new_bin, &staged).map_err(|e| format!("cannot write {}: {}", staged.display(), e))?;
+    let _ = fs::set_permissions(&staged, fs::Permissions::from_mode(0o755));
+    fs::rename(&staged, exe).map_err(|e| {
+        let _ = fs::remove_file(&staged);
+        format!("cannot replace {}: {}", exe.display(), e)
+    })?;
+    Ok(format!("[ok] Updated {} to {}", exe.display(), tag))
+}
+
+fn latest_tag() -> Result<String, String> {
+    let url = format!("https://api.github.com/repos/{}/releases/latest", REPO);
+    let out = Command::new("curl")
+        .args(["-fsSL", &url])
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| format!("cannot run curl: {}", e))?;
+    if !out.status.success() {
+        return Err("failed to fetch latest release".into());
+    }
+    let v: serde_json::Value =
+        serde_json::from_slice(&out.stdout).map_err(|_| "invalid release metadata")?;
+    v.get("tag_name")
+        .and_then(|t| t.as_str())
+        .map(|t| t.to_string())
+        .ok_or_else(|| "release has no tag_name".into())
+}
+
+fn download(url: &str, dest: &PathBuf) -> Result<(), String> {
+    let status = Command::new("curl")
+        .args(["-fsSL", "-o"])
+        .arg(dest)
+        .arg(url)
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| format!("cannot run curl: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("download failed: {}", url))
+    }
+}
+
+/// Find the sha256 for `file` in `sha256sum` output.
+fn expected_sum(sums: &str, file: &str) -> Option<String> {
+    sums.lines().find_map(|l| {
+        let mut parts = l.split_whitespace();
+        let sum = parts.next()?;
+        let name = parts.next()?.trim_start_matches('*');
+        (name == file).then(|| sum.to_lowercase())
+    })
+}
+
+/// `major.minor.patch` of a release tag or version (`v2.1.0`, `2.1.0-rc1`).
+/// Pre-release suffixes are ignored.
+fn parse_version(v: &str) -> Option<(u64, u64, u64)> {
+    let v = v.trim().trim_start_matches('v');
+    let v = v.split(['-', '+']).next()?;
+    let mut parts = v.split('.').map(|p| p.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some((major, minor, patch))
+}
+
+/// A release tag: a version such as v2.1.0 or 2.1.0-rc.1, nothing else.
+fn valid_tag(tag: &str) -> bool {
+    parse_version(tag).is_some()
+        && tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'))
+}
+
+/// Whether release `tag` is newer than `current`. An unparseable tag is not.
+fn is_newer(tag: &str, current: &str) -> bool {
+    match (parse_version(tag), parse_version(current)) {
+        (Some(t), Some(c)) => t > c,
+        (Some(_), None) => true,
+        _ => false,
+    }
+}
+
+/// Release platform name, matching install-remote.sh.
+fn platform() -> Option<String> {
+    platform_for(env::consts::OS, env::consts::ARCH)
+}
+
+fn platform_for(os: &str, arch: &str) -> Option<String> {
+    let os = match os {
+        "macos" => "darwin",
+        "linux" => "linux",
+        _ => return None,
+    };
+    let arch = match arch {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        _ => return None,
+    };
+    Some(format!("{}-{}", os, arch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_compare_as_semver() {
+        assert_eq!(parse_version("v2.10.1"), Some((2, 10, 1)));
+        assert_eq!(parse_version("2.1"), Some((2, 1, 0)));
+        assert_eq!(parse_version("2.1.0-rc1"), Some((2, 1, 0)));
+        assert_eq!(parse_version("nightly"), None);
+        assert!(is_newer("v2.10.0", "2.9.3"));
+        assert!(!is_newer("v2.0.0", "2.0.0"));
+        assert!(!is_newer("v1.9.0", "2.0.0"));
+        assert!(!is_newer("latest", "2.0.0"));
+        assert!(valid_tag("v2.1.0-rc.1"));
+        assert!(!valid_tag("v2.1.0/../../evil"));
+        assert!(!valid_tag("v2.1.0 --output /etc/x"));
+        assert!(!valid_tag("latest"));
+    }
+
+    #[test]
+    fn release_platforms() {
+        assert_eq!(
+            platform_for("linux", "x86_64").as_deref(),
+            Some("linux-amd64")
+        );
+        assert_eq!(
+            platform_for("macos", "aarch64").as_deref(),
+            Some("darwin-arm64")
+        );
+        assert_eq!(platform_for("windows", "x86_64"), None);
+        assert_eq!(platform_for("linux", "riscv64"), None);
+    }
+
+    #[test]
+    fn checksum_lines() {
+        let sums = "ABC123  claude-code-statusline-v2.0.0-linux-amd64.tar.gz\n\
+                    def456 *claude-code-statusline-v2.0.0-darwin-arm64.tar.gz\n";
+        assert_eq!(
+            expected_sum(sums, "claude-code-statusline-v2.0.0-linux-amd64.tar.gz").as_deref(),
+            Some("abc123")
+        );
+        assert_eq!(
+            expected_sum(sums, "claude-code-statusline-v2.0.0-darwin-arm64.tar.gz").as_deref(),
+            Some("def456")
+        );
+        assert_eq!(expected_sum(sums, "other.tar.gz"), None);
+    }
+}
@@ -0,0 +1,62 @@
+//! Exports for the WASM build (feature `wasm`), so a web config playground
+//! can preview themes and layouts with sample sessions. Rendering is the C
+//! ABI in `ffi`: the host copies NUL-terminated UTF-8 JSON into buffers from
+//! `statusline_alloc`, sets the time, calls `statusline_render` and hands the
+//! result to `statusline_free`. On wasm32 that render reads no files, no
+//! environment and no git, and spawns nothing: see `embed::render`.
+
+/// A buffer of `len` bytes for the host to write into.
+#[no_mangle]
+pub extern "C" fn statusline_alloc(len: usize) -> *mut u8 {
+    let mut buf = Vec::<u8>::with_capacity(len);
+    let ptr = buf.as_mut_ptr();
+    std::mem::forget(buf);
+    ptr
+}
+
+/// Release a buffer from `statusline_alloc`.
+///
+/// # Safety
+///
+/// `ptr` and `len` must come from one `statusline_alloc` call, released once.
+#[no_mangle]
+pub unsafe extern "C" fn statusline_dealloc(ptr: *mut u8, len: usize) {
+    if !ptr.is_null() {
+        drop(Vec::from_raw_parts(ptr, 0, len));
+    }
+}
+
+/// The current Unix time in seconds for the renders that follow, since
+/// wasm32 has no clock of its own.
+#[no_mangle]
+pub extern "C" fn statusline_set_now(now: i64) {
+    crate::clock::set_now(now);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::{statusline_free, statusline_render};
+    use std::ffi::CStr;
+
+    #[test]
+    fn render_from_host_buffers() {
+        let session = b"{\"model\": {\"display_name\": \"Claude Haiku 4.5\"}}\0";
+        let options = b"{\"layout\": \"minimal\", \"show_git\": false}\0";
+        unsafe {
+            let buf = statusline_alloc(session.len());
+            std::ptr::copy_nonoverlapping(session.as_ptr(), buf, session.len());
+            statusline_set_now(1);
+            let out = statusline_render(buf.cast(), options.as_ptr().cast());
+            let text = CStr::from_ptr(out).to_string_lossy().into_owned();
+            assert!(
+                crate::render::strip_ansi(&text).starts_with("H 0%"),
+                "{:?}",
+                text
+            );
+            statusline_free(out);
+            statusline_dealloc(buf, session.len());
+        }
+        statusline_set_now(0);
+    }
+}
@@ -0,0 +1,89 @@
+use crate::cache;
+use crate::config::{self, Config};
+use crate::render;
+use crate::session::{self, Session};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// `statusline watch [--interval 2s]`: standalone dashboard that re-renders
+/// the most recent session (saved by the last render) in a spare pane.
+pub fn run(cfg: &Config) {
+    let interval = config::arg_value(&cfg.command_args, "--interval")
+        .and_then(|v| parse_interval(&v))
+        .unwrap_or(DEFAULT_INTERVAL);
+    let mut out = io::stdout();
+    loop {
+        let frame = frame(cfg);
+        // Home + clear screen, then the frame
+        let _ = write!(out, "\x1b[H\x1b[2J{}", frame);
+        let _ = out.flush();
+        thread::sleep(interval);
+    }
+}
+
+fn frame(cfg: &Config) -> String {
+    match latest() {
+        Some(sess) => render::render(&sess, cfg),
+        None => "waiting for a Claude Code session\u{2026}\n".to_string(),
+    }
+}
+
+/// The most recent session (saved by the last render), with the working
+/// directory moved to its project so git state is read from there.
+pub fn latest() -> Option<Session> {
+    let raw = fs::read(cache::last_session_path()).ok()?;
+    let sess = session::parse(&raw[..]);
+    // Git state belongs to the session's directory, not the watcher's
+    let dir = if !sess.cwd.is_empty() {
+        &sess.cwd
+    } else {
+        &sess.workspace.current_dir
+    };
+    if !dir.is_empty() && Path::new(dir).is_dir() {
+        let _ = std::env::set_current_dir(dir);
+    }
+    Some(sess)
+}
+
+/// Parse `2s`, `500ms`, `1m` or plain seconds.
+fn parse_interval(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let d = if let Some(ms) = s.strip_suffix("ms") {
+        Duration::from_millis(ms.parse().ok()?)
+    } else if let Some(m) = s.strip_suffix('m') {
+        Duration::from_secs(m.parse::<u64>().ok()?.checked_mul(60)?)
+    } else {
+        // Negative, NaN and infinite seconds are rejected, not a panic
+        Duration::try_from_secs_f64(s.strip_suffix('s').unwrap_or(s).parse().ok()?).ok()?
+    };
+    (!d.is_zero()).then_some(d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intervals() {
+        assert_eq!(parse_interval("2s"), Some(Duration::from_secs(2)));
+        assert_eq!(parse_interval("0.5"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_interval("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_interval("1m"), Some(Duration::from_secs(60)));
+        for bad in [
+            "-1",
+            "nan",
+            "inf",
+            "0",
+            "1e30",
+            "soon",
+            "307445734561825861m",
+        ] {
+            assert_eq!(parse_interval(bad), None, "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,335 @@
+use crate::config::{self, Config};
+use crate::render;
+use crate::segments::SEGMENTS;
+use crate::session;
+use std::fs;
+use std::io::{self, BufRead, Write};
+
+/// Sample payload used for the preview (mirrors tests/fixtures/basic-session.json).
+pub const SAMPLE_SESSION: &str = r#"{
+  "model": {"id": "claude-opus-4-6", "display_name": "Claude Opus 4.6"},
+  "context_window": {"used_percentage": 38, "context_window_size": 200000,
+    "total_input_tokens": 287500, "total_output_tokens": 41200},
+  "cost": {"total_cost_usd": 8.42, "total_duration_ms": 900000,
+    "total_api_duration_ms": 600000, "total_lines_added": 127, "total_lines_removed": 34}
+}"#;
+
+pub const SETTINGS_SNIPPET: &str = r#"{
+  "statusLine": {
+    "type": "command",
+    "command": "~/.claude/statusline.sh",
+    "padding": 0
+  }
+}"#;
+
+/// Built-in layouts offered by the theme step, as STATUSLINE_LAYOUT values.
+const LAYOUTS: &[&str] = &["default", "classic", "swapped", "dense", "minimal"];
+
+/// `statusline init`: ask which segments and theme to show, preview, and
+/// merge the answers into statusline.env.
+pub fn run() {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut out = io::stdout();
+
+    // Seed from the file alone: a value set only in the environment would
+    // otherwise be written into statusline.env
+    let mut cfg = config::from_options(&config::file_values());
+    let _ = writeln!(out, "Claude Code Status Line — setup\n");
+
+    for seg in SEGMENTS.iter().filter(|s| s.line == 1) {
+        let on = ask(&mut input, &mut out, seg.description, (seg.get)(&cfg));
+        (seg.set)(&mut cfg, on);
+    }
+    cfg.line2 = ask(
+        &mut input,
+        &mut out,
+        "Second line (tokens, speed, cumulative)",
+        cfg.line2,
+    );
+    if cfg.line2 {
+        for seg in SEGMENTS.iter().filter(|s| s.line == 2) {
+            let on = ask(&mut input, &mut out, seg.description, (seg.get)(&cfg));
+            (seg.set)(&mut cfg, on);
+        }
+    }
+
+    // Theme: layout and symbol set
+    let current = if cfg.layout.is_empty() {
+        "default"
+    } else {
+        &cfg.layout
+    };
+    cfg.layout = choose(&mut input, &mut out, "Layout", LAYOUTS, current).to_string();
+    let ascii = ask(
+        &mut input,
+        &mut out,
+        "Plain ASCII symbols (proj, all, |) instead of \u{2302} \u{03a3} \u{2502}",
+        has_ascii(&cfg.symbols),
+    );
+    cfg.symbols = with_ascii(&cfg.symbols, ascii);
+
+    let sample = session::parse(SAMPLE_SESSION.as_bytes());
+    let _ = writeln!(out, "\nPreview:\n");
+    let _ = write!(out, "{}", render::render(&sample, &cfg));
+
+    let path = config::config_path();
+    let prompt = format!("\nWrite {}", path.display());
+    if !ask(&mut input, &mut out, &prompt, true) {
+        let _ = writeln!(out, "Nothing written.");
+        return;
+    }
+
+    let existing = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            let _ = writeln!(out, "[!!] Cannot read {}: {}", path.display(), e);
+            return;
+        }
+    };
+    if path.exists() {
+        let backup = path.with_extension("env.bak");
+        if let Err(e) = fs::copy(&path, &backup) {
+            let _ = writeln!(out, "[!!] Cannot back up to {}: {}", backup.display(), e);
+            return;
+        }
+        let _ = writeln!(out, "[ok] Backed up to {}", backup.display());
+    }
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    match fs::write(&path, env_file_contents(&existing, &cfg)) {
+        Ok(_) => {
+            let _ = writeln!(out, "[ok] Wrote {}", path.display());
+        }
+        Err(e) => {
+            let _ = writeln!(out, "[!!] Cannot write {}: {}", path.display(), e);
+            return;
+        }
+    }
+
+    let _ = writeln!(
+        out,
+        "\nAdd this to ~/.claude/settings.json (or run `statusline install-hook`):\n\n{}",
+        SETTINGS_SNIPPET
+    );
+}
+
+/// The keys the wizard sets, in file order: segment toggles and theme.
+fn answers(cfg: &Config) -> Vec<(&'static str, String)> {
+    let mut keys = Vec::new();
+    for seg in SEGMENTS.iter().filter(|s| s.line == 1) {
+        keys.push((seg.env_key, (seg.get)(cfg).to_string()));
+    }
+    keys.push(("STATUSLINE_LINE2", cfg.line2.to_string()));
+    for seg in SEGMENTS.iter().filter(|s| s.line == 2) {
+        keys.push((seg.env_key, (seg.get)(cfg).to_string()));
+    }
+    keys.push(("STATUSLINE_LAYOUT", cfg.layout.clone()));
+    keys.push(("STATUSLINE_SYMBOLS", cfg.symbols.clone()));
+    keys
+}
+
+/// Merge the wizard's answers into `existing` statusline.env text: lines
+/// setting one of its keys are rewritten in place (keeping `export`), every
+/// other line and comment is kept, and keys not there yet are appended.
+/// Empty values are only written over an existing line.
+pub fn env_file_contents(existing: &str, cfg: &Config) -> String {
+    let answers = answers(cfg);
+    let mut s = if existing.is_empty() {
+        String::from("# Claude Code Status Line — generated by `statusline init`\n")
+    } else {
+        String::new()
+    };
+    let mut written = Vec::new();
+    for line in existing.lines() {
+        let answer = line_key(line).and_then(|k| answers.iter().find(|(key, _)| *key == k));
+        match answer {
+            Some((key, value)) => {
+                let export = if line.trim_start().starts_with("export ") {
+                    "export "
+                } else {
+                    ""
+                };
+                s.push_str(&format!("{}{}={}\n", export, key, quote(value)));
+                written.push(*key);
+            }
+            None => {
+                s.push_str(line);
+                s.push('\n');
+            }
+        }
+    }
+    for (key, value) in &answers {
+        if !written.contains(key) && !value.is_empty() {
+            s.push_str(&format!("{}={}\n", key, quote(value)));
+        }
+    }
+    s
+}
+
+/// The key a `KEY=value` line sets, if it is one.
+fn line_key(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    let line = line.strip_prefix("export ").unwrap_or(line);
+    let (key, _) = line.split_once('=')?;
+    let key = key.trim();
+    (!key.is_empty() && !key.starts_with('#')).then_some(key)
+}
+
+/// Single-quote values with characters the shell would read differently,
+/// closing and reopening the quotes around any `'` inside.
+fn quote(value: &str) -> String {
+    if value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_-.,/=:".contains(c))
+    {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+fn has_ascii(symbols: &str) -> bool {
+    symbols.split(',').any(|item| item.trim() == "ascii")
+}
+
+/// STATUSLINE_SYMBOLS with the `ascii` item added or removed, keeping any
+/// `key=text` overrides.
+fn with_ascii(symbols: &str, ascii: bool) -> String {
+    let mut items: Vec<&str> = symbols
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty() && *item != "ascii")
+        .collect();
+    if ascii {
+        items.insert(0, "ascii");
+    }
+    items.join(",")
+}
+
+/// Ask for one of `choices`; empty, unknown input or EOF keeps the default.
+fn choose<'a>(
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+    prompt: &str,
+    choices: &[&'a str],
+    default: &'a str,
+) -> &'a str {
+    let _ = write!(out, "{} ({})? [{}] ", prompt, choices.join("/"), default);
+    let _ = out.flush();
+    let mut line = String::new();
+    if input.read_line(&mut line).unwrap_or(0) == 0 {
+        let _ = writeln!(out);
+        return default;
+    }
+    let answer = line.trim().to_lowercase();
+    choices
+        .iter()
+        .find(|c| **c == answer)
+        .copied()
+        .unwrap_or(default)
+}
+
+/// Ask a yes/no question; empty input or EOF keeps the default.
+fn ask(input: &mut impl BufRead, out: &mut impl Write, prompt: &str, default: bool) -> bool {
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+    let _ = write!(out, "{}? {} ", prompt, hint);
+    let _ = out.flush();
+    let mut line = String::new();
+    if input.read_line(&mut line).unwrap_or(0) == 0 {
+        let _ = writeln!(out);
+        return default;
+    }
+    match line.trim().to_lowercase().as_str() {
+        "y" | "yes" => true,
+        "n" | "no" => false,
+        _ => default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn answers_merge_into_the_existing_file() {
+        let existing = "# my settings\n\
+                        export STATUSLINE_SHOW_COST=true\n\
+                        STATUSLINE_BUDGET=20 # per session\n\
+                        include = [work.env]\n\
+                        STATUSLINE_SYMBOLS='sep=/'\n";
+        let mut cfg = config::from_options(&HashMap::new());
+        cfg.show_cost = false;
+        cfg.layout = "dense".into();
+        cfg.symbols = with_ascii("sep=/", true);
+        let merged = env_file_contents(existing, &cfg);
+        let lines: Vec<&str> = merged.lines().collect();
+        assert_eq!(lines[0], "# my settings");
+        assert_eq!(lines[1], "export STATUSLINE_SHOW_COST=false");
+        assert_eq!(lines[2], "STATUSLINE_BUDGET=20 # per session");
+        assert_eq!(lines[3], "include = [work.env]");
+        assert_eq!(lines[4], "STATUSLINE_SYMBOLS=ascii,sep=/");
+        assert!(lines.contains(&"STATUSLINE_LAYOUT=dense"));
+        assert!(lines.contains(&"STATUSLINE_SHOW_MODEL=true"));
+        assert_eq!(merged.matches("STATUSLINE_SHOW_COST=").count(), 1);
+
+        // A second run with the same answers changes nothing
+        assert_eq!(env_file_contents(&merged, &cfg), merged);
+    }
+
+    #[test]
+    fn new_file_skips_empty_answers() {
+        let cfg = config::from_options(&HashMap::new());
+        let text = env_file_contents("", &cfg);
+        assert!(text.starts_with("# Claude Code Status Line"));
+        assert!(!text.contains("STATUSLINE_SYMBOLS"));
+        assert!(!text.contains("STATUSLINE_LAYOUT"));
+        assert_eq!(with_ascii("ascii, sep=/", false), "sep=/");
+    }
+
+    #[test]
+    fn quote_escapes_single_quotes() {
+        assert_eq!(quote("ascii,sep=/"), "ascii,sep=/");
+        assert_eq!(quote("a b"), "'a b'");
+        assert_eq!(quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn choose_keeps_the_default() {
+        let mut out = Vec::new();
+        assert_eq!(
+            choose(
+                &mut "dense\n".as_bytes(),
+                &mut out,
+                "Layout",
+                LAYOUTS,
+                "default"
+            ),
+            "dense"
+        );
+        assert_eq!(
+            choose(
+                &mut "fancy\n".as_bytes(),
+                &mut out,
+                "Layout",
+                LAYOUTS,
+                "minimal"
+            ),
+            "minimal"
+        );
+        assert_eq!(
+            choose(&mut "".as_bytes(), &mut out, "Layout", LAYOUTS, "default"),
+            "default"
+        );
+    }
+
+    #[test]
+    fn offers_every_built_in_layout() {
+        let schema = crate::schema::schema();
+        let layouts = &schema["properties"]["STATUSLINE_LAYOUT"]["enum"];
+        assert_eq!(layouts, &serde_json::json!(LAYOUTS));
+    }
+}
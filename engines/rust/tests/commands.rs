@@ -0,0 +1,113 @@
+//! Subcommands through the binary's own argument handling and dispatch.
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const BIN: &str = env!("CARGO_BIN_EXE_statusline");
+
+fn run(args: &[&str]) -> Output {
+    let home = env::temp_dir().join(format!("statusline-commands-{}", std::process::id()));
+    fs::create_dir_all(&home).unwrap();
+    Command::new(BIN)
+        .env_clear()
+        .env("PATH", env::var("PATH").unwrap_or_default())
+        .env("HOME", &home)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Render `input` with a fresh HOME and the given settings.
+fn render(home: &Path, vars: &[(&str, &str)], input: &[u8]) -> Output {
+    let mut child = Command::new(BIN)
+        .env_clear()
+        .env("PATH", env::var("PATH").unwrap_or_default())
+        .env("HOME", home)
+        .envs(vars.iter().copied())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn subcommand_flags_are_not_global() {
+    // --check stops before any download
+    let out = run(&["self-update", "--version", "v9.9.9", "--check"]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success());
+    assert!(stdout.contains("-> v9.9.9"), "{}", stdout);
+    assert!(!stdout.contains("(rust)"), "{}", stdout);
+
+    let out = run(&["--version"]);
+    assert!(String::from_utf8_lossy(&out.stdout).ends_with("(rust)\n"));
+}
+
+#[test]
+fn self_update_refuses_odd_tags() {
+    let out = run(&["self-update", "--version", "v9.9.9/../../x", "--check"]);
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("not a release version"), "{}", stderr);
+}
+
+#[test]
+fn help_lines_keep_their_indentation() {
+    let out = run(&["--help"]);
+    let help = String::from_utf8_lossy(&out.stderr);
+    // A line without its `\n\` continuation carries the source indentation
+    for line in help.lines() {
+        assert!(!line.starts_with("          "), "{:?}", line);
+    }
+    assert!(help.contains("\n  --explain "));
+}
+
+#[test]
+fn replayed_renders_count_as_activity() {
+    let home = env::temp_dir().join(format!("statusline-activity-{}", std::process::id()));
+    let cache = home.join(".cache/claude-code-statusline");
+    fs::create_dir_all(&cache).unwrap();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let activity = cache.join("activity.json");
+    let last = || -> i64 {
+        let data: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&activity).unwrap()).unwrap();
+        data["last"].as_i64().unwrap()
+    };
+    let vars = [
+        ("STATUSLINE_RENDER_CACHE", "true"),
+        ("STATUSLINE_BREAK_AFTER", "30"),
+        ("NO_COLOR", "1"),
+    ];
+    let input = br#"{"model":{"display_name":"Opus"},"session_id":"abc"}"#;
+
+    fs::write(
+        &activity,
+        format!(r#"{{"start":{},"last":{}}}"#, now - 3600, now - 60),
+    )
+    .unwrap();
+    let first = render(&home, &vars, input);
+    assert!(String::from_utf8_lossy(&first.stdout).contains("\u{2615} 1h"));
+    assert!(last() >= now);
+
+    // The same input again is answered from the render cache
+    fs::write(
+        &activity,
+        format!(r#"{{"start":{},"last":{}}}"#, now - 3600, now - 120),
+    )
+    .unwrap();
+    let second = render(&home, &vars, input);
+    assert_eq!(second.stdout, first.stdout);
+    assert!(last() >= now);
+    let _ = fs::remove_dir_all(&home);
+}
@@ -0,0 +1,269 @@
+//! Graceful degradation matrix: with any data source missing or broken (no
+//! git, no or corrupt caches, empty or invalid session JSON, no HOME) the
+//! binary still prints both status lines, exits 0 and logs no panic. The
+//! panic hook would hide a crash behind a fallback line; these runs check the
+//! debug log so they fail on the panic itself.
+
+use md5::{Digest, Md5};
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+const BIN: &str = env!("CARGO_BIN_EXE_statusline");
+
+/// Every opt-in that reads a data source, with values that make it look.
+const OPT_INS: &[(&str, &str)] = &[
+    ("STATUSLINE_SPARKLINE", "true"),
+    ("STATUSLINE_MOOD", "true"),
+    ("STATUSLINE_HISTORY", "true"),
+    ("STATUSLINE_FILES", "true"),
+    ("STATUSLINE_TICKET", "true"),
+    ("STATUSLINE_SIGNED", "true"),
+    ("STATUSLINE_COMMITS_TODAY", "true"),
+    ("STATUSLINE_STASH_DETAIL", "age"),
+    ("STATUSLINE_LARGE_FILE_MB", "1"),
+    ("STATUSLINE_LFS_POINTERS", "true"),
+    ("STATUSLINE_CI", "true"),
+    ("STATUSLINE_AWAITING_SECS", "1"),
+    ("STATUSLINE_HIDE_WHEN_ZERO", "cost,context=dim,tokens"),
+    ("STATUSLINE_RENDER_CACHE", "true"),
+    ("STATUSLINE_STALE_SECS", "1"),
+];
+
+const FLAG_SETS: &[&[&str]] = &[
+    &[],
+    &["--no-git"],
+    &["--no-line2"],
+    &["--no-color"],
+    &["--layout", "minimal"],
+];
+
+fn fixture(name: &str) -> Vec<u8> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../tests/fixtures");
+    fs::read(dir.join(name)).unwrap_or_else(|e| panic!("fixture {}: {}", name, e))
+}
+
+/// Session payloads from valid to garbage.
+fn inputs() -> Vec<(&'static str, Vec<u8>)> {
+    let basic = fixture("basic-session.json");
+    let half = basic[..basic.len() / 2].to_vec();
+    vec![
+        ("basic", basic),
+        ("zero", fixture("zero-session.json")),
+        ("minimal", fixture("minimal.json")),
+        ("extreme", fixture("extreme-tokens.json")),
+        ("truncated", half),
+        ("empty", Vec::new()),
+        ("object", b"{}".to_vec()),
+        ("null", b"null".to_vec()),
+        ("array", b"[]".to_vec()),
+        ("text", b"not json".to_vec()),
+        ("binary", vec![0, 0xff, 0xfe, b'{']),
+        (
+            "wrong types",
+            br#"{"model":7,"cost":"x","context_window":[]}"#.to_vec(),
+        ),
+    ]
+}
+
+/// A fresh directory for one case.
+fn scratch(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!(
+        "statusline-degradation-{}-{}",
+        std::process::id(),
+        name
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+struct Env<'a> {
+    cwd: &'a Path,
+    /// None runs without HOME; caches then land in `<cwd>/.cache`
+    home: Option<&'a Path>,
+    vars: &'a [(&'a str, &'a str)],
+}
+
+/// Render `input` and check the output shape and the debug log.
+fn render(case: &str, input: &[u8], args: &[&str], env: &Env) -> String {
+    let mut cmd = Command::new(BIN);
+    cmd.env_clear()
+        .env("PATH", env::var("PATH").unwrap_or_default())
+        // Hermetic: no background jobs outliving the case, no network
+        .env("STATUSLINE_NO_SPAWN", "true")
+        .env("STATUSLINE_OFFLINE", "true")
+        .envs(env.vars.iter().copied())
+        .args(args)
+        .current_dir(env.cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    if let Some(home) = env.home {
+        cmd.env("HOME", home);
+    }
+    let mut child = cmd.spawn().unwrap();
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    let out = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8_lossy(&out.stdout).to_string();
+
+    assert!(out.status.success(), "{}: exit {}", case, out.status);
+    assert!(
+        !stdout.lines().next().unwrap_or_default().trim().is_empty(),
+        "{}: empty first line",
+        case
+    );
+    assert_eq!(stdout.matches('\n').count(), 2, "{}: {:?}", case, stdout);
+    let log = env
+        .home
+        .unwrap_or(env.cwd)
+        .join(".cache/claude-code-statusline/debug.log");
+    let log = fs::read_to_string(log).unwrap_or_default();
+    assert!(!log.contains("panic"), "{}: {}", case, log);
+    stdout
+}
+
+/// Every input under every flag set, with and without the opt-ins.
+fn matrix(name: &str, env: &Env) {
+    for (input_name, input) in inputs() {
+        for args in FLAG_SETS {
+            for vars in [env.vars, OPT_INS] {
+                let case = format!(
+                    "{} / {} / {:?} / {} vars",
+                    name,
+                    input_name,
+                    args,
+                    vars.len()
+                );
+                let out = render(&case, &input, args, &Env { vars, ..*env });
+                if input_name == "basic" && !args.contains(&"--layout") {
+                    assert!(out.contains("Opus 4.6"), "{}: {:?}", case, out);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn renders_without_caches() {
+    let dir = scratch("no-caches");
+    let home = dir.join("home");
+    fs::create_dir_all(&home).unwrap();
+    matrix(
+        "no caches",
+        &Env {
+            cwd: &dir,
+            home: Some(&home),
+            vars: &[],
+        },
+    );
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn renders_without_home() {
+    let dir = scratch("no-home");
+    matrix(
+        "no HOME",
+        &Env {
+            cwd: &dir,
+            home: None,
+            vars: &[],
+        },
+    );
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn renders_with_corrupt_caches() {
+    let dir = scratch("corrupt");
+    let home = dir.join("home");
+    let cache = home.join(".cache/claude-code-statusline");
+    fs::create_dir_all(&cache).unwrap();
+    // bash: echo "$slug" | md5, first 8 hex digits
+    let slug = "tmp-statusline-test-project\n";
+    let hash = format!("{:x}", Md5::digest(slug.as_bytes()))[..8].to_string();
+    for name in [
+        "models-test-session-basic.json".to_string(),
+        format!("proj-{}.json", hash),
+        format!("deadline-{}.json", hash),
+        "all.json".into(),
+        "render-cache".into(),
+        "last-session.json".into(),
+        "activity.json".into(),
+    ] {
+        fs::write(cache.join(name), b"{\"models\": [garbage").unwrap();
+    }
+    matrix(
+        "corrupt caches",
+        &Env {
+            cwd: &dir,
+            home: Some(&home),
+            vars: &[],
+        },
+    );
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn renders_in_odd_git_states() {
+    if Command::new("git").arg("--version").output().is_err() {
+        return;
+    }
+    let dir = scratch("git");
+    let home = dir.join("home");
+    fs::create_dir_all(&home).unwrap();
+    let git = |repo: &Path, args: &[&str]| {
+        let ok = Command::new("git")
+            .args(args)
+            .current_dir(repo)
+            .env("HOME", &home)
+            .env("GIT_AUTHOR_NAME", "t")
+            .env("GIT_AUTHOR_EMAIL", "t@t")
+            .env("GIT_COMMITTER_NAME", "t")
+            .env("GIT_COMMITTER_EMAIL", "t@t")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|s| s.success());
+        assert!(ok, "git {:?}", args);
+    };
+
+    // No commits yet: HEAD points at an unborn branch
+    let unborn = dir.join("unborn");
+    fs::create_dir_all(&unborn).unwrap();
+    git(&unborn, &["init", "-q"]);
+
+    // Detached HEAD with a dirty tree
+    let detached = dir.join("detached");
+    fs::create_dir_all(&detached).unwrap();
+    git(&detached, &["init", "-q"]);
+    fs::write(detached.join("a.txt"), "a").unwrap();
+    git(&detached, &["add", "a.txt"]);
+    git(&detached, &["commit", "-qm", "a"]);
+    git(&detached, &["checkout", "-q", "--detach"]);
+    fs::write(detached.join("a.txt"), "b").unwrap();
+
+    // A git dir that is not a repository
+    let broken = dir.join("broken");
+    fs::create_dir_all(broken.join(".git")).unwrap();
+    fs::write(broken.join(".git/HEAD"), "garbage").unwrap();
+
+    for (name, cwd) in [
+        ("unborn HEAD", &unborn),
+        ("detached HEAD", &detached),
+        ("broken .git", &broken),
+    ] {
+        matrix(
+            name,
+            &Env {
+                cwd,
+                home: Some(&home),
+                vars: &[],
+            },
+        );
+    }
+    let _ = fs::remove_dir_all(&dir);
+}